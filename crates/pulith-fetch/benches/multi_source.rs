@@ -8,8 +8,8 @@ use bytes::Bytes;
 use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
 use futures_util::stream;
 use pulith_fetch::{
-    BoxStream, DownloadSource, Fetcher, HttpClient, MultiSourceFetcher, MultiSourceOptions,
-    SourceSelectionStrategy,
+    BoxStream, DownloadSource, FetchOptions, Fetcher, HttpClient, MultiSourceFetcher,
+    MultiSourceOptions, SourceSelectionStrategy,
 };
 
 #[derive(Debug, Clone)]
@@ -237,6 +237,7 @@ fn bench_priority_success_first(c: &mut Criterion) {
                                     context.sources,
                                     &destination,
                                     context.options,
+                                    &FetchOptions::default(),
                                 )
                                 .await
                                 .unwrap();
@@ -272,6 +273,7 @@ fn bench_priority_fallback(c: &mut Criterion) {
                                     context.sources,
                                     &destination,
                                     context.options,
+                                    &FetchOptions::default(),
                                 )
                                 .await
                                 .unwrap();
@@ -307,6 +309,7 @@ fn bench_race_one_success(c: &mut Criterion) {
                                     context.sources,
                                     &destination,
                                     context.options,
+                                    &FetchOptions::default(),
                                 )
                                 .await
                                 .unwrap();
@@ -4,9 +4,10 @@
 //! support following RFC 7234.
 
 use crate::error::{Error, Result};
+use crate::net::credentials::url_digest;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::fs;
 use tokio::sync::RwLock;
@@ -40,6 +41,9 @@ impl Default for CacheConfig {
 pub struct CacheEntry {
     /// The URL that was cached
     pub url: String,
+    /// SHA-256 of the requested URL, which `url` may show without credentials
+    #[serde(default)]
+    pub url_sha256: String,
     /// ETag from the server
     pub etag: Option<String>,
     /// Last-Modified from the server
@@ -141,7 +145,28 @@ impl Cache {
         }
     }
 
+    /// Get the entry for `url` even if it is expired, for revalidation.
+    pub async fn peek(&self, url: &str) -> Option<CacheEntry> {
+        self.entries.read().await.get(url).cloned()
+    }
+
+    /// Configuration this cache was opened with.
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Path of the cached content for `url`.
+    pub fn content_path(&self, url: &str) -> PathBuf {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(url.as_bytes());
+        let hash = hex::encode(hasher.finalize());
+        self.config.dir.join(format!("{}.cache", hash))
+    }
+
     /// Store content in the cache.
+    ///
+    /// Content larger than `max_size` is not cached.
     pub async fn put(
         &self,
         url: String,
@@ -163,6 +188,7 @@ impl Cache {
             .as_secs();
 
         let entry = CacheEntry {
+            url_sha256: url_digest(&url),
             url: url.clone(),
             etag,
             last_modified,
@@ -175,32 +201,117 @@ impl Cache {
             no_cache,
         };
 
-        // Check if we need to evict entries
-        if let Some(max_size) = self.config.max_size {
-            let current_size = *self.current_size.read().await;
-            if current_size + entry.size > max_size {
-                self.evict_lru(entry.size).await?;
-            }
+        if !self.admit(&entry).await? {
+            return Ok(());
         }
 
         // Write content to file
-        let cache_file = self.cache_file_path(&url);
+        let cache_file = self.content_path(&url);
         fs::write(&cache_file, content)
             .await
             .map_err(|e| Error::Network(format!("Failed to write cache file: {}", e)))?;
 
-        // Update metadata
+        self.commit(entry).await
+    }
+
+    /// Store a copy of the file at `path` under `entry`.
+    ///
+    /// `entry.size` and `entry.checksum` must describe the file. Files larger
+    /// than `max_size` are not cached.
+    pub async fn put_file(&self, entry: CacheEntry, path: &Path) -> Result<()> {
+        if !self.admit(&entry).await? {
+            return Ok(());
+        }
+
+        fs::copy(path, self.content_path(&entry.url))
+            .await
+            .map_err(|e| Error::Network(format!("Failed to write cache file: {}", e)))?;
+
+        self.commit(entry).await
+    }
+
+    /// Mark the entry for `url` as confirmed by the server just now.
+    pub async fn refresh(&self, url: &str, max_age: Option<u64>, no_cache: bool) -> Result<()> {
+        {
+            let mut entries = self.entries.write().await;
+            let Some(entry) = entries.get_mut(url) else {
+                return Ok(());
+            };
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            entry.cached_at = now;
+            entry.last_accessed = now;
+            entry.access_count += 1;
+            entry.max_age = max_age;
+            entry.no_cache = no_cache;
+        }
+
+        if self.config.persist_metadata {
+            self.save_metadata().await?;
+        }
+        Ok(())
+    }
+
+    /// Record a hit on the entry for `url` for LRU ordering.
+    pub async fn touch(&self, url: &str) -> Result<()> {
+        self.update_access(url).await;
+        if self.config.persist_metadata {
+            self.save_metadata().await?;
+        }
+        Ok(())
+    }
+
+    /// Drop the entry for `url` and its content.
+    pub async fn remove(&self, url: &str) -> Result<()> {
+        if let Some(entry) = self.entries.write().await.remove(url) {
+            let mut current_size = self.current_size.write().await;
+            *current_size = current_size.saturating_sub(entry.size);
+        }
+        let _ = fs::remove_file(self.content_path(url)).await;
+
+        if self.config.persist_metadata {
+            self.save_metadata().await?;
+        }
+        Ok(())
+    }
+
+    /// Evict entries so `entry` fits; `false` if it can never fit.
+    async fn admit(&self, entry: &CacheEntry) -> Result<bool> {
+        let Some(max_size) = self.config.max_size else {
+            return Ok(true);
+        };
+        if entry.size > max_size {
+            return Ok(false);
+        }
+
+        let replaced = self
+            .entries
+            .read()
+            .await
+            .get(&entry.url)
+            .map_or(0, |old| old.size);
+        let projected = (*self.current_size.read().await).saturating_sub(replaced) + entry.size;
+        if projected > max_size {
+            self.evict_lru(projected - max_size, &entry.url).await?;
+        }
+        Ok(true)
+    }
+
+    /// Record `entry` whose content has been written.
+    async fn commit(&self, entry: CacheEntry) -> Result<()> {
         {
             let mut entries = self.entries.write().await;
             let mut current_size = self.current_size.write().await;
 
             // Remove old entry if exists
-            if let Some(old_entry) = entries.remove(&url) {
+            if let Some(old_entry) = entries.remove(&entry.url) {
                 *current_size = current_size.saturating_sub(old_entry.size);
             }
 
-            entries.insert(url.clone(), entry.clone());
             *current_size += entry.size;
+            entries.insert(entry.url.clone(), entry);
         }
 
         // Persist metadata if enabled
@@ -221,11 +332,9 @@ impl Cache {
         let entries = self.entries.read().await;
 
         if let Some(entry) = entries.get(url) {
-            // Check ETag
-            if let (Some(cached_etag), Some(server_etag)) = (&entry.etag, server_etag)
-                && cached_etag == server_etag
-            {
-                return Ok(true);
+            // An ETag on both sides decides on its own
+            if let (Some(cached_etag), Some(server_etag)) = (&entry.etag, server_etag) {
+                return Ok(cached_etag == server_etag);
             }
 
             // Check Last-Modified
@@ -240,15 +349,6 @@ impl Cache {
         Ok(false)
     }
 
-    /// Get the cache file path for a URL.
-    fn cache_file_path(&self, url: &str) -> PathBuf {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(url.as_bytes());
-        let hash = hex::encode(hasher.finalize());
-        self.config.dir.join(format!("{}.cache", hash))
-    }
-
     /// Update access statistics for an entry.
     async fn update_access(&self, url: &str) {
         let mut entries = self.entries.write().await;
@@ -261,14 +361,14 @@ impl Cache {
         }
     }
 
-    /// Evict least recently used entries to make space.
-    async fn evict_lru(&self, needed_space: u64) -> Result<()> {
+    /// Evict least recently used entries other than `keep` to free `needed_space` bytes.
+    async fn evict_lru(&self, needed_space: u64, keep: &str) -> Result<()> {
         let mut entries = self.entries.write().await;
         let mut current_size = self.current_size.write().await;
 
         // Collect entries sorted by last accessed time
-        let mut sorted_entries: Vec<_> = entries.iter().collect();
-        sorted_entries.sort_by_key(|(_, entry)| entry.last_accessed);
+        let mut sorted_entries: Vec<_> = entries.iter().filter(|(url, _)| *url != keep).collect();
+        sorted_entries.sort_by_key(|(_, entry)| (entry.last_accessed, entry.access_count));
 
         let mut freed_space = 0u64;
         let mut to_remove = Vec::new();
@@ -288,7 +388,7 @@ impl Cache {
                 *current_size = current_size.saturating_sub(entry.size);
 
                 // Delete cache file
-                let cache_file = self.cache_file_path(&url);
+                let cache_file = self.content_path(&url);
                 let _ = fs::remove_file(cache_file).await;
             }
         }
@@ -344,7 +444,7 @@ impl Cache {
 
         // Delete all cache files
         for url in entries.keys() {
-            let cache_file = self.cache_file_path(url);
+            let cache_file = self.content_path(url);
             let _ = fs::remove_file(cache_file).await;
         }
 
@@ -499,6 +599,14 @@ mod tests {
         // Validate with matching Last-Modified
         assert!(cache.validate(url, None, Some(1234567890)).await.unwrap());
 
+        // A changed ETag wins over an unchanged Last-Modified
+        assert!(
+            !cache
+                .validate(url, Some("\"etag456\""), Some(1234567890))
+                .await
+                .unwrap()
+        );
+
         // Validate with newer Last-Modified
         assert!(!cache.validate(url, None, Some(1234567891)).await.unwrap());
     }
@@ -522,6 +630,37 @@ mod tests {
         assert!(stats.entry_count <= 3);
     }
 
+    #[tokio::test]
+    async fn test_cache_evicts_least_recently_used_first() {
+        let (cache, _temp_dir) = create_test_cache().await;
+        let url = |name: &str| format!("https://example.com/{name}");
+
+        for name in ["a", "b"] {
+            cache
+                .put(url(name), &[b'x'; 400], None, None, None, false)
+                .await
+                .unwrap();
+        }
+        cache.touch(&url("a")).await.unwrap();
+        cache
+            .put(url("c"), &[b'x'; 400], None, None, None, false)
+            .await
+            .unwrap();
+
+        assert!(cache.peek(&url("a")).await.is_some());
+        assert!(cache.peek(&url("b")).await.is_none());
+        assert!(!cache.content_path(&url("b")).exists());
+        assert_eq!(cache.stats().await.total_size, 800);
+
+        // Larger than max_size: not cached, nothing evicted
+        cache
+            .put(url("huge"), &[b'x'; 2048], None, None, None, false)
+            .await
+            .unwrap();
+        assert!(cache.peek(&url("huge")).await.is_none());
+        assert_eq!(cache.stats().await.entry_count, 2);
+    }
+
     #[tokio::test]
     async fn test_cache_clear() {
         let (cache, _temp_dir) = create_test_cache().await;
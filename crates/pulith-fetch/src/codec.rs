@@ -1,7 +1,12 @@
 pub mod decompress;
+pub mod manifest;
 pub mod signature;
 pub mod verify;
 
 pub use decompress::{CompressionType, StreamTransform, TransformError, create_decoder};
-pub use signature::{SignatureConfig, SignatureVerifier, verify_signature};
+pub use manifest::{ChecksumManifest, ManifestEntry};
+pub use signature::{
+    Ed25519Verifier, MinisignSignature, OpenPgpVerifier, RsaVerifier, SignatureConfig,
+    SignatureVerifier, SigstoreTrustRoot, SigstoreVerifier, verify_signature,
+};
 pub use verify::{ChecksumConfig, MultiVerifier, StreamVerifier, verify_checksum};
@@ -0,0 +1,315 @@
+//! Checksum manifest parsing.
+//!
+//! Upstreams commonly publish digests in a separate file instead of inline.
+//! The following line formats are recognised, and may be mixed in one file:
+//!
+//! - coreutils `sha256sum` output, also used by GoReleaser `checksums.txt`:
+//!   `<hex>  <name>` or `<hex> *<name>`
+//! - BSD and `sha256sum --tag` output: `SHA256 (<name>) = <hex>`
+//! - single-digest files such as `<artifact>.sha256` holding only `<hex>`
+
+use pulith_resource::{DigestAlgorithm, ValidDigest};
+
+use crate::error::{Error, Result};
+
+/// One digest line of a checksum manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Algorithm named on the line, only known for BSD-style entries.
+    pub algorithm: Option<DigestAlgorithm>,
+    /// File name, absent for single-digest files.
+    pub file_name: Option<String>,
+    /// Lower-case hex digest.
+    pub digest_hex: String,
+}
+
+/// A parsed checksum manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChecksumManifest {
+    entries: Vec<ManifestEntry>,
+}
+
+impl ChecksumManifest {
+    /// Parse manifest text. Blank lines and `#` comments are ignored; any
+    /// other line that is not a recognised digest line is an error.
+    pub fn parse(text: &str) -> Result<Self> {
+        let entries = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index, line.trim_end_matches('\r')))
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(index, line)| {
+                parse_bsd_line(line)
+                    .or_else(|| parse_coreutils_line(line))
+                    .ok_or_else(|| {
+                        Error::InvalidState(format!(
+                            "Unrecognised checksum manifest line {}: {}",
+                            index + 1,
+                            line
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { entries })
+    }
+
+    /// Get the parsed entries.
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// Look up the digest of `file_name`.
+    ///
+    /// Entries match on the exact name or on the final path component, so
+    /// `./dist/tool.tar.gz` matches `tool.tar.gz`. BSD-style entries for a
+    /// different algorithm are ignored. A manifest holding a single nameless
+    /// digest applies to any file. Conflicting digests for the same file are
+    /// an error.
+    pub fn digest_for(&self, file_name: &str, algorithm: &DigestAlgorithm) -> Result<ValidDigest> {
+        let candidates = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                entry
+                    .algorithm
+                    .as_ref()
+                    .is_none_or(|found| found == algorithm)
+            })
+            .collect::<Vec<_>>();
+
+        let mut matches = candidates
+            .iter()
+            .filter(|entry| {
+                entry
+                    .file_name
+                    .as_deref()
+                    .is_some_and(|name| name_matches(name, file_name))
+            })
+            .map(|entry| entry.digest_hex.as_str())
+            .collect::<Vec<_>>();
+        if matches.is_empty()
+            && let [entry] = candidates.as_slice()
+            && entry.file_name.is_none()
+        {
+            matches.push(&entry.digest_hex);
+        }
+        matches.sort_unstable();
+        matches.dedup();
+
+        match matches.as_slice() {
+            [] => Err(Error::InvalidState(format!(
+                "Checksum manifest has no {:?} entry for `{}`",
+                algorithm, file_name
+            ))),
+            [digest] => ValidDigest::from_hex(algorithm.clone(), digest)
+                .map_err(|e| Error::InvalidState(format!("Invalid manifest digest: {}", e))),
+            _ => Err(Error::InvalidState(format!(
+                "Checksum manifest lists conflicting digests for `{}`",
+                file_name
+            ))),
+        }
+    }
+}
+
+fn parse_bsd_line(line: &str) -> Option<ManifestEntry> {
+    let (algorithm, rest) = line.split_once('(')?;
+    let (name, digest) = rest.rsplit_once(')')?;
+    let digest = digest.trim_start().strip_prefix('=')?.trim();
+    let algorithm = algorithm.trim();
+    if algorithm.is_empty() || name.is_empty() || !is_hex(digest) {
+        return None;
+    }
+
+    Some(ManifestEntry {
+        algorithm: Some(parse_algorithm(algorithm)),
+        file_name: Some(name.to_string()),
+        digest_hex: digest.to_ascii_lowercase(),
+    })
+}
+
+fn parse_coreutils_line(line: &str) -> Option<ManifestEntry> {
+    // coreutils prefixes the line with `\` when the name contains escapes.
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (digest, name) = match line.split_once([' ', '\t']) {
+        Some((digest, name)) => {
+            let name = name.trim_start();
+            let name = name.strip_prefix('*').unwrap_or(name);
+            (digest, Some(name))
+        }
+        None => (line, None),
+    };
+    if !is_hex(digest) || name.is_some_and(str::is_empty) {
+        return None;
+    }
+
+    Some(ManifestEntry {
+        algorithm: None,
+        file_name: name.map(|name| {
+            if escaped {
+                unescape(name)
+            } else {
+                name.to_string()
+            }
+        }),
+        digest_hex: digest.to_ascii_lowercase(),
+    })
+}
+
+fn parse_algorithm(name: &str) -> DigestAlgorithm {
+    let normalized = name.to_ascii_lowercase().replace('-', "");
+    match normalized.as_str() {
+        "sha256" | "sha2256" => DigestAlgorithm::Sha256,
+        "blake3" => DigestAlgorithm::Blake3,
+        _ => DigestAlgorithm::Custom(normalized),
+    }
+}
+
+fn name_matches(entry: &str, file_name: &str) -> bool {
+    entry == file_name || entry.rsplit('/').next() == Some(file_name)
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty()
+        && value.len().is_multiple_of(2)
+        && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn unescape(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.clone().next()) {
+            ('\\', Some('\\')) => {
+                output.push('\\');
+                chars.next();
+            }
+            ('\\', Some('n')) => {
+                output.push('\n');
+                chars.next();
+            }
+            ('\\', Some('r')) => {
+                output.push('\r');
+                chars.next();
+            }
+            _ => output.push(ch),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST_A: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    const DIGEST_B: &str = "486ea46224d1bb4fb680f34f7c9ad96a8f24ec88be73ea8e5a6c65260e9cb8a7";
+
+    fn sha256(hex: &str) -> ValidDigest {
+        ValidDigest::from_hex(DigestAlgorithm::Sha256, hex).unwrap()
+    }
+
+    #[test]
+    fn test_coreutils_and_goreleaser_manifests() {
+        let manifest = ChecksumManifest::parse(&format!(
+            "{DIGEST_A}  tool_1.0.0_linux_amd64.tar.gz\n\
+             {DIGEST_B} *tool_1.0.0_windows_amd64.zip\n"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            manifest
+                .digest_for("tool_1.0.0_linux_amd64.tar.gz", &DigestAlgorithm::Sha256)
+                .unwrap(),
+            sha256(DIGEST_A)
+        );
+        assert_eq!(
+            manifest
+                .digest_for("tool_1.0.0_windows_amd64.zip", &DigestAlgorithm::Sha256)
+                .unwrap(),
+            sha256(DIGEST_B)
+        );
+        assert!(
+            manifest
+                .digest_for("tool_1.0.0_darwin_arm64.tar.gz", &DigestAlgorithm::Sha256)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_bsd_manifest_filters_by_algorithm() {
+        let manifest = ChecksumManifest::parse(&format!(
+            "SHA512 (tool.tar.gz) = {DIGEST_B}{DIGEST_B}\n\
+             SHA256 (tool.tar.gz) = {}\n",
+            DIGEST_A.to_uppercase()
+        ))
+        .unwrap();
+
+        assert_eq!(
+            manifest
+                .digest_for("tool.tar.gz", &DigestAlgorithm::Sha256)
+                .unwrap(),
+            sha256(DIGEST_A)
+        );
+        assert_eq!(
+            manifest.entries()[0].algorithm,
+            Some(DigestAlgorithm::Custom("sha512".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_single_digest_file_and_path_prefixes() {
+        let single = ChecksumManifest::parse(&format!("{DIGEST_A}\n")).unwrap();
+        assert_eq!(
+            single
+                .digest_for("anything.zip", &DigestAlgorithm::Sha256)
+                .unwrap(),
+            sha256(DIGEST_A)
+        );
+
+        let prefixed = ChecksumManifest::parse(&format!("{DIGEST_A}  ./dist/tool.zip\n")).unwrap();
+        assert_eq!(
+            prefixed
+                .digest_for("tool.zip", &DigestAlgorithm::Sha256)
+                .unwrap(),
+            sha256(DIGEST_A)
+        );
+    }
+
+    #[test]
+    fn test_escaped_names_comments_and_crlf() {
+        let manifest = ChecksumManifest::parse(&format!(
+            "# release checksums\r\n\\{DIGEST_A}  dir\\\\tool\\nname.zip\r\n"
+        ))
+        .unwrap();
+
+        assert_eq!(
+            manifest.entries()[0].file_name.as_deref(),
+            Some("dir\\tool\nname.zip")
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_and_conflicting_manifests() {
+        assert!(ChecksumManifest::parse("not a checksum line\n").is_err());
+
+        let conflicting =
+            ChecksumManifest::parse(&format!("{DIGEST_A}  tool.zip\n{DIGEST_B}  tool.zip\n"))
+                .unwrap();
+        assert!(
+            conflicting
+                .digest_for("tool.zip", &DigestAlgorithm::Sha256)
+                .is_err()
+        );
+
+        let short = ChecksumManifest::parse("abcd  tool.zip\n").unwrap();
+        assert!(
+            short
+                .digest_for("tool.zip", &DigestAlgorithm::Sha256)
+                .is_err()
+        );
+    }
+}
@@ -1,13 +1,33 @@
 //! Digital signature verification functionality.
 //!
 //! This module provides types and interfaces for verifying
-//! digital signatures of downloaded content. Currently provides
-//! type definitions and interfaces for future implementation.
+//! digital signatures of downloaded content. Ed25519 and RSA
+//! (PKCS#1 v1.5 and PSS over SHA-256) verifiers are registered by default.
+//! Minisign and signify signature files are handled by the Ed25519 verifier
+//! through [`SignatureFormat::Minisign`] and [`SignatureFormat::Signify`]. OpenPGP signatures need a keyring
+//! and are verified by [`OpenPgpVerifier`]. Sigstore bundles carry their own
+//! certificate and are verified against a pinned trust root by
+//! [`SigstoreVerifier`].
+
+pub mod minisign;
+pub mod openpgp;
+pub mod sigstore;
 
 use crate::error::{Error, Result};
+use ed25519_dalek::pkcs8::DecodePublicKey as _;
+use rsa::pkcs1::DecodeRsaPublicKey as _;
+use rsa::signature::Verifier as _;
+use rsa::traits::PublicKeyParts as _;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::collections::HashMap;
 
+pub use minisign::{MinisignAlgorithm, MinisignMode, MinisignSignature};
+pub use openpgp::{
+    OpenPgpKey, OpenPgpKeyring, OpenPgpSignature, OpenPgpVerification, OpenPgpVerifier,
+};
+pub use sigstore::{SigstoreBundle, SigstoreTrustRoot, SigstoreVerification, SigstoreVerifier};
+
 /// Supported signature algorithms.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SignatureAlgorithm {
@@ -21,6 +41,8 @@ pub enum SignatureAlgorithm {
     EdDsa,
     /// Digital Signature Algorithm (DSA) - deprecated
     Dsa,
+    /// OpenPGP signature packets (RSA or EdDSA, chosen by the signing key)
+    OpenPgp,
 }
 
 impl SignatureAlgorithm {
@@ -32,6 +54,7 @@ impl SignatureAlgorithm {
             SignatureAlgorithm::Ecdsa => "ecdsa",
             SignatureAlgorithm::EdDsa => "eddsa",
             SignatureAlgorithm::Dsa => "dsa",
+            SignatureAlgorithm::OpenPgp => "openpgp",
         }
     }
 
@@ -43,6 +66,8 @@ impl SignatureAlgorithm {
             SignatureAlgorithm::Ecdsa => 256,
             SignatureAlgorithm::EdDsa => 256,
             SignatureAlgorithm::Dsa => 2048,
+            // Checked per key when the keyring is loaded.
+            SignatureAlgorithm::OpenPgp => 0,
         }
     }
 
@@ -54,6 +79,7 @@ impl SignatureAlgorithm {
             SignatureAlgorithm::Ecdsa => true,
             SignatureAlgorithm::EdDsa => true,
             SignatureAlgorithm::Dsa => false, // DSA is deprecated
+            SignatureAlgorithm::OpenPgp => true,
         }
     }
 }
@@ -71,6 +97,12 @@ pub enum SignatureFormat {
     Pem,
     /// DER format
     Der,
+    /// Minisign signature file, with a trusted comment
+    Minisign,
+    /// Signify signature file, without a trusted comment
+    Signify,
+    /// Armored or binary OpenPGP signature
+    OpenPgp,
 }
 
 impl SignatureFormat {
@@ -82,6 +114,9 @@ impl SignatureFormat {
             SignatureFormat::Hex => "hex",
             SignatureFormat::Pem => "pem",
             SignatureFormat::Der => "der",
+            SignatureFormat::Minisign => "minisign",
+            SignatureFormat::Signify => "signify",
+            SignatureFormat::OpenPgp => "openpgp",
         }
     }
 }
@@ -99,6 +134,10 @@ pub enum PublicKeyFormat {
     Jwk,
     /// SSH public key format
     Ssh,
+    /// Minisign or signify public key
+    Minisign,
+    /// Armored or binary OpenPGP key export
+    OpenPgp,
 }
 
 impl PublicKeyFormat {
@@ -110,6 +149,8 @@ impl PublicKeyFormat {
             PublicKeyFormat::Raw => "raw",
             PublicKeyFormat::Jwk => "jwk",
             PublicKeyFormat::Ssh => "ssh",
+            PublicKeyFormat::Minisign => "minisign",
+            PublicKeyFormat::OpenPgp => "openpgp",
         }
     }
 }
@@ -178,6 +219,14 @@ impl PublicKey {
 
         Ok(())
     }
+
+    /// Parse the key data as a minisign or signify public key.
+    pub fn minisign(&self) -> Result<pulith_resource::MinisignKey> {
+        let text = std::str::from_utf8(&self.data)
+            .map_err(|e| Error::InvalidState(format!("Invalid minisign public key: {}", e)))?;
+        pulith_resource::MinisignKey::parse(text)
+            .map_err(|e| Error::InvalidState(format!("Invalid minisign public key: {}", e)))
+    }
 }
 
 /// Key usage constraints.
@@ -269,22 +318,78 @@ impl Signature {
     pub fn decode_data(&self) -> Result<Vec<u8>> {
         match self.format {
             SignatureFormat::Raw => Ok(self.data.clone()),
-            SignatureFormat::Base64 => {
-                use base64::{Engine as _, engine::general_purpose};
-                general_purpose::STANDARD
-                    .decode(&self.data)
-                    .map_err(|e| Error::InvalidState(format!("Invalid base64 signature: {}", e)))
-            }
-            SignatureFormat::Hex => hex::decode(&self.data)
+            SignatureFormat::Base64 => decode_base64(self.data.trim_ascii())
+                .map_err(|e| Error::InvalidState(format!("Invalid base64 signature: {}", e))),
+            SignatureFormat::Hex => hex::decode(self.data.trim_ascii())
                 .map_err(|e| Error::InvalidState(format!("Invalid hex signature: {}", e))),
-            SignatureFormat::Pem | SignatureFormat::Der => {
-                // Would need proper PEM/DER parsing
-                Err(Error::InvalidState(
-                    "PEM/DER signature parsing not implemented".to_string(),
-                ))
-            }
+            SignatureFormat::Pem => decode_pem_block(&self.data)
+                .map(|(_, body)| body)
+                .map_err(|e| Error::InvalidState(format!("Invalid PEM signature: {}", e))),
+            // RSA and Ed25519 signatures are bare octet strings, so their DER
+            // encoding is the signature bytes themselves.
+            SignatureFormat::Der => Ok(self.data.clone()),
+            SignatureFormat::Minisign | SignatureFormat::Signify => self
+                .minisign()
+                .map(|signature| signature.signature_bytes().to_vec()),
+            // OpenPGP signatures are packet streams, dearmored by the verifier.
+            SignatureFormat::OpenPgp => Ok(self.data.clone()),
         }
     }
+
+    /// Parse the signature data as a minisign or signify signature file.
+    pub fn minisign(&self) -> Result<MinisignSignature> {
+        let text = std::str::from_utf8(&self.data)
+            .map_err(|e| Error::InvalidState(format!("Invalid minisign signature: {}", e)))?;
+        MinisignSignature::parse(text)
+    }
+}
+
+/// Record a SHA-256 digest of verified content for [`pulith_resource::VerifiedSignature`].
+fn sha256_digest(digest: &[u8]) -> pulith_resource::ValidDigest {
+    pulith_resource::ValidDigest {
+        algorithm: pulith_resource::DigestAlgorithm::Sha256,
+        bytes: digest.to_vec(),
+    }
+}
+
+fn decode_base64(data: &[u8]) -> std::result::Result<Vec<u8>, base64::DecodeError> {
+    use base64::{Engine as _, engine::general_purpose};
+    general_purpose::STANDARD.decode(data)
+}
+
+/// Decode the first PEM block in `data`, returning its label and body.
+fn decode_pem_block(data: &[u8]) -> std::result::Result<(String, Vec<u8>), String> {
+    let text = std::str::from_utf8(data).map_err(|e| e.to_string())?;
+    let mut lines = text.lines().map(str::trim);
+
+    let label = lines
+        .by_ref()
+        .find_map(|line| {
+            line.strip_prefix("-----BEGIN ")
+                .and_then(|rest| rest.strip_suffix("-----"))
+        })
+        .ok_or_else(|| "missing BEGIN line".to_string())?
+        .to_string();
+    let end = format!("-----END {label}-----");
+
+    let mut body = String::new();
+    let mut terminated = false;
+    for line in lines {
+        if line == end {
+            terminated = true;
+            break;
+        }
+        // Skip RFC 1421 style headers such as `Comment: ...`.
+        if !line.contains(':') {
+            body.push_str(line);
+        }
+    }
+    if !terminated {
+        return Err(format!("missing END line for `{label}`"));
+    }
+
+    let bytes = decode_base64(body.as_bytes()).map_err(|e| e.to_string())?;
+    Ok((label, bytes))
 }
 
 /// Signature verification configuration.
@@ -394,40 +499,212 @@ impl SignatureVerifier for MockVerifier {
     }
 }
 
+/// Ed25519 signature verifier.
+///
+/// Accepts public keys as SubjectPublicKeyInfo PEM/DER, minisign/signify
+/// public keys, or the raw 32-byte key. Verification uses strict
+/// (non-malleable) checking.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Ed25519Verifier;
+
+impl Ed25519Verifier {
+    /// Create a new Ed25519 verifier.
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn decode_key(key: &PublicKey) -> Result<ed25519_dalek::VerifyingKey> {
+        let decoded = match key.format {
+            PublicKeyFormat::Pem => {
+                let pem = std::str::from_utf8(&key.data)
+                    .map_err(|e| Error::InvalidState(format!("Invalid PEM public key: {}", e)))?;
+                ed25519_dalek::VerifyingKey::from_public_key_pem(pem.trim())
+                    .map_err(|e| e.to_string())
+            }
+            PublicKeyFormat::Der => ed25519_dalek::VerifyingKey::from_public_key_der(&key.data)
+                .map_err(|e| e.to_string()),
+            PublicKeyFormat::Raw => {
+                <[u8; ed25519_dalek::PUBLIC_KEY_LENGTH]>::try_from(key.data.as_slice())
+                    .map_err(|_| {
+                        format!(
+                            "expected {} raw key bytes, got {}",
+                            ed25519_dalek::PUBLIC_KEY_LENGTH,
+                            key.data.len()
+                        )
+                    })
+                    .and_then(|bytes| {
+                        ed25519_dalek::VerifyingKey::from_bytes(&bytes).map_err(|e| e.to_string())
+                    })
+            }
+            PublicKeyFormat::Minisign => {
+                ed25519_dalek::VerifyingKey::from_bytes(key.minisign()?.public_key())
+                    .map_err(|e| e.to_string())
+            }
+            PublicKeyFormat::Jwk | PublicKeyFormat::Ssh | PublicKeyFormat::OpenPgp => {
+                return Err(unsupported_key_format(key));
+            }
+        };
+
+        decoded.map_err(|e| Error::InvalidState(format!("Invalid Ed25519 public key: {}", e)))
+    }
+}
+
+impl SignatureVerifier for Ed25519Verifier {
+    fn verify(&self, data: &[u8], config: &SignatureConfig) -> Result<bool> {
+        config.validate()?;
+        let mode = match config.signature.format {
+            SignatureFormat::Minisign => Some(MinisignMode::Minisign),
+            SignatureFormat::Signify => Some(MinisignMode::Signify),
+            _ => None,
+        };
+        if let Some(mode) = mode {
+            let signature = config.signature.minisign()?;
+            return match config.public_key.format {
+                PublicKeyFormat::Minisign => {
+                    signature.verify(&config.public_key.minisign()?, mode, data)
+                }
+                _ => signature.verify_with_key(&Self::decode_key(&config.public_key)?, mode, data),
+            };
+        }
+
+        let key = Self::decode_key(&config.public_key)?;
+        let signature = ed25519_dalek::Signature::from_slice(&config.signature.decode_data()?)
+            .map_err(|e| Error::InvalidState(format!("Invalid Ed25519 signature: {}", e)))?;
+
+        Ok(key.verify_strict(data, &signature).is_ok())
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::EdDsa
+    }
+}
+
+/// RSA signature verifier over SHA-256 digests.
+///
+/// Accepts public keys as SubjectPublicKeyInfo (`PUBLIC KEY`) or PKCS#1
+/// (`RSA PUBLIC KEY`) PEM/DER. Raw keys are interpreted as PKCS#1 DER.
+#[derive(Debug, Clone, Copy)]
+pub struct RsaVerifier {
+    algorithm: SignatureAlgorithm,
+}
+
+impl RsaVerifier {
+    /// Create a verifier for RSASSA-PKCS1-v1_5 signatures.
+    pub fn pkcs1v15() -> Self {
+        Self {
+            algorithm: SignatureAlgorithm::RsaPkcs1v15,
+        }
+    }
+
+    /// Create a verifier for RSASSA-PSS signatures.
+    pub fn pss() -> Self {
+        Self {
+            algorithm: SignatureAlgorithm::RsaPss,
+        }
+    }
+
+    fn decode_key(key: &PublicKey) -> Result<rsa::RsaPublicKey> {
+        let decoded = match key.format {
+            PublicKeyFormat::Pem => {
+                let (label, der) = decode_pem_block(&key.data)
+                    .map_err(|e| Error::InvalidState(format!("Invalid PEM public key: {}", e)))?;
+                if label == "RSA PUBLIC KEY" {
+                    rsa::RsaPublicKey::from_pkcs1_der(&der).map_err(|e| e.to_string())
+                } else {
+                    rsa::RsaPublicKey::from_public_key_der(&der).map_err(|e| e.to_string())
+                }
+            }
+            PublicKeyFormat::Der => rsa::RsaPublicKey::from_public_key_der(&key.data)
+                .or_else(|_| rsa::RsaPublicKey::from_pkcs1_der(&key.data))
+                .map_err(|e| e.to_string()),
+            PublicKeyFormat::Raw => {
+                rsa::RsaPublicKey::from_pkcs1_der(&key.data).map_err(|e| e.to_string())
+            }
+            PublicKeyFormat::Jwk
+            | PublicKeyFormat::Ssh
+            | PublicKeyFormat::Minisign
+            | PublicKeyFormat::OpenPgp => {
+                return Err(unsupported_key_format(key));
+            }
+        };
+        let decoded =
+            decoded.map_err(|e| Error::InvalidState(format!("Invalid RSA public key: {}", e)))?;
+
+        let modulus_bits = decoded.size() * 8;
+        if modulus_bits < key.algorithm.min_key_size() {
+            return Err(Error::InvalidState(format!(
+                "RSA modulus size {} is below minimum {}",
+                modulus_bits,
+                key.algorithm.min_key_size()
+            )));
+        }
+
+        Ok(decoded)
+    }
+}
+
+impl SignatureVerifier for RsaVerifier {
+    fn verify(&self, data: &[u8], config: &SignatureConfig) -> Result<bool> {
+        config.validate()?;
+        let key = Self::decode_key(&config.public_key)?;
+        let signature = config.signature.decode_data()?;
+        let invalid_signature =
+            |e: rsa::signature::Error| Error::InvalidState(format!("Invalid RSA signature: {}", e));
+
+        let verified = match self.algorithm {
+            SignatureAlgorithm::RsaPss => {
+                let signature = rsa::pss::Signature::try_from(signature.as_slice())
+                    .map_err(invalid_signature)?;
+                rsa::pss::VerifyingKey::<Sha256>::new(key)
+                    .verify(data, &signature)
+                    .is_ok()
+            }
+            _ => {
+                let signature = rsa::pkcs1v15::Signature::try_from(signature.as_slice())
+                    .map_err(invalid_signature)?;
+                rsa::pkcs1v15::VerifyingKey::<Sha256>::new(key)
+                    .verify(data, &signature)
+                    .is_ok()
+            }
+        };
+
+        Ok(verified)
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        self.algorithm
+    }
+}
+
+fn unsupported_key_format(key: &PublicKey) -> Error {
+    Error::InvalidState(format!(
+        "Public key format {} is not supported for {}",
+        key.format.as_str(),
+        key.algorithm.as_str()
+    ))
+}
+
 /// Signature verification manager.
 pub struct SignatureManager {
     verifiers: HashMap<SignatureAlgorithm, Box<dyn SignatureVerifier>>,
 }
 
 impl SignatureManager {
-    /// Create a new signature manager.
+    /// Create a new signature manager with the built-in verifiers registered.
+    ///
+    /// Ed25519, RSA PKCS#1 v1.5 and RSA-PSS are supported out of the box.
+    /// Other algorithms require [`SignatureManager::add_verifier`].
     pub fn new() -> Self {
         let mut manager = Self {
             verifiers: HashMap::new(),
         };
 
-        // Add mock verifiers for all algorithms
-        manager.add_mock_verifiers();
+        manager.add_verifier(Box::new(Ed25519Verifier::new()));
+        manager.add_verifier(Box::new(RsaVerifier::pkcs1v15()));
+        manager.add_verifier(Box::new(RsaVerifier::pss()));
         manager
     }
 
-    /// Add a mock verifier for each algorithm.
-    fn add_mock_verifiers(&mut self) {
-        use SignatureAlgorithm::*;
-
-        // Add mock verifiers that always return true for testing
-        self.verifiers
-            .insert(RsaPkcs1v15, Box::new(MockVerifier::new(RsaPkcs1v15, true)));
-        self.verifiers
-            .insert(RsaPss, Box::new(MockVerifier::new(RsaPss, true)));
-        self.verifiers
-            .insert(Ecdsa, Box::new(MockVerifier::new(Ecdsa, true)));
-        self.verifiers
-            .insert(EdDsa, Box::new(MockVerifier::new(EdDsa, true)));
-        self.verifiers
-            .insert(Dsa, Box::new(MockVerifier::new(Dsa, true)));
-    }
-
     /// Add a custom verifier for an algorithm.
     pub fn add_verifier(&mut self, verifier: Box<dyn SignatureVerifier>) {
         self.verifiers.insert(verifier.algorithm(), verifier);
@@ -632,11 +909,13 @@ mod tests {
         let manager = SignatureManager::new();
 
         assert!(manager.supports_algorithm(SignatureAlgorithm::RsaPkcs1v15));
-        assert!(manager.supports_algorithm(SignatureAlgorithm::Ecdsa));
+        assert!(manager.supports_algorithm(SignatureAlgorithm::RsaPss));
+        assert!(manager.supports_algorithm(SignatureAlgorithm::EdDsa));
+        assert!(!manager.supports_algorithm(SignatureAlgorithm::Ecdsa));
 
         let algorithms = manager.supported_algorithms();
         assert!(algorithms.contains(&SignatureAlgorithm::RsaPkcs1v15));
-        assert!(algorithms.contains(&SignatureAlgorithm::Ecdsa));
+        assert!(algorithms.contains(&SignatureAlgorithm::EdDsa));
 
         let key = PublicKey::new(
             SignatureAlgorithm::RsaPkcs1v15,
@@ -650,25 +929,173 @@ mod tests {
         );
         let config = SignatureConfig::new(key, sig);
 
-        // Mock verifier returns true
+        // Real verifier rejects malformed key material
+        assert!(manager.verify(b"data", &config).is_err());
+    }
+
+    #[test]
+    fn test_signature_manager_custom_verifier() {
+        let mut manager = SignatureManager::new();
+        manager.add_verifier(Box::new(MockVerifier::new(SignatureAlgorithm::Ecdsa, true)));
+        assert!(manager.supports_algorithm(SignatureAlgorithm::Ecdsa));
+
+        let key = PublicKey::new(
+            SignatureAlgorithm::Ecdsa,
+            PublicKeyFormat::Raw,
+            vec![0u8; 32],
+        );
+        let sig = Signature::new(
+            SignatureAlgorithm::Ecdsa,
+            SignatureFormat::Raw,
+            b"signature".to_vec(),
+        );
+        let config = SignatureConfig::new(key, sig);
+
         assert!(manager.verify(b"data", &config).unwrap());
     }
 
     #[test]
-    fn test_convenience_function() {
+    fn test_signature_manager_rejects_unregistered_algorithm() {
+        let manager = SignatureManager::new();
         let key = PublicKey::new(
-            SignatureAlgorithm::RsaPkcs1v15,
-            PublicKeyFormat::Pem,
-            vec![0u8; 256],
+            SignatureAlgorithm::Ecdsa,
+            PublicKeyFormat::Raw,
+            vec![0u8; 32],
         );
         let sig = Signature::new(
-            SignatureAlgorithm::RsaPkcs1v15,
+            SignatureAlgorithm::Ecdsa,
             SignatureFormat::Raw,
             b"signature".to_vec(),
         );
+
+        assert!(
+            manager
+                .verify(b"data", &SignatureConfig::new(key, sig))
+                .is_err()
+        );
+    }
+
+    fn ed25519_config(data: &[u8], format: SignatureFormat) -> SignatureConfig {
+        use ed25519_dalek::Signer as _;
+
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let signature = signing_key.sign(data).to_bytes().to_vec();
+        let encoded = match format {
+            SignatureFormat::Raw | SignatureFormat::Der => signature,
+            SignatureFormat::Hex => hex::encode(signature).into_bytes(),
+            SignatureFormat::Base64 => {
+                use base64::{Engine as _, engine::general_purpose};
+                general_purpose::STANDARD.encode(signature).into_bytes()
+            }
+            SignatureFormat::Pem => {
+                use base64::{Engine as _, engine::general_purpose};
+                format!(
+                    "-----BEGIN SIGNATURE-----\n{}\n-----END SIGNATURE-----\n",
+                    general_purpose::STANDARD.encode(signature)
+                )
+                .into_bytes()
+            }
+            SignatureFormat::Minisign | SignatureFormat::Signify => {
+                use base64::{Engine as _, engine::general_purpose};
+                let mut encoded = b"Ed".to_vec();
+                encoded.extend_from_slice(&[0u8; 8]);
+                encoded.extend_from_slice(&signature);
+                let mut text = format!(
+                    "untrusted comment: signature\n{}\n",
+                    general_purpose::STANDARD.encode(encoded)
+                );
+                if format == SignatureFormat::Minisign {
+                    let comment = "timestamp:0";
+                    let mut global = signature;
+                    global.extend_from_slice(comment.as_bytes());
+                    text.push_str(&format!(
+                        "trusted comment: {comment}\n{}\n",
+                        general_purpose::STANDARD.encode(signing_key.sign(&global).to_bytes())
+                    ));
+                }
+                text.into_bytes()
+            }
+            SignatureFormat::OpenPgp => panic!("OpenPGP signatures are covered by openpgp tests"),
+        };
+
+        SignatureConfig::new(
+            PublicKey::new(
+                SignatureAlgorithm::EdDsa,
+                PublicKeyFormat::Raw,
+                signing_key.verifying_key().to_bytes().to_vec(),
+            ),
+            Signature::new(SignatureAlgorithm::EdDsa, format, encoded),
+        )
+    }
+
+    #[test]
+    fn test_ed25519_verifier_accepts_valid_signature() {
+        let verifier = Ed25519Verifier::new();
+        for format in [
+            SignatureFormat::Raw,
+            SignatureFormat::Base64,
+            SignatureFormat::Hex,
+            SignatureFormat::Pem,
+            SignatureFormat::Der,
+            SignatureFormat::Minisign,
+            SignatureFormat::Signify,
+        ] {
+            let config = ed25519_config(b"payload", format);
+            assert!(verifier.verify(b"payload", &config).unwrap(), "{format:?}");
+        }
+
+        let mut signify = ed25519_config(b"payload", SignatureFormat::Signify);
+        signify.signature.format = SignatureFormat::Minisign;
+        assert!(verifier.verify(b"payload", &signify).is_err());
+    }
+
+    #[test]
+    fn test_ed25519_verifier_rejects_tampered_data() {
+        let config = ed25519_config(b"payload", SignatureFormat::Raw);
+        assert!(!Ed25519Verifier::new().verify(b"tampered", &config).unwrap());
+    }
+
+    #[test]
+    fn test_ed25519_verifier_rejects_unsupported_key_format() {
+        let mut config = ed25519_config(b"payload", SignatureFormat::Raw);
+        config.public_key.format = PublicKeyFormat::Jwk;
+        assert!(Ed25519Verifier::new().verify(b"payload", &config).is_err());
+    }
+
+    #[test]
+    fn test_rsa_verifier_rejects_malformed_key() {
+        let key = PublicKey::new(
+            SignatureAlgorithm::RsaPss,
+            PublicKeyFormat::Der,
+            vec![0u8; 256],
+        );
+        let sig = Signature::new(
+            SignatureAlgorithm::RsaPss,
+            SignatureFormat::Raw,
+            vec![0u8; 256],
+        );
         let config = SignatureConfig::new(key, sig);
 
-        // Mock verifier returns true
+        assert_eq!(RsaVerifier::pss().algorithm(), SignatureAlgorithm::RsaPss);
+        assert!(RsaVerifier::pss().verify(b"data", &config).is_err());
+    }
+
+    #[test]
+    fn test_decode_pem_block() {
+        let (label, body) =
+            decode_pem_block(b"-----BEGIN SIGNATURE-----\naGVsbG8=\n-----END SIGNATURE-----\n")
+                .unwrap();
+        assert_eq!(label, "SIGNATURE");
+        assert_eq!(body, b"hello");
+
+        assert!(decode_pem_block(b"-----BEGIN SIGNATURE-----\naGVsbG8=\n").is_err());
+        assert!(decode_pem_block(b"aGVsbG8=").is_err());
+    }
+
+    #[test]
+    fn test_convenience_function() {
+        let config = ed25519_config(b"data", SignatureFormat::Base64);
         assert!(verify_signature(b"data", &config).unwrap());
+        assert!(!verify_signature(b"other", &config).unwrap());
     }
 }
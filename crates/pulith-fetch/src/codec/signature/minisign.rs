@@ -0,0 +1,449 @@
+//! Minisign and signify detached signatures.
+//!
+//! Both tools share the same Ed25519 key and signature encoding. Minisign
+//! adds a trusted comment covered by a global signature, and may sign a
+//! BLAKE2b-512 prehash of the content instead of the content itself.
+//!
+//! Since a minisign file with its last two lines removed reads as a valid
+//! signify file, callers name the [`MinisignMode`] they expect and a file in
+//! the other mode is refused.
+
+use blake2::{Blake2b512, Digest as _};
+use pulith_resource::{MinisignKey, SignatureScheme, VerifiedSignature, format_minisign_key_id};
+use sha2::Sha256;
+
+use super::{decode_base64, sha256_digest};
+use crate::error::{Error, Result};
+
+const UNTRUSTED_COMMENT_PREFIX: &str = "untrusted comment:";
+/// Minisign signs the bytes after this prefix verbatim, spaces included.
+const TRUSTED_COMMENT_PREFIX: &str = "trusted comment: ";
+
+/// How the signed content was fed to Ed25519.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinisignAlgorithm {
+    /// `Ed`: the content itself is signed (minisign legacy and signify).
+    Pure,
+    /// `ED`: a BLAKE2b-512 digest of the content is signed.
+    Prehashed,
+}
+
+/// Which tool a signature file is expected to come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinisignMode {
+    /// A minisign file, with a trusted comment and global signature.
+    Minisign,
+    /// A signify file, which stops after the signature line.
+    Signify,
+}
+
+/// A parsed minisign or signify signature file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinisignSignature {
+    algorithm: MinisignAlgorithm,
+    key_id: [u8; 8],
+    signature: [u8; 64],
+    untrusted_comment: String,
+    trusted_comment: Option<String>,
+    global_signature: Option<[u8; 64]>,
+}
+
+impl MinisignSignature {
+    /// Parse the text of a `.minisig` or signify `.sig` file.
+    ///
+    /// Signify files stop after the signature line; minisign files carry a
+    /// trusted comment and its global signature on the following two lines.
+    /// Prehashed (`ED`) signatures only exist in minisign, so they must carry
+    /// both.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+
+        let untrusted_comment = lines
+            .next()
+            .and_then(|line| line.strip_prefix(UNTRUSTED_COMMENT_PREFIX))
+            .ok_or_else(|| invalid("missing untrusted comment line"))?
+            .trim()
+            .to_string();
+
+        let signature_line = lines
+            .next()
+            .ok_or_else(|| invalid("missing signature line"))?;
+        let bytes = decode_base64(signature_line.trim().as_bytes())
+            .map_err(|e| invalid(&format!("signature line is not base64: {}", e)))?;
+        if bytes.len() != 2 + 8 + 64 {
+            return Err(invalid(&format!(
+                "unexpected signature length {}",
+                bytes.len()
+            )));
+        }
+        let algorithm = match &bytes[..2] {
+            b"Ed" => MinisignAlgorithm::Pure,
+            b"ED" => MinisignAlgorithm::Prehashed,
+            _ => return Err(invalid("unsupported signature algorithm")),
+        };
+        let key_id = to_array(&bytes[2..10]);
+        let signature = to_array(&bytes[10..]);
+
+        let (trusted_comment, global_signature) = match lines.next() {
+            None => (None, None),
+            Some(line) => {
+                let comment = line
+                    .strip_prefix(TRUSTED_COMMENT_PREFIX)
+                    .ok_or_else(|| invalid("expected trusted comment line"))?
+                    .to_string();
+                let global = lines
+                    .next()
+                    .ok_or_else(|| invalid("missing global signature line"))?;
+                let global = decode_base64(global.trim().as_bytes())
+                    .map_err(|e| invalid(&format!("global signature is not base64: {}", e)))?;
+                if global.len() != 64 {
+                    return Err(invalid(&format!(
+                        "unexpected global signature length {}",
+                        global.len()
+                    )));
+                }
+                (Some(comment), Some(to_array(&global)))
+            }
+        };
+        if algorithm == MinisignAlgorithm::Prehashed && trusted_comment.is_none() {
+            return Err(invalid("prehashed signature without a trusted comment"));
+        }
+
+        Ok(Self {
+            algorithm,
+            key_id,
+            signature,
+            untrusted_comment,
+            trusted_comment,
+            global_signature,
+        })
+    }
+
+    /// Get the signing algorithm.
+    pub fn algorithm(&self) -> MinisignAlgorithm {
+        self.algorithm
+    }
+
+    /// Get the tool this file was written by, judging by its trusted comment.
+    pub fn mode(&self) -> MinisignMode {
+        match self.trusted_comment {
+            Some(_) => MinisignMode::Minisign,
+            None => MinisignMode::Signify,
+        }
+    }
+
+    /// Get the signing key id, formatted as minisign prints it.
+    pub fn key_id(&self) -> String {
+        format_minisign_key_id(&self.key_id)
+    }
+
+    /// Get the raw 64-byte Ed25519 signature.
+    pub fn signature_bytes(&self) -> &[u8; 64] {
+        &self.signature
+    }
+
+    /// Get the untrusted comment. It is not covered by any signature.
+    pub fn untrusted_comment(&self) -> &str {
+        &self.untrusted_comment
+    }
+
+    /// Get the trusted comment, if this is a minisign signature.
+    ///
+    /// The value is only authentic once [`MinisignSignature::verify`] succeeds.
+    pub fn trusted_comment(&self) -> Option<&str> {
+        self.trusted_comment.as_deref()
+    }
+
+    /// Verify `data` against this signature with the given key, expecting a
+    /// file in `mode`.
+    ///
+    /// A key id mismatch is an error rather than `Ok(false)`, since it means
+    /// the wrong key was configured rather than the content being tampered with.
+    /// A file in the other mode is an error too, so a minisign signature
+    /// stripped of its trusted comment does not pass as signify.
+    pub fn verify(&self, key: &MinisignKey, mode: MinisignMode, data: &[u8]) -> Result<bool> {
+        if key.key_id() != self.key_id() {
+            return Err(Error::InvalidState(format!(
+                "Minisign key id mismatch: signature is from {}, key is {}",
+                self.key_id(),
+                key.key_id()
+            )));
+        }
+
+        let key = ed25519_dalek::VerifyingKey::from_bytes(key.public_key())
+            .map_err(|e| Error::InvalidState(format!("Invalid minisign public key: {}", e)))?;
+        self.verify_with_key(&key, mode, data)
+    }
+
+    /// Verify `data` and, on success, describe the signature for trust evaluation.
+    pub fn verified(
+        &self,
+        key: &MinisignKey,
+        mode: MinisignMode,
+        data: &[u8],
+    ) -> Result<Option<VerifiedSignature>> {
+        Ok(self.verify(key, mode, data)?.then(|| VerifiedSignature {
+            scheme: SignatureScheme::Minisign,
+            key_id: self.key_id(),
+            public_key: Some(key.fingerprint()),
+            digest: Some(sha256_digest(&Sha256::digest(data))),
+        }))
+    }
+
+    pub(super) fn verify_with_key(
+        &self,
+        key: &ed25519_dalek::VerifyingKey,
+        mode: MinisignMode,
+        data: &[u8],
+    ) -> Result<bool> {
+        if self.mode() != mode {
+            return Err(invalid(match mode {
+                MinisignMode::Minisign => "expected a minisign signature with a trusted comment",
+                MinisignMode::Signify => "expected a signify signature without a trusted comment",
+            }));
+        }
+
+        let signature = ed25519_dalek::Signature::from_bytes(&self.signature);
+        let verified = match self.algorithm {
+            MinisignAlgorithm::Pure => key.verify_strict(data, &signature),
+            MinisignAlgorithm::Prehashed => {
+                key.verify_strict(&Blake2b512::digest(data), &signature)
+            }
+        };
+        if verified.is_err() {
+            return Ok(false);
+        }
+
+        Ok(match (&self.trusted_comment, &self.global_signature) {
+            (Some(comment), Some(global)) => {
+                let mut signed = self.signature.to_vec();
+                signed.extend_from_slice(comment.as_bytes());
+                key.verify_strict(&signed, &ed25519_dalek::Signature::from_bytes(global))
+                    .is_ok()
+            }
+            _ => true,
+        })
+    }
+}
+
+fn to_array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut array = [0u8; N];
+    array.copy_from_slice(bytes);
+    array
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidState(format!("Invalid minisign signature: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer as _;
+
+    const PUBLIC_KEY: &str = "untrusted comment: minisign public key E7620F1842B4E81F\n\
+         RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3\n";
+
+    const PURE_SIGNATURE: &str = "untrusted comment: signature from minisign secret key\n\
+         RWQf6LRCGA9i59SLOFxz6NxvASXDJeRtuZykwQepbDEGt87ig1BNpWaVWuNrm73YiIiJbq71Wi+dP9eKL8OC351vwIasSSbXxwA=\n\
+         trusted comment: timestamp:1555779966\tfile:test\n\
+         QtKMXWyYcwdpZAlPF7tE2ENJkRd1ujvKjlj1m9RtHTBnZPa5WKU5uWRs5GoP5M/VqE81QFuMKI5k/SfNQUaOAA==\n";
+
+    const PREHASHED_SIGNATURE: &str = "untrusted comment: signature from minisign secret key\n\
+         RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=\n\
+         trusted comment: timestamp:1556193335\tfile:test\n\
+         y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==\n";
+
+    fn key() -> MinisignKey {
+        MinisignKey::parse(PUBLIC_KEY).unwrap()
+    }
+
+    #[test]
+    fn test_parse_minisign_signature() {
+        let signature = MinisignSignature::parse(PURE_SIGNATURE).unwrap();
+
+        assert_eq!(signature.algorithm(), MinisignAlgorithm::Pure);
+        assert_eq!(signature.key_id(), "E7620F1842B4E81F");
+        assert_eq!(
+            signature.untrusted_comment(),
+            "signature from minisign secret key"
+        );
+        assert_eq!(
+            signature.trusted_comment(),
+            Some("timestamp:1555779966\tfile:test")
+        );
+    }
+
+    #[test]
+    fn test_verify_pure_and_prehashed_signatures() {
+        for text in [PURE_SIGNATURE, PREHASHED_SIGNATURE] {
+            let signature = MinisignSignature::parse(text).unwrap();
+            assert!(
+                signature
+                    .verify(&key(), MinisignMode::Minisign, b"test")
+                    .unwrap()
+            );
+            assert!(
+                !signature
+                    .verify(&key(), MinisignMode::Minisign, b"Test")
+                    .unwrap()
+            );
+        }
+
+        let prehashed = MinisignSignature::parse(PREHASHED_SIGNATURE).unwrap();
+        assert_eq!(prehashed.algorithm(), MinisignAlgorithm::Prehashed);
+    }
+
+    #[test]
+    fn test_tampered_trusted_comment_is_rejected() {
+        let text = PURE_SIGNATURE.replace("file:test", "file:evil");
+        let signature = MinisignSignature::parse(&text).unwrap();
+
+        assert!(
+            !signature
+                .verify(&key(), MinisignMode::Minisign, b"test")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_stripped_trusted_comment_is_rejected() {
+        let stripped = PURE_SIGNATURE
+            .lines()
+            .take(2)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let signature = MinisignSignature::parse(&stripped).unwrap();
+        assert_eq!(signature.mode(), MinisignMode::Signify);
+        assert!(
+            signature
+                .verify(&key(), MinisignMode::Minisign, b"test")
+                .is_err()
+        );
+
+        let stripped = PREHASHED_SIGNATURE
+            .lines()
+            .take(2)
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(MinisignSignature::parse(&stripped).is_err());
+    }
+
+    #[test]
+    fn test_trusted_comment_is_verified_verbatim() {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]);
+        let key_id = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut public_key = b"Ed".to_vec();
+        public_key.extend_from_slice(&key_id);
+        public_key.extend_from_slice(signing_key.verifying_key().as_bytes());
+        let key = MinisignKey::parse(base64_encode(&public_key)).unwrap();
+
+        let signature = signing_key.sign(b"payload").to_bytes();
+        let mut encoded = b"Ed".to_vec();
+        encoded.extend_from_slice(&key_id);
+        encoded.extend_from_slice(&signature);
+        let comment = "  padded comment ";
+        let mut global = signature.to_vec();
+        global.extend_from_slice(comment.as_bytes());
+        let text = format!(
+            "untrusted comment: x\n{}\ntrusted comment: {comment}\n{}\n",
+            base64_encode(&encoded),
+            base64_encode(&signing_key.sign(&global).to_bytes())
+        );
+
+        let parsed = MinisignSignature::parse(&text).unwrap();
+        assert_eq!(parsed.trusted_comment(), Some(comment));
+        assert!(
+            parsed
+                .verify(&key, MinisignMode::Minisign, b"payload")
+                .unwrap()
+        );
+        let trimmed = text.replace(comment, comment.trim());
+        let parsed = MinisignSignature::parse(&trimmed).unwrap();
+        assert!(
+            !parsed
+                .verify(&key, MinisignMode::Minisign, b"payload")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_key_id_mismatch_is_an_error() {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]);
+        let mut encoded = b"Ed".to_vec();
+        encoded.extend_from_slice(&[1u8; 8]);
+        encoded.extend_from_slice(signing_key.verifying_key().as_bytes());
+        let other = MinisignKey::parse(base64_encode(&encoded)).unwrap();
+
+        let signature = MinisignSignature::parse(PURE_SIGNATURE).unwrap();
+        assert!(
+            signature
+                .verify(&other, MinisignMode::Minisign, b"test")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_signify_signature() {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]);
+        let key_id = [1, 2, 3, 4, 5, 6, 7, 8];
+
+        let mut public_key = b"Ed".to_vec();
+        public_key.extend_from_slice(&key_id);
+        public_key.extend_from_slice(signing_key.verifying_key().as_bytes());
+        let public_key = format!(
+            "untrusted comment: signify public key\n{}\n",
+            base64_encode(&public_key)
+        );
+
+        let mut signature = b"Ed".to_vec();
+        signature.extend_from_slice(&key_id);
+        signature.extend_from_slice(&signing_key.sign(b"payload").to_bytes());
+        let signature = format!(
+            "untrusted comment: verify with release.pub\n{}\n",
+            base64_encode(&signature)
+        );
+
+        let key = MinisignKey::parse(public_key).unwrap();
+        let signature = MinisignSignature::parse(&signature).unwrap();
+        assert_eq!(signature.trusted_comment(), None);
+        assert_eq!(signature.mode(), MinisignMode::Signify);
+        assert!(
+            signature
+                .verify(&key, MinisignMode::Minisign, b"payload")
+                .is_err()
+        );
+        assert_eq!(
+            signature
+                .verified(&key, MinisignMode::Signify, b"payload")
+                .unwrap(),
+            Some(VerifiedSignature {
+                scheme: SignatureScheme::Minisign,
+                key_id: key.key_id(),
+                public_key: Some(key.fingerprint()),
+                digest: Some(sha256_digest(&Sha256::digest(b"payload"))),
+            })
+        );
+        assert_eq!(
+            signature
+                .verified(&key, MinisignMode::Signify, b"other")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_signature() {
+        assert!(MinisignSignature::parse("").is_err());
+        assert!(MinisignSignature::parse("untrusted comment: x\nnot base64!\n").is_err());
+        assert!(
+            MinisignSignature::parse(&PURE_SIGNATURE.replace("trusted comment:", "comment:"))
+                .is_err()
+        );
+    }
+
+    fn base64_encode(bytes: &[u8]) -> String {
+        use base64::{Engine as _, engine::general_purpose};
+        general_purpose::STANDARD.encode(bytes)
+    }
+}
@@ -0,0 +1,1661 @@
+//! OpenPGP detached signatures.
+//!
+//! Only what is needed to check a detached signature against a local keyring
+//! is implemented: version 4 public keys and subkeys (RSA and Ed25519),
+//! version 4 binary and text signatures over SHA-2 digests, and both ASCII
+//! armor and binary packet streams.
+//!
+//! Keys in a keyring file only count once their self-signatures check out:
+//! a primary key needs a valid user id or direct-key self-signature, a
+//! subkey needs a valid binding signature and, to sign, a back signature
+//! made by the subkey itself. Revoked keys are dropped, key flags decide
+//! which keys may sign, and a signature must be made while its key was
+//! valid. Third-party certifications are ignored.
+//!
+//! Signatures are also checked against the current time (or
+//! [`OpenPgpVerifier::with_reference_time`]): the signing key must not have
+//! expired, the signature must not have expired or be dated in the future,
+//! and, following RFC 4880 §5.2.3.1, a signature with a hashed subpacket
+//! marked critical that this module does not understand is rejected.
+//!
+//! This is deliberately a small parser rather than `sequoia-openpgp` or
+//! `pgp` (rpgp): both pull in a full OpenPGP stack (symmetric ciphers,
+//! compression, secret keys, message composition) and their own crypto
+//! backends next to the `rsa`, `ed25519-dalek` and `sha2` crates already
+//! used for the other signature schemes, while verifying a detached release
+//! signature needs only the packet framing and key binding checks here.
+//! Anything outside that subset is rejected rather than guessed at.
+
+use std::io::Read;
+use std::path::Path;
+
+use pulith_resource::{SignatureScheme, ValidDigest, VerifiedSignature};
+use rsa::{BigUint, Pkcs1v15Sign};
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+use super::{PublicKeyFormat, SignatureAlgorithm, SignatureConfig, SignatureVerifier};
+use super::{decode_base64, sha256_digest};
+use crate::error::{Error, Result};
+use crate::fetch::FetchReceipt;
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_USER_ATTRIBUTE: u8 = 17;
+
+const SIGNATURE_BINARY: u8 = 0x00;
+const SIGNATURE_TEXT: u8 = 0x01;
+const SIGNATURE_CERTIFICATIONS: [u8; 4] = [0x10, 0x11, 0x12, 0x13];
+const SIGNATURE_SUBKEY_BINDING: u8 = 0x18;
+const SIGNATURE_PRIMARY_KEY_BINDING: u8 = 0x19;
+const SIGNATURE_DIRECT_KEY: u8 = 0x1F;
+const SIGNATURE_KEY_REVOCATION: u8 = 0x20;
+const SIGNATURE_SUBKEY_REVOCATION: u8 = 0x28;
+
+const SUBPACKET_CREATION_TIME: u8 = 2;
+const SUBPACKET_SIGNATURE_EXPIRATION_TIME: u8 = 3;
+const SUBPACKET_KEY_EXPIRATION_TIME: u8 = 9;
+const SUBPACKET_ISSUER_KEY_ID: u8 = 16;
+const SUBPACKET_KEY_FLAGS: u8 = 27;
+const SUBPACKET_EMBEDDED_SIGNATURE: u8 = 32;
+const SUBPACKET_ISSUER_FINGERPRINT: u8 = 33;
+
+/// Hashed subpackets that may be marked critical: the ones read above, and
+/// preferences, key server settings, the primary user id flag and feature
+/// flags, none of which change whether a signature is valid.
+const UNDERSTOOD_SUBPACKETS: &[u8] = &[
+    SUBPACKET_CREATION_TIME,
+    SUBPACKET_SIGNATURE_EXPIRATION_TIME,
+    SUBPACKET_KEY_EXPIRATION_TIME,
+    11, // preferred symmetric algorithms
+    SUBPACKET_ISSUER_KEY_ID,
+    21, // preferred hash algorithms
+    22, // preferred compression algorithms
+    23, // key server preferences
+    25, // primary user id
+    SUBPACKET_KEY_FLAGS,
+    30, // features
+    SUBPACKET_EMBEDDED_SIGNATURE,
+    SUBPACKET_ISSUER_FINGERPRINT,
+];
+
+const KEY_FLAG_SIGN: u8 = 0x02;
+
+const ED25519_LEGACY_OID: &[u8] = &[0x2B, 0x06, 0x01, 0x04, 0x01, 0xDA, 0x47, 0x0F, 0x01];
+const MIN_RSA_BITS: usize = 2048;
+/// How far in the future a signature may be dated, to allow for clock skew.
+const MAX_CLOCK_SKEW: u64 = 300;
+
+/// File extensions read by [`OpenPgpKeyring::from_dir`].
+const KEYRING_EXTENSIONS: &[&str] = &["asc", "gpg", "pgp", "key"];
+
+#[derive(Debug, Clone)]
+enum KeyMaterial {
+    Rsa(rsa::RsaPublicKey),
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+/// A public key or subkey whose self-signatures have been checked.
+#[derive(Debug, Clone)]
+pub struct OpenPgpKey {
+    fingerprint: [u8; 20],
+    primary_fingerprint: [u8; 20],
+    material: KeyMaterial,
+    created_at: u32,
+    expires_at: Option<u64>,
+    signing: bool,
+}
+
+impl OpenPgpKey {
+    /// Get the fingerprint of this key as upper-case hex.
+    pub fn fingerprint(&self) -> String {
+        hex::encode_upper(self.fingerprint)
+    }
+
+    /// Get the fingerprint of the primary key this key belongs to.
+    ///
+    /// For primary keys this is the same as [`OpenPgpKey::fingerprint`].
+    pub fn primary_fingerprint(&self) -> String {
+        hex::encode_upper(self.primary_fingerprint)
+    }
+
+    /// Check whether this key is a subkey.
+    pub fn is_subkey(&self) -> bool {
+        self.fingerprint != self.primary_fingerprint
+    }
+
+    /// Check whether this key may make signatures.
+    ///
+    /// Subkeys additionally need a back signature proving they agree to
+    /// belong to their primary key.
+    pub fn can_sign(&self) -> bool {
+        self.signing
+    }
+
+    /// Get the time this key, or its primary key, expires (Unix timestamp).
+    pub fn expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
+
+    /// Check whether a signature made at `time` falls in this key's lifetime,
+    /// and the key has not expired by `now`.
+    fn valid_at(&self, time: Option<u32>, now: u64) -> bool {
+        time.is_some_and(|time| {
+            time >= self.created_at
+                && self
+                    .expires_at
+                    .is_none_or(|expires_at| u64::from(time) < expires_at && now < expires_at)
+        })
+    }
+
+    fn matches_issuer(&self, issuer: &Issuer) -> bool {
+        match issuer {
+            Issuer::Fingerprint(fingerprint) => self.fingerprint.as_slice() == fingerprint,
+            Issuer::KeyId(key_id) => self.fingerprint[12..] == key_id[..],
+        }
+    }
+}
+
+/// A set of OpenPGP public keys used to verify signatures.
+#[derive(Debug, Clone, Default)]
+pub struct OpenPgpKeyring {
+    keys: Vec<OpenPgpKey>,
+}
+
+impl OpenPgpKeyring {
+    /// Create an empty keyring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load every `.asc`, `.gpg`, `.pgp` and `.key` file in a directory.
+    ///
+    /// Files are read in name order and may hold armored or binary exports
+    /// of one or more keys. Other files, such as GnuPG `pubring.kbx`, are
+    /// ignored.
+    pub fn from_dir(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let read_error = |path: &Path, source| {
+            Error::Fs(pulith_fs::Error::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+        };
+
+        let mut entries = std::fs::read_dir(path)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<std::io::Result<Vec<_>>>()
+            })
+            .map_err(|source| read_error(path, source))?;
+        entries.sort();
+
+        let mut keyring = Self::new();
+        for entry in entries {
+            let is_key_file = entry.is_file()
+                && entry
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| KEYRING_EXTENSIONS.contains(&extension));
+            if !is_key_file {
+                continue;
+            }
+
+            let data = std::fs::read(&entry).map_err(|source| read_error(&entry, source))?;
+            keyring.add_keys(&data).map_err(|e| {
+                Error::InvalidState(format!(
+                    "Invalid OpenPGP keyring file {}: {}",
+                    entry.display(),
+                    e
+                ))
+            })?;
+        }
+
+        Ok(keyring)
+    }
+
+    /// Add the keys from an armored or binary key export.
+    ///
+    /// Keys with unsupported algorithms, RSA moduli below 2048 bits, no
+    /// valid self-signature or binding signature, or a valid revocation are
+    /// skipped. Returns the number of keys added.
+    pub fn add_keys(&mut self, data: &[u8]) -> Result<usize> {
+        let data = dearmor(data)?;
+        let before = self.keys.len();
+        for certificate in certificates(&packets(&data)?)? {
+            self.add_certificate(&certificate)?;
+        }
+        Ok(self.keys.len() - before)
+    }
+
+    fn add_certificate(&mut self, certificate: &Certificate<'_>) -> Result<()> {
+        let fingerprint = v4_fingerprint(certificate.primary)?;
+        let skip = |reason: &str| {
+            tracing::debug!(
+                fingerprint = %hex::encode_upper(fingerprint),
+                reason,
+                "skipping OpenPGP key"
+            );
+        };
+        let Some(material) = parse_key_material(certificate.primary)? else {
+            skip("unsupported key");
+            return Ok(());
+        };
+        let primary = key_prefix(certificate.primary)?;
+
+        let revoked = certificate.direct.iter().any(|signature| {
+            signature.signature_type == SIGNATURE_KEY_REVOCATION
+                && signature.verify_over(&material, &[&primary])
+        });
+        if revoked {
+            skip("revoked");
+            return Ok(());
+        }
+
+        let direct = certificate.direct.iter().filter(|signature| {
+            signature.signature_type == SIGNATURE_DIRECT_KEY
+                && signature.verify_over(&material, &[&primary])
+        });
+        let certifications = certificate
+            .user_ids
+            .iter()
+            .flat_map(|(user_id, signatures)| {
+                signatures.iter().filter(|signature| {
+                    SIGNATURE_CERTIFICATIONS.contains(&signature.signature_type)
+                        && signature.verify_over(&material, &[&primary, user_id])
+                })
+            });
+        let Some(self_signature) = direct
+            .chain(certifications)
+            .max_by_key(|signature| signature.created_at)
+        else {
+            skip("no valid self-signature");
+            return Ok(());
+        };
+        let created_at = key_created_at(certificate.primary)?;
+        let expires_at = self_signature.key_expires_at(created_at);
+
+        let mut subkeys = Vec::new();
+        for (body, signatures) in &certificate.subkeys {
+            let subkey_fingerprint = v4_fingerprint(body)?;
+            let skip = |reason: &str| {
+                tracing::debug!(
+                    fingerprint = %hex::encode_upper(subkey_fingerprint),
+                    reason,
+                    "skipping OpenPGP subkey"
+                );
+            };
+            let Some(subkey_material) = parse_key_material(body)? else {
+                skip("unsupported key");
+                continue;
+            };
+            let subkey = key_prefix(body)?;
+            let parts: [&[u8]; 2] = [&primary, &subkey];
+
+            let revoked = signatures.iter().any(|signature| {
+                signature.signature_type == SIGNATURE_SUBKEY_REVOCATION
+                    && signature.verify_over(&material, &parts)
+            });
+            if revoked {
+                skip("revoked");
+                continue;
+            }
+            let Some(binding) = signatures
+                .iter()
+                .filter(|signature| {
+                    signature.signature_type == SIGNATURE_SUBKEY_BINDING
+                        && signature.verify_over(&material, &parts)
+                })
+                .max_by_key(|signature| signature.created_at)
+            else {
+                skip("no valid binding signature");
+                continue;
+            };
+
+            let back_signed = binding.embedded.as_deref().is_some_and(|back| {
+                back.signature_type == SIGNATURE_PRIMARY_KEY_BINDING
+                    && back.verify_over(&subkey_material, &parts)
+            });
+            let subkey_created_at = key_created_at(body)?;
+            let subkey_expires_at = match (binding.key_expires_at(subkey_created_at), expires_at) {
+                (Some(subkey), Some(primary)) => Some(subkey.min(primary)),
+                (subkey, primary) => subkey.or(primary),
+            };
+            subkeys.push(OpenPgpKey {
+                fingerprint: subkey_fingerprint,
+                primary_fingerprint: fingerprint,
+                material: subkey_material,
+                created_at: subkey_created_at,
+                expires_at: subkey_expires_at,
+                signing: binding.allows_signing() && back_signed,
+            });
+        }
+
+        self.keys.push(OpenPgpKey {
+            fingerprint,
+            primary_fingerprint: fingerprint,
+            material,
+            created_at,
+            expires_at,
+            signing: self_signature.allows_signing(),
+        });
+        self.keys.extend(subkeys);
+        Ok(())
+    }
+
+    /// Get the keys in this keyring.
+    pub fn keys(&self) -> &[OpenPgpKey] {
+        &self.keys
+    }
+
+    /// Get the number of keys in this keyring.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check whether this keyring is empty.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Signing keys matching `issuer`; a short key id can match several.
+    fn find<'a>(&'a self, issuer: &'a Issuer) -> impl Iterator<Item = &'a OpenPgpKey> {
+        self.keys
+            .iter()
+            .filter(move |key| key.signing && key.matches_issuer(issuer))
+    }
+}
+
+/// A primary key with the user ids, subkeys and signatures that follow it.
+struct Certificate<'a> {
+    primary: &'a [u8],
+    direct: Vec<OpenPgpSignature>,
+    /// User ids and attributes in their hashed form, with their signatures.
+    user_ids: Vec<(Vec<u8>, Vec<OpenPgpSignature>)>,
+    subkeys: Vec<(&'a [u8], Vec<OpenPgpSignature>)>,
+}
+
+/// Group a key export into certificates. Signatures this module cannot
+/// parse, such as third-party certifications with other algorithms, are
+/// dropped.
+fn certificates<'a>(packets: &[Packet<'a>]) -> Result<Vec<Certificate<'a>>> {
+    let mut certificates: Vec<Certificate<'a>> = Vec::new();
+    // Signatures belong to the key or user id packet that precedes them.
+    let mut signed = TAG_PUBLIC_KEY;
+    for packet in packets {
+        if packet.tag == TAG_PUBLIC_KEY {
+            signed = TAG_PUBLIC_KEY;
+            certificates.push(Certificate {
+                primary: packet.body,
+                direct: Vec::new(),
+                user_ids: Vec::new(),
+                subkeys: Vec::new(),
+            });
+            continue;
+        }
+        let Some(certificate) = certificates.last_mut() else {
+            if packet.tag == TAG_PUBLIC_SUBKEY {
+                return Err(invalid("public subkey without a primary key"));
+            }
+            continue;
+        };
+
+        match packet.tag {
+            TAG_USER_ID | TAG_USER_ATTRIBUTE => {
+                let marker = if packet.tag == TAG_USER_ID {
+                    0xB4
+                } else {
+                    0xD1
+                };
+                let length =
+                    u32::try_from(packet.body.len()).map_err(|_| invalid("user id is too long"))?;
+                let mut hashed = vec![marker];
+                hashed.extend_from_slice(&length.to_be_bytes());
+                hashed.extend_from_slice(packet.body);
+                certificate.user_ids.push((hashed, Vec::new()));
+                signed = TAG_USER_ID;
+            }
+            TAG_PUBLIC_SUBKEY => {
+                certificate.subkeys.push((packet.body, Vec::new()));
+                signed = TAG_PUBLIC_SUBKEY;
+            }
+            TAG_SIGNATURE => {
+                let signature = match OpenPgpSignature::parse_packet(packet.body, true) {
+                    Ok(signature) => signature,
+                    Err(error) => {
+                        tracing::debug!(%error, "skipping OpenPGP key signature");
+                        continue;
+                    }
+                };
+                let signatures = match signed {
+                    TAG_USER_ID => certificate.user_ids.last_mut().map(|(_, s)| s),
+                    TAG_PUBLIC_SUBKEY => certificate.subkeys.last_mut().map(|(_, s)| s),
+                    _ => None,
+                };
+                signatures
+                    .unwrap_or(&mut certificate.direct)
+                    .push(signature);
+            }
+            _ => {}
+        }
+    }
+    Ok(certificates)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Issuer {
+    Fingerprint(Vec<u8>),
+    KeyId([u8; 8]),
+}
+
+impl Issuer {
+    fn to_hex(&self) -> String {
+        match self {
+            Issuer::Fingerprint(fingerprint) => hex::encode_upper(fingerprint),
+            Issuer::KeyId(key_id) => hex::encode_upper(key_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SignatureMaterial {
+    Rsa(Vec<u8>),
+    Ed25519([u8; 64]),
+}
+
+/// A parsed OpenPGP signature packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenPgpSignature {
+    signature_type: u8,
+    hash_algorithm: u8,
+    hashed: Vec<u8>,
+    left16: [u8; 2],
+    issuer: Option<Issuer>,
+    created_at: Option<u32>,
+    expiration: Option<u32>,
+    key_flags: Option<u8>,
+    key_expiration: Option<u32>,
+    embedded: Option<Box<OpenPgpSignature>>,
+    material: SignatureMaterial,
+}
+
+impl OpenPgpSignature {
+    /// Parse every signature packet in an armored or binary detached signature.
+    pub fn parse_all(data: &[u8]) -> Result<Vec<Self>> {
+        let data = dearmor(data)?;
+        let signatures = packets(&data)?
+            .into_iter()
+            .filter(|packet| packet.tag == TAG_SIGNATURE)
+            .map(|packet| {
+                let signature = Self::parse_packet(packet.body, false)?;
+                if ![SIGNATURE_BINARY, SIGNATURE_TEXT].contains(&signature.signature_type) {
+                    return Err(invalid(&format!(
+                        "signature type {:#04x} is not a document signature",
+                        signature.signature_type
+                    )));
+                }
+                Ok(signature)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if signatures.is_empty() {
+            return Err(invalid("no signature packet found"));
+        }
+        Ok(signatures)
+    }
+
+    /// Parse a signature packet body, and with `embedded` the signature
+    /// embedded in it (a subkey's back signature).
+    fn parse_packet(body: &[u8], embedded: bool) -> Result<Self> {
+        let mut cursor = Cursor(body);
+        let version = cursor.u8()?;
+        if version != 4 {
+            return Err(invalid(&format!(
+                "signature version {} is not supported",
+                version
+            )));
+        }
+
+        let signature_type = cursor.u8()?;
+        let public_key_algorithm = cursor.u8()?;
+        let hash_algorithm = cursor.u8()?;
+        let hashed_length = cursor.u16()?;
+        let hashed_subpackets = cursor.take(hashed_length)?;
+        let hashed = body[..6 + hashed_length].to_vec();
+        let unhashed_length = cursor.u16()?;
+        let unhashed_subpackets = cursor.take(unhashed_length)?;
+        let left16 = [cursor.u8()?, cursor.u8()?];
+
+        let material = match public_key_algorithm {
+            1 | 3 => SignatureMaterial::Rsa(cursor.mpi()?.to_vec()),
+            22 => {
+                let mut signature = [0u8; 64];
+                copy_right_aligned(&mut signature[..32], cursor.mpi()?)?;
+                copy_right_aligned(&mut signature[32..], cursor.mpi()?)?;
+                SignatureMaterial::Ed25519(signature)
+            }
+            27 => {
+                let mut signature = [0u8; 64];
+                signature.copy_from_slice(cursor.take(64)?);
+                SignatureMaterial::Ed25519(signature)
+            }
+            other => {
+                return Err(invalid(&format!(
+                    "public key algorithm {} is not supported",
+                    other
+                )));
+            }
+        };
+
+        let mut issuer = None;
+        let mut created_at = None;
+        let mut expiration = None;
+        let mut key_flags = None;
+        let mut key_expiration = None;
+        // Only hashed subpackets are covered by the signature, so only they
+        // can make it invalid through the critical bit.
+        for (kind, critical, value) in subpackets(hashed_subpackets)? {
+            if critical && !UNDERSTOOD_SUBPACKETS.contains(&kind) {
+                return Err(invalid(&format!("unknown critical subpacket {}", kind)));
+            }
+            match (kind, value) {
+                (SUBPACKET_CREATION_TIME, [a, b, c, d]) => {
+                    created_at.get_or_insert(u32::from_be_bytes([*a, *b, *c, *d]));
+                }
+                (SUBPACKET_SIGNATURE_EXPIRATION_TIME, [a, b, c, d]) => {
+                    expiration.get_or_insert(u32::from_be_bytes([*a, *b, *c, *d]));
+                }
+                (SUBPACKET_KEY_EXPIRATION_TIME, [a, b, c, d]) => {
+                    key_expiration.get_or_insert(u32::from_be_bytes([*a, *b, *c, *d]));
+                }
+                (SUBPACKET_KEY_FLAGS, [flags, ..]) => {
+                    key_flags.get_or_insert(*flags);
+                }
+                _ => {}
+            }
+        }
+
+        let mut embedded_signature = None;
+        // Hashed subpackets come first so they win over unhashed ones. The
+        // issuer is only a hint and the back signature is verified itself.
+        for (kind, _, value) in subpackets(hashed_subpackets)?
+            .into_iter()
+            .chain(subpackets(unhashed_subpackets)?)
+        {
+            match (kind, value) {
+                (SUBPACKET_EMBEDDED_SIGNATURE, value)
+                    if embedded && embedded_signature.is_none() =>
+                {
+                    embedded_signature = Self::parse_packet(value, false).ok().map(Box::new);
+                }
+                (SUBPACKET_ISSUER_FINGERPRINT, [4, fingerprint @ ..])
+                    if fingerprint.len() == 20
+                        && !matches!(issuer, Some(Issuer::Fingerprint(_))) =>
+                {
+                    issuer = Some(Issuer::Fingerprint(fingerprint.to_vec()));
+                }
+                (SUBPACKET_ISSUER_KEY_ID, key_id) if key_id.len() == 8 && issuer.is_none() => {
+                    let mut bytes = [0u8; 8];
+                    bytes.copy_from_slice(key_id);
+                    issuer = Some(Issuer::KeyId(bytes));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            signature_type,
+            hash_algorithm,
+            hashed,
+            left16,
+            issuer,
+            created_at,
+            expiration,
+            key_flags,
+            key_expiration,
+            embedded: embedded_signature,
+            material,
+        })
+    }
+
+    /// Get the fingerprint or key id of the issuing key, as upper-case hex.
+    pub fn issuer(&self) -> Option<String> {
+        self.issuer.as_ref().map(Issuer::to_hex)
+    }
+
+    /// Get the signature creation time (Unix timestamp).
+    pub fn created_at(&self) -> Option<u64> {
+        self.created_at.map(u64::from)
+    }
+
+    /// Get the time this signature expires (Unix timestamp).
+    pub fn expires_at(&self) -> Option<u64> {
+        let created_at = self.created_at?;
+        self.expiration
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| u64::from(created_at) + u64::from(seconds))
+    }
+
+    /// Check whether this signature was current at `now`: not dated in the
+    /// future and not expired.
+    fn current_at(&self, now: u64) -> bool {
+        self.created_at
+            .is_some_and(|created_at| u64::from(created_at) <= now + MAX_CLOCK_SKEW)
+            && self.expires_at().is_none_or(|expires_at| now < expires_at)
+    }
+
+    /// Check whether this is a canonical text signature.
+    pub fn is_text(&self) -> bool {
+        self.signature_type == SIGNATURE_TEXT
+    }
+
+    /// Whether a self-signature lets its key sign; keys without key flags
+    /// may be used for anything their algorithm allows.
+    fn allows_signing(&self) -> bool {
+        self.key_flags
+            .is_none_or(|flags| flags & KEY_FLAG_SIGN != 0)
+    }
+
+    /// When a key created at `created_at` expires according to this
+    /// self-signature.
+    fn key_expires_at(&self, created_at: u32) -> Option<u64> {
+        self.key_expiration
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| u64::from(created_at) + u64::from(seconds))
+    }
+
+    /// Verify a key signature over the hashed forms of the keys and user id
+    /// it binds.
+    fn verify_over(&self, key: &KeyMaterial, parts: &[&[u8]]) -> bool {
+        let Ok(mut hash) = HashState::new(self.hash_algorithm) else {
+            return false;
+        };
+        for part in parts {
+            hash.update(part);
+        }
+        self.hash_trailer(&mut hash);
+        self.verify_digest(key, &hash.finalize())
+    }
+
+    fn hash_trailer(&self, hash: &mut HashState) {
+        hash.update(&self.hashed);
+        hash.update(&[0x04, 0xFF]);
+        hash.update(&(self.hashed.len() as u32).to_be_bytes());
+    }
+
+    fn verify_digest(&self, key: &KeyMaterial, digest: &[u8]) -> bool {
+        if digest[..2] != self.left16 {
+            return false;
+        }
+
+        match (key, &self.material) {
+            (KeyMaterial::Rsa(key), SignatureMaterial::Rsa(signature)) => {
+                let Some(scheme) = pkcs1v15_scheme(self.hash_algorithm) else {
+                    return false;
+                };
+                let mut padded = vec![0u8; rsa::traits::PublicKeyParts::size(key)];
+                if copy_right_aligned(&mut padded, signature).is_err() {
+                    return false;
+                }
+                key.verify(scheme, digest, &padded).is_ok()
+            }
+            (KeyMaterial::Ed25519(key), SignatureMaterial::Ed25519(signature)) => key
+                .verify_strict(digest, &ed25519_dalek::Signature::from_bytes(signature))
+                .is_ok(),
+            _ => false,
+        }
+    }
+}
+
+/// Result of a successful OpenPGP signature check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenPgpVerification {
+    /// Fingerprint of the key that made the signature.
+    pub fingerprint: String,
+    /// Fingerprint of the primary key owning the signing key.
+    pub primary_fingerprint: String,
+    /// Signature creation time (Unix timestamp).
+    pub created_at: Option<u64>,
+    /// SHA-256 digest of the verified content.
+    pub digest: ValidDigest,
+}
+
+impl OpenPgpVerification {
+    /// Describe this verification for `pulith-resource` trust evaluation.
+    ///
+    /// The primary key fingerprint is used as key id, since that is what
+    /// publishers list for their release keys.
+    pub fn verified_signature(&self) -> VerifiedSignature {
+        VerifiedSignature {
+            scheme: SignatureScheme::OpenPgp,
+            key_id: self.primary_fingerprint.clone(),
+            public_key: None,
+            digest: Some(self.digest.clone()),
+        }
+    }
+}
+
+/// OpenPGP detached signature verifier backed by a local keyring.
+///
+/// Not registered by [`super::SignatureManager::new`] because it needs a
+/// keyring; add it with [`super::SignatureManager::add_verifier`].
+#[derive(Debug, Clone, Default)]
+pub struct OpenPgpVerifier {
+    keyring: OpenPgpKeyring,
+    reference_time: Option<u64>,
+}
+
+impl OpenPgpVerifier {
+    /// Create a verifier for the given keyring.
+    pub fn new(keyring: OpenPgpKeyring) -> Self {
+        Self {
+            keyring,
+            reference_time: None,
+        }
+    }
+
+    /// Check key and signature expiry at `time` (Unix timestamp) instead of
+    /// the current time.
+    #[must_use]
+    pub fn with_reference_time(mut self, time: u64) -> Self {
+        self.reference_time = Some(time);
+        self
+    }
+
+    fn now(&self) -> u64 {
+        self.reference_time.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        })
+    }
+
+    /// Create a verifier from a keyring directory, see [`OpenPgpKeyring::from_dir`].
+    pub fn from_keyring_dir(path: impl AsRef<Path>) -> Result<Self> {
+        OpenPgpKeyring::from_dir(path).map(Self::new)
+    }
+
+    /// Get the keyring used by this verifier.
+    pub fn keyring(&self) -> &OpenPgpKeyring {
+        &self.keyring
+    }
+
+    /// Verify `data` against an armored or binary detached signature.
+    ///
+    /// Returns `Ok(None)` when the signature does not match the data, and an
+    /// error when the signature is malformed or made by a key that is not in
+    /// the keyring.
+    pub fn verify_detached(
+        &self,
+        data: &[u8],
+        signature: &[u8],
+    ) -> Result<Option<OpenPgpVerification>> {
+        self.verify_reader(data, signature)
+    }
+
+    /// Verify a file against an armored or binary detached signature.
+    pub fn verify_file(
+        &self,
+        path: impl AsRef<Path>,
+        signature: &[u8],
+    ) -> Result<Option<OpenPgpVerification>> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path).map_err(|source| {
+            Error::Fs(pulith_fs::Error::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+        })?;
+        self.verify_reader(file, signature)
+    }
+
+    /// Verify a fetched file and record the signing key in its receipt.
+    ///
+    /// A signature that does not match the fetched content is an error.
+    pub fn verify_receipt(
+        &self,
+        receipt: &mut FetchReceipt,
+        signature: &[u8],
+    ) -> Result<OpenPgpVerification> {
+        let verification = self
+            .verify_file(&receipt.destination, signature)?
+            .ok_or_else(|| {
+                Error::InvalidState(format!(
+                    "OpenPGP signature does not match {}",
+                    receipt.destination.display()
+                ))
+            })?;
+        receipt.signatures.push(verification.verified_signature());
+        Ok(verification)
+    }
+
+    fn verify_reader(
+        &self,
+        mut reader: impl Read,
+        signature: &[u8],
+    ) -> Result<Option<OpenPgpVerification>> {
+        let signatures = OpenPgpSignature::parse_all(signature)?;
+        let now = self.now();
+        let candidates = signatures
+            .iter()
+            .filter(|signature| signature.current_at(now))
+            .flat_map(|signature| {
+                signature
+                    .issuer
+                    .iter()
+                    .flat_map(|issuer| self.keyring.find(issuer))
+                    .filter(move |key| key.valid_at(signature.created_at, now))
+                    .map(move |key| (signature, key))
+            })
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            let issuers = signatures
+                .iter()
+                .map(|signature| signature.issuer().unwrap_or_else(|| "unknown".to_string()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(Error::InvalidState(format!(
+                "OpenPGP signature issuer {} is not a signing key in the keyring, \
+                 the key has expired or was not valid when the signature was made, \
+                 or the signature has expired or is dated in the future",
+                issuers
+            )));
+        }
+
+        let mut hashers = candidates
+            .iter()
+            .map(|(signature, _)| DocumentHasher::new(signature))
+            .collect::<Result<Vec<_>>>()?;
+        let mut content = Sha256::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            content.update(&buffer[..read]);
+            for hasher in &mut hashers {
+                hasher.update(&buffer[..read]);
+            }
+        }
+        let content = sha256_digest(&content.finalize());
+
+        Ok(candidates
+            .into_iter()
+            .zip(hashers)
+            .find_map(|((signature, key), hasher)| {
+                let digest = hasher.finalize(signature);
+                signature
+                    .verify_digest(&key.material, &digest)
+                    .then(|| OpenPgpVerification {
+                        fingerprint: key.fingerprint(),
+                        primary_fingerprint: key.primary_fingerprint(),
+                        created_at: signature.created_at(),
+                        digest: content.clone(),
+                    })
+            }))
+    }
+}
+
+impl SignatureVerifier for OpenPgpVerifier {
+    fn verify(&self, data: &[u8], config: &SignatureConfig) -> Result<bool> {
+        config.validate()?;
+        let signature = config.signature.decode_data()?;
+
+        let verification = if config.public_key.format == PublicKeyFormat::OpenPgp
+            && !config.public_key.data.is_empty()
+        {
+            let mut keyring = self.keyring.clone();
+            keyring.add_keys(&config.public_key.data)?;
+            Self {
+                keyring,
+                reference_time: self.reference_time,
+            }
+            .verify_detached(data, &signature)?
+        } else {
+            self.verify_detached(data, &signature)?
+        };
+
+        Ok(verification.is_some())
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::OpenPgp
+    }
+}
+
+enum HashState {
+    Sha224(Sha224),
+    Sha256(Sha256),
+    Sha384(Sha384),
+    Sha512(Sha512),
+}
+
+impl HashState {
+    fn new(algorithm: u8) -> Result<Self> {
+        match algorithm {
+            8 => Ok(Self::Sha256(Sha256::new())),
+            9 => Ok(Self::Sha384(Sha384::new())),
+            10 => Ok(Self::Sha512(Sha512::new())),
+            11 => Ok(Self::Sha224(Sha224::new())),
+            2 => Err(invalid("SHA-1 signatures are not accepted")),
+            other => Err(invalid(&format!(
+                "hash algorithm {} is not supported",
+                other
+            ))),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha224(hash) => hash.update(data),
+            Self::Sha256(hash) => hash.update(data),
+            Self::Sha384(hash) => hash.update(data),
+            Self::Sha512(hash) => hash.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Self::Sha224(hash) => hash.finalize().to_vec(),
+            Self::Sha256(hash) => hash.finalize().to_vec(),
+            Self::Sha384(hash) => hash.finalize().to_vec(),
+            Self::Sha512(hash) => hash.finalize().to_vec(),
+        }
+    }
+}
+
+fn pkcs1v15_scheme(algorithm: u8) -> Option<Pkcs1v15Sign> {
+    match algorithm {
+        8 => Some(Pkcs1v15Sign::new::<Sha256>()),
+        9 => Some(Pkcs1v15Sign::new::<Sha384>()),
+        10 => Some(Pkcs1v15Sign::new::<Sha512>()),
+        11 => Some(Pkcs1v15Sign::new::<Sha224>()),
+        _ => None,
+    }
+}
+
+/// Hashes signed content, converting CRLF, LF and lone CR line endings to
+/// CRLF for text signatures.
+struct DocumentHasher {
+    hash: HashState,
+    text: bool,
+    previous_cr: bool,
+}
+
+impl DocumentHasher {
+    fn new(signature: &OpenPgpSignature) -> Result<Self> {
+        Ok(Self {
+            hash: HashState::new(signature.hash_algorithm)?,
+            text: signature.is_text(),
+            previous_cr: false,
+        })
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        if !self.text {
+            self.hash.update(data);
+            return;
+        }
+
+        let mut start = 0;
+        for (index, byte) in data.iter().enumerate() {
+            if matches!(byte, b'\r' | b'\n') {
+                self.hash.update(&data[start..index]);
+                // The LF of a CRLF was already written with its CR.
+                if !(*byte == b'\n' && self.previous_cr) {
+                    self.hash.update(b"\r\n");
+                }
+                start = index + 1;
+            }
+            self.previous_cr = *byte == b'\r';
+        }
+        self.hash.update(&data[start..]);
+    }
+
+    fn finalize(mut self, signature: &OpenPgpSignature) -> Vec<u8> {
+        signature.hash_trailer(&mut self.hash);
+        self.hash.finalize()
+    }
+}
+
+fn parse_key_material(body: &[u8]) -> Result<Option<KeyMaterial>> {
+    let mut cursor = Cursor(body);
+    if cursor.u8()? != 4 {
+        return Ok(None);
+    }
+    cursor.take(4)?; // creation time
+
+    let material = match cursor.u8()? {
+        1 | 3 => {
+            let modulus = BigUint::from_bytes_be(cursor.mpi()?);
+            let exponent = BigUint::from_bytes_be(cursor.mpi()?);
+            if modulus.bits() < MIN_RSA_BITS {
+                return Ok(None);
+            }
+            rsa::RsaPublicKey::new(modulus, exponent)
+                .map(KeyMaterial::Rsa)
+                .map_err(|e| invalid(&format!("invalid RSA key: {}", e)))?
+        }
+        22 => {
+            let oid_length = usize::from(cursor.u8()?);
+            if cursor.take(oid_length)? != ED25519_LEGACY_OID {
+                return Ok(None);
+            }
+            match cursor.mpi()? {
+                [0x40, point @ ..] => ed25519_key(point)?,
+                _ => return Err(invalid("invalid EdDSA key encoding")),
+            }
+        }
+        27 => ed25519_key(cursor.take(32)?)?,
+        _ => return Ok(None),
+    };
+
+    Ok(Some(material))
+}
+
+fn ed25519_key(bytes: &[u8]) -> Result<KeyMaterial> {
+    let bytes = <[u8; 32]>::try_from(bytes).map_err(|_| invalid("invalid Ed25519 key length"))?;
+    ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+        .map(KeyMaterial::Ed25519)
+        .map_err(|e| invalid(&format!("invalid Ed25519 key: {}", e)))
+}
+
+fn key_created_at(body: &[u8]) -> Result<u32> {
+    let mut cursor = Cursor(body);
+    cursor.u8()?; // version
+    let bytes = cursor.take(4)?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// The form in which a key packet is hashed, for fingerprints and the
+/// signatures that bind it.
+fn key_prefix(body: &[u8]) -> Result<Vec<u8>> {
+    let length = u16::try_from(body.len()).map_err(|_| invalid("key packet is too long"))?;
+    let mut prefix = vec![0x99];
+    prefix.extend_from_slice(&length.to_be_bytes());
+    prefix.extend_from_slice(body);
+    Ok(prefix)
+}
+
+fn v4_fingerprint(body: &[u8]) -> Result<[u8; 20]> {
+    Ok(Sha1::digest(key_prefix(body)?).into())
+}
+
+struct Packet<'a> {
+    tag: u8,
+    body: &'a [u8],
+}
+
+fn packets(mut data: &[u8]) -> Result<Vec<Packet<'_>>> {
+    let mut packets = Vec::new();
+    while let Some((&header, rest)) = data.split_first() {
+        if header & 0x80 == 0 {
+            return Err(invalid("not an OpenPGP packet stream"));
+        }
+
+        let (tag, length, rest) = if header & 0x40 != 0 {
+            let (length, rest) = match rest {
+                [first @ 0..=191, rest @ ..] => (usize::from(*first), rest),
+                [first @ 192..=223, second, rest @ ..] => (
+                    ((usize::from(*first) - 192) << 8) + usize::from(*second) + 192,
+                    rest,
+                ),
+                [255, rest @ ..] => read_be(rest, 4)?,
+                [224..=254, ..] => return Err(invalid("partial body lengths are not supported")),
+                _ => return Err(invalid("truncated packet header")),
+            };
+            (header & 0x3F, length, rest)
+        } else {
+            let (length, rest) = match header & 0x03 {
+                0 => read_be(rest, 1)?,
+                1 => read_be(rest, 2)?,
+                2 => read_be(rest, 4)?,
+                _ => (rest.len(), rest),
+            };
+            ((header >> 2) & 0x0F, length, rest)
+        };
+
+        if rest.len() < length {
+            return Err(invalid("truncated packet"));
+        }
+        let (body, rest) = rest.split_at(length);
+        packets.push(Packet { tag, body });
+        data = rest;
+    }
+
+    Ok(packets)
+}
+
+/// Split a subpacket area into (type, critical, body) triples.
+fn subpackets(mut data: &[u8]) -> Result<Vec<(u8, bool, &[u8])>> {
+    let mut subpackets = Vec::new();
+    while !data.is_empty() {
+        let (length, rest) = match data {
+            [first @ 0..=191, rest @ ..] => (usize::from(*first), rest),
+            [first @ 192..=254, second, rest @ ..] => (
+                ((usize::from(*first) - 192) << 8) + usize::from(*second) + 192,
+                rest,
+            ),
+            [255, rest @ ..] => read_be(rest, 4)?,
+            _ => return Err(invalid("truncated subpacket")),
+        };
+        if length == 0 || rest.len() < length {
+            return Err(invalid("truncated subpacket"));
+        }
+
+        let (body, rest) = rest.split_at(length);
+        subpackets.push((body[0] & 0x7F, body[0] & 0x80 != 0, &body[1..]));
+        data = rest;
+    }
+
+    Ok(subpackets)
+}
+
+fn read_be(data: &[u8], width: usize) -> Result<(usize, &[u8])> {
+    if data.len() < width {
+        return Err(invalid("truncated packet"));
+    }
+    let (value, rest) = data.split_at(width);
+    let value = value
+        .iter()
+        .fold(0usize, |acc, byte| (acc << 8) | usize::from(*byte));
+    Ok((value, rest))
+}
+
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        if self.0.len() < length {
+            return Err(invalid("truncated packet body"));
+        }
+        let (value, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(value)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<usize> {
+        read_be(self.take(2)?, 2).map(|(value, _)| value)
+    }
+
+    fn mpi(&mut self) -> Result<&'a [u8]> {
+        let bits = self.u16()?;
+        self.take(bits.div_ceil(8))
+    }
+}
+
+fn copy_right_aligned(target: &mut [u8], value: &[u8]) -> Result<()> {
+    let offset = target
+        .len()
+        .checked_sub(value.len())
+        .ok_or_else(|| invalid("signature value is too long"))?;
+    target[offset..].copy_from_slice(value);
+    Ok(())
+}
+
+/// Strip ASCII armor if present, concatenating the packets of every block.
+fn dearmor(data: &[u8]) -> Result<Vec<u8>> {
+    if !data.trim_ascii_start().starts_with(b"-----BEGIN PGP ") {
+        return Ok(data.to_vec());
+    }
+
+    let text = std::str::from_utf8(data).map_err(|e| invalid(&e.to_string()))?;
+    let mut lines = text.lines().map(str::trim);
+    let mut packets = Vec::new();
+
+    while lines
+        .by_ref()
+        .any(|line| line.starts_with("-----BEGIN PGP "))
+    {
+        let mut body = String::new();
+        let mut checksum = None;
+        let mut in_headers = true;
+        let mut terminated = false;
+
+        for line in lines.by_ref() {
+            if line.starts_with("-----END PGP ") {
+                terminated = true;
+                break;
+            }
+            if in_headers {
+                // Armor headers such as `Version: ...` end at the first blank line.
+                if line.contains(':') {
+                    continue;
+                }
+                in_headers = false;
+                if line.is_empty() {
+                    continue;
+                }
+            }
+            match line.strip_prefix('=') {
+                Some(crc) if line.len() == 5 => checksum = Some(crc),
+                _ => body.push_str(line),
+            }
+        }
+        if !terminated {
+            return Err(invalid("armor is missing its END line"));
+        }
+
+        let decoded = decode_base64(body.as_bytes()).map_err(|e| invalid(&e.to_string()))?;
+        if let Some(checksum) = checksum {
+            let expected =
+                decode_base64(checksum.as_bytes()).map_err(|e| invalid(&e.to_string()))?;
+            if expected != crc24(&decoded).to_be_bytes()[1..] {
+                return Err(invalid("armor checksum mismatch"));
+            }
+        }
+        packets.extend_from_slice(&decoded);
+    }
+
+    Ok(packets)
+}
+
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc = 0x00B7_04CEu32;
+    for byte in data {
+        crc ^= u32::from(*byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= 0x0186_4CFB;
+            }
+        }
+    }
+    crc & 0x00FF_FFFF
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidState(format!("Invalid OpenPGP data: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer as _, SigningKey};
+
+    #[test]
+    fn test_crc24_matches_rfc_initial_value() {
+        assert_eq!(crc24(b""), 0x00B7_04CE);
+        assert_eq!(crc24(b"123456789"), 0x0021_CF02);
+    }
+
+    #[test]
+    fn test_packets_parse_old_and_new_headers() {
+        let data = [0x88, 0x02, 0xAA, 0xBB, 0xC6, 0x01, 0xCC];
+        let packets = packets(&data).unwrap();
+
+        assert_eq!(packets.len(), 2);
+        assert_eq!((packets[0].tag, packets[0].body), (2, &[0xAA, 0xBB][..]));
+        assert_eq!((packets[1].tag, packets[1].body), (6, &[0xCC][..]));
+        assert!(super::packets(&[0x88, 0x05, 0x00]).is_err());
+        assert!(super::packets(b"plain text").is_err());
+    }
+
+    #[test]
+    fn test_text_hasher_normalizes_line_endings_across_chunks() {
+        let digest = |chunks: &[&[u8]]| {
+            let mut hasher = DocumentHasher {
+                hash: HashState::new(8).unwrap(),
+                text: true,
+                previous_cr: false,
+            };
+            for chunk in chunks {
+                hasher.update(chunk);
+            }
+            hasher.hash.finalize()
+        };
+
+        let expected = Sha256::digest(b"a\r\nb\r\n").to_vec();
+        assert_eq!(digest(&[b"a\nb\n"]), expected);
+        assert_eq!(digest(&[b"a\r\nb\r\n"]), expected);
+        assert_eq!(digest(&[b"a\r", b"\nb", b"\n"]), expected);
+        assert_eq!(digest(&[b"a\rb\r"]), expected);
+        assert_eq!(digest(&[b"a\r", b"b\r"]), expected);
+        assert_eq!(digest(&[b"a\r\rb"]), Sha256::digest(b"a\r\n\r\nb").to_vec());
+    }
+
+    #[test]
+    fn test_oversized_key_packet_has_no_v4_fingerprint() {
+        assert!(v4_fingerprint(&vec![0; usize::from(u16::MAX) + 1]).is_err());
+        assert!(v4_fingerprint(&[4; 40]).is_ok());
+    }
+
+    #[test]
+    fn test_dearmor_rejects_bad_checksum() {
+        let armored = "-----BEGIN PGP SIGNATURE-----\n\nqrs=\n=AAAA\n-----END PGP SIGNATURE-----\n";
+        assert!(dearmor(armored.as_bytes()).is_err());
+        assert!(dearmor(b"-----BEGIN PGP SIGNATURE-----\n\nqrs=\n").is_err());
+    }
+
+    #[test]
+    fn test_sha1_signatures_are_rejected() {
+        assert!(HashState::new(2).is_err());
+    }
+
+    const CREATED: u32 = 1_700_000_000;
+
+    /// An Ed25519 (algorithm 27) test key and its packet body.
+    struct TestKey {
+        signing: SigningKey,
+        body: Vec<u8>,
+    }
+
+    impl TestKey {
+        fn new(seed: u8) -> Self {
+            let signing = SigningKey::from_bytes(&[seed; 32]);
+            let mut body = vec![4];
+            body.extend_from_slice(&CREATED.to_be_bytes());
+            body.push(27);
+            body.extend_from_slice(signing.verifying_key().as_bytes());
+            Self { signing, body }
+        }
+
+        fn prefix(&self) -> Vec<u8> {
+            key_prefix(&self.body).unwrap()
+        }
+
+        /// A v4 signature body over `parts`, with a creation time and, unless
+        /// `hashed` names an issuer key id, an issuer fingerprint in addition
+        /// to `hashed`.
+        fn sign(
+            &self,
+            signature_type: u8,
+            created_at: u32,
+            hashed: &[(u8, Vec<u8>)],
+            unhashed: &[(u8, Vec<u8>)],
+            parts: &[&[u8]],
+        ) -> Vec<u8> {
+            let mut issuer = vec![4];
+            issuer.extend_from_slice(&v4_fingerprint(&self.body).unwrap());
+            let mut hashed = hashed.to_vec();
+            hashed.push((SUBPACKET_CREATION_TIME, created_at.to_be_bytes().to_vec()));
+            if !hashed
+                .iter()
+                .any(|(kind, _)| *kind == SUBPACKET_ISSUER_KEY_ID)
+            {
+                hashed.push((SUBPACKET_ISSUER_FINGERPRINT, issuer));
+            }
+
+            let mut body = vec![4, signature_type, 27, 8];
+            let hashed = encode_subpackets(&hashed);
+            body.extend_from_slice(&(hashed.len() as u16).to_be_bytes());
+            body.extend_from_slice(&hashed);
+            let trailer = body.clone();
+
+            let mut hash = Sha256::new();
+            for part in parts {
+                hash.update(part);
+            }
+            hash.update(&trailer);
+            hash.update([0x04, 0xFF]);
+            hash.update((trailer.len() as u32).to_be_bytes());
+            let digest = hash.finalize();
+
+            let unhashed = encode_subpackets(unhashed);
+            body.extend_from_slice(&(unhashed.len() as u16).to_be_bytes());
+            body.extend_from_slice(&unhashed);
+            body.extend_from_slice(&digest[..2]);
+            body.extend_from_slice(&self.signing.sign(&digest).to_bytes());
+            body
+        }
+
+        fn fingerprint(&self) -> String {
+            hex::encode_upper(v4_fingerprint(&self.body).unwrap())
+        }
+    }
+
+    fn encode_subpackets(subpackets: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for (kind, value) in subpackets {
+            encoded.push(u8::try_from(value.len() + 1).unwrap());
+            encoded.push(*kind);
+            encoded.extend_from_slice(value);
+        }
+        encoded
+    }
+
+    fn packet(tag: u8, body: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0xC0 | tag, 255];
+        packet.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        packet.extend_from_slice(body);
+        packet
+    }
+
+    fn flags(flags: u8) -> (u8, Vec<u8>) {
+        (SUBPACKET_KEY_FLAGS, vec![flags])
+    }
+
+    /// A certificate for `primary` with one user id self-signed with `hashed`.
+    fn certificate(primary: &TestKey, hashed: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let user_id = b"Pulith Test <test@pulith.test>";
+        let mut hashed_user_id = vec![0xB4];
+        hashed_user_id.extend_from_slice(&(user_id.len() as u32).to_be_bytes());
+        hashed_user_id.extend_from_slice(user_id);
+        let certification = primary.sign(
+            0x13,
+            CREATED,
+            hashed,
+            &[],
+            &[&primary.prefix(), &hashed_user_id],
+        );
+
+        let mut data = packet(TAG_PUBLIC_KEY, &primary.body);
+        data.extend(packet(TAG_USER_ID, user_id));
+        data.extend(packet(TAG_SIGNATURE, &certification));
+        data
+    }
+
+    /// Append `subkey` to a certificate of `primary` with the given binding
+    /// signature flags, optionally back-signed and signed by `binder`.
+    fn add_subkey(
+        data: &mut Vec<u8>,
+        primary: &TestKey,
+        subkey: &TestKey,
+        binder: &TestKey,
+        key_flags: u8,
+        back_signed: bool,
+    ) {
+        let parts: [&[u8]; 2] = [&primary.prefix(), &subkey.prefix()];
+        let back = back_signed.then(|| {
+            (
+                SUBPACKET_EMBEDDED_SIGNATURE,
+                subkey.sign(SIGNATURE_PRIMARY_KEY_BINDING, CREATED, &[], &[], &parts),
+            )
+        });
+        let binding = binder.sign(
+            SIGNATURE_SUBKEY_BINDING,
+            CREATED,
+            &[flags(key_flags)],
+            back.as_slice(),
+            &parts,
+        );
+        data.extend(packet(TAG_PUBLIC_SUBKEY, &subkey.body));
+        data.extend(packet(TAG_SIGNATURE, &binding));
+    }
+
+    fn document_signature(key: &TestKey, created_at: u32, data: &[u8]) -> Vec<u8> {
+        packet(
+            TAG_SIGNATURE,
+            &key.sign(SIGNATURE_BINARY, created_at, &[], &[], &[data]),
+        )
+    }
+
+    #[test]
+    fn test_subkeys_need_binding_key_flags_and_back_signature() {
+        let primary = TestKey::new(1);
+        let [valid, unsigned_back, forged, encrypting, revoked] = [2, 3, 4, 5, 6].map(TestKey::new);
+        let mut data = certificate(&primary, &[flags(0x01)]);
+        add_subkey(&mut data, &primary, &valid, &primary, KEY_FLAG_SIGN, true);
+        add_subkey(
+            &mut data,
+            &primary,
+            &unsigned_back,
+            &primary,
+            KEY_FLAG_SIGN,
+            false,
+        );
+        add_subkey(&mut data, &primary, &forged, &forged, KEY_FLAG_SIGN, true);
+        add_subkey(&mut data, &primary, &encrypting, &primary, 0x0C, true);
+        add_subkey(&mut data, &primary, &revoked, &primary, KEY_FLAG_SIGN, true);
+        let revocation = primary.sign(
+            SIGNATURE_SUBKEY_REVOCATION,
+            CREATED,
+            &[],
+            &[],
+            &[&primary.prefix(), &revoked.prefix()],
+        );
+        data.extend(packet(TAG_SIGNATURE, &revocation));
+
+        let mut keyring = OpenPgpKeyring::new();
+        assert_eq!(keyring.add_keys(&data).unwrap(), 4);
+        let keys = keyring
+            .keys()
+            .iter()
+            .map(|key| (key.fingerprint(), key.can_sign()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                (primary.fingerprint(), false),
+                (valid.fingerprint(), true),
+                (unsigned_back.fingerprint(), false),
+                (encrypting.fingerprint(), false),
+            ]
+        );
+
+        let verifier = OpenPgpVerifier::new(keyring);
+        let verification = verifier
+            .verify_detached(b"payload", &document_signature(&valid, CREATED, b"payload"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(verification.primary_fingerprint, primary.fingerprint());
+        for key in [&primary, &unsigned_back, &forged, &encrypting, &revoked] {
+            let signature = document_signature(key, CREATED, b"payload");
+            assert!(verifier.verify_detached(b"payload", &signature).is_err());
+        }
+    }
+
+    #[test]
+    fn test_signatures_must_fall_in_the_key_lifetime() {
+        let primary = TestKey::new(1);
+        let expiring = [
+            flags(0x03),
+            (SUBPACKET_KEY_EXPIRATION_TIME, 100u32.to_be_bytes().to_vec()),
+        ];
+        let mut keyring = OpenPgpKeyring::new();
+        keyring.add_keys(&certificate(&primary, &expiring)).unwrap();
+        assert_eq!(
+            keyring.keys()[0].expires_at(),
+            Some(u64::from(CREATED) + 100)
+        );
+        let verifier = OpenPgpVerifier::new(keyring).with_reference_time(u64::from(CREATED) + 60);
+
+        let verify = |created_at| {
+            let signature = document_signature(&primary, created_at, b"payload");
+            verifier.verify_detached(b"payload", &signature)
+        };
+        assert!(verify(CREATED + 50).unwrap().is_some());
+        assert!(verify(CREATED + 100).is_err());
+        assert!(verify(CREATED - 1).is_err());
+
+        // Once the key has expired, even signatures made before are refused.
+        let expired = verifier
+            .clone()
+            .with_reference_time(u64::from(CREATED) + 100);
+        let signature = document_signature(&primary, CREATED + 50, b"payload");
+        assert!(expired.verify_detached(b"payload", &signature).is_err());
+
+        let mut revoked = certificate(&primary, &[flags(0x03)]);
+        let revocation = primary.sign(
+            SIGNATURE_KEY_REVOCATION,
+            CREATED,
+            &[],
+            &[],
+            &[&primary.prefix()],
+        );
+        revoked.splice(
+            primary.body.len() + 6..primary.body.len() + 6,
+            packet(TAG_SIGNATURE, &revocation),
+        );
+        let mut keyring = OpenPgpKeyring::new();
+        assert_eq!(keyring.add_keys(&revoked).unwrap(), 0);
+
+        let mut unsigned = packet(TAG_PUBLIC_KEY, &primary.body);
+        unsigned.extend(packet(TAG_USER_ID, b"Pulith Test"));
+        assert_eq!(keyring.add_keys(&unsigned).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_signatures_are_current_and_honor_critical_subpackets() {
+        let primary = TestKey::new(1);
+        let mut keyring = OpenPgpKeyring::new();
+        keyring
+            .add_keys(&certificate(&primary, &[flags(0x03)]))
+            .unwrap();
+        let now = u64::from(CREATED) + 3_600;
+        let verifier = OpenPgpVerifier::new(keyring).with_reference_time(now);
+        let verify = |created_at, hashed: &[(u8, Vec<u8>)]| {
+            let signature = packet(
+                TAG_SIGNATURE,
+                &primary.sign(SIGNATURE_BINARY, created_at, hashed, &[], &[b"payload"]),
+            );
+            verifier.verify_detached(b"payload", &signature)
+        };
+
+        assert!(verify(CREATED, &[]).unwrap().is_some());
+        assert!(verify(CREATED + 7_200, &[]).is_err());
+
+        let expiration = |seconds: u32| {
+            (
+                0x80 | SUBPACKET_SIGNATURE_EXPIRATION_TIME,
+                seconds.to_be_bytes().to_vec(),
+            )
+        };
+        assert!(verify(CREATED, &[expiration(7_200)]).unwrap().is_some());
+        assert!(verify(CREATED, &[expiration(60)]).is_err());
+
+        assert!(verify(CREATED, &[(100, vec![1])]).unwrap().is_some());
+        assert!(verify(CREATED, &[(0x80 | 100, vec![1])]).is_err());
+        assert!(verify(CREATED, &[(0x80 | 20, vec![0; 8])]).is_err());
+        assert!(verify(CREATED, &[(0x80 | 30, vec![1])]).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_short_key_id_collisions_try_every_key() {
+        let [impostor, signer] = [7, 8].map(TestKey::new);
+        let mut keyring = OpenPgpKeyring::new();
+        keyring
+            .add_keys(&certificate(&signer, &[flags(0x03)]))
+            .unwrap();
+        // A key whose fingerprint shares the signer's short key id, listed first.
+        let mut colliding = keyring.keys[0].clone();
+        colliding.material = KeyMaterial::Ed25519(impostor.signing.verifying_key());
+        colliding.fingerprint[..12].fill(0xAA);
+        keyring.keys.insert(0, colliding);
+
+        let key_id = v4_fingerprint(&signer.body).unwrap()[12..].to_vec();
+        let signature = packet(
+            TAG_SIGNATURE,
+            &signer.sign(
+                SIGNATURE_BINARY,
+                CREATED,
+                &[(SUBPACKET_ISSUER_KEY_ID, key_id)],
+                &[],
+                &[b"payload"],
+            ),
+        );
+        let verification = OpenPgpVerifier::new(keyring)
+            .verify_detached(b"payload", &signature)
+            .unwrap()
+            .unwrap();
+        assert_eq!(verification.fingerprint, signer.fingerprint());
+    }
+}
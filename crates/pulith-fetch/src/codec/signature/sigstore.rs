@@ -0,0 +1,1057 @@
+//! Sigstore bundle verification against a pinned trust root.
+//!
+//! A bundle produced by `cosign sign-blob --bundle` (or any Sigstore client)
+//! carries a short-lived Fulcio certificate, the signature over the artifact
+//! digest, and the Rekor transparency log entry recording both. Verification
+//! is fully offline: the certificate chain, the Rekor checkpoint and the
+//! signed entry timestamp are all checked against keys from a locally pinned
+//! `trusted_root.json`, and certificate validity is evaluated at the time the
+//! entry was integrated into the log rather than at the current time.
+//!
+//! Message signature bundles with ECDSA leaf keys are supported. The
+//! integration time is only trusted through the Rekor signed entry
+//! timestamp, so bundles without one are rejected; RFC 3161 timestamps,
+//! DSSE attestation bundles and certificate transparency (SCT) checks are
+//! not supported.
+
+use std::io::Read;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use p256::ecdsa::signature::hazmat::PrehashVerifier as _;
+use p256::pkcs8::DecodePublicKey as _;
+use pulith_resource::{
+    Metadata, SIGSTORE_IDENTITY_METADATA_KEY, SIGSTORE_ISSUER_METADATA_KEY, SignatureScheme,
+    ValidDigest, VerifiedSignature, sigstore_key_id,
+};
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256, Sha384};
+use x509_cert::Certificate;
+use x509_cert::der::asn1::{ObjectIdentifier, Utf8StringRef};
+use x509_cert::der::{Decode as _, Encode as _};
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::{ExtendedKeyUsage, SubjectAltName};
+
+use super::{decode_base64, decode_pem_block, sha256_digest};
+use crate::error::{Error, Result};
+use crate::fetch::fetcher::FetchReceipt;
+
+const ECDSA_WITH_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+const ECDSA_WITH_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.3");
+const SUBJECT_ALT_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.17");
+const EXTENDED_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.37");
+const CODE_SIGNING: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.3");
+/// Fulcio OIDC issuer extension, DER-encoded `UTF8String`.
+const FULCIO_ISSUER_V2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.57264.1.8");
+/// Deprecated Fulcio OIDC issuer extension, raw UTF-8 bytes.
+const FULCIO_ISSUER_V1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.57264.1.1");
+
+/// Keys and certificate authorities trusted for Sigstore verification.
+///
+/// Parsed from the `trusted_root.json` format distributed through Sigstore
+/// TUF repositories. Only the transparency log and certificate authority
+/// sections are used.
+#[derive(Debug, Clone, Default)]
+pub struct SigstoreTrustRoot {
+    logs: Vec<TransparencyLog>,
+    authorities: Vec<CertificateAuthority>,
+}
+
+#[derive(Debug, Clone)]
+struct TransparencyLog {
+    log_id: Vec<u8>,
+    key: EcdsaKey,
+    valid_for: ValidityPeriod,
+}
+
+#[derive(Debug, Clone)]
+struct CertificateAuthority {
+    chain: Vec<Certificate>,
+    valid_for: ValidityPeriod,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ValidityPeriod {
+    start: Option<i64>,
+    end: Option<i64>,
+}
+
+impl ValidityPeriod {
+    fn contains(&self, time: i64) -> bool {
+        self.start.is_none_or(|start| start <= time) && self.end.is_none_or(|end| time <= end)
+    }
+}
+
+impl SigstoreTrustRoot {
+    /// Parse a `trusted_root.json` document.
+    pub fn from_json(data: &[u8]) -> Result<Self> {
+        let root: TrustRootJson = serde_json::from_slice(data)
+            .map_err(|e| Error::InvalidState(format!("Invalid Sigstore trust root: {}", e)))?;
+
+        let logs = root
+            .tlogs
+            .into_iter()
+            .map(|log| {
+                let key = decode_field(&log.public_key.raw_bytes, "transparency log key")?;
+                Ok(TransparencyLog {
+                    log_id: decode_field(&log.log_id.key_id, "transparency log id")?,
+                    key: EcdsaKey::from_public_key_der(&key)?,
+                    valid_for: log.public_key.valid_for.into(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let authorities = root
+            .certificate_authorities
+            .into_iter()
+            .map(|authority| {
+                let chain = authority
+                    .cert_chain
+                    .certificates
+                    .iter()
+                    .map(|certificate| {
+                        parse_certificate(&decode_field(&certificate.raw_bytes, "CA certificate")?)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                if chain.is_empty() {
+                    return Err(Error::InvalidState(
+                        "Invalid Sigstore trust root: empty certificate chain".to_string(),
+                    ));
+                }
+                Ok(CertificateAuthority {
+                    chain,
+                    valid_for: authority.valid_for.into(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { logs, authorities })
+    }
+
+    /// Read a `trusted_root.json` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| {
+            Error::Fs(pulith_fs::Error::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+        })?;
+        Self::from_json(&data)
+    }
+
+    fn log(&self, log_id: &[u8]) -> Option<&TransparencyLog> {
+        self.logs.iter().find(|log| log.log_id == log_id)
+    }
+}
+
+/// A parsed Sigstore bundle holding a message signature.
+#[derive(Debug, Clone)]
+pub struct SigstoreBundle {
+    media_type: String,
+    certificate_der: Vec<u8>,
+    certificate: Certificate,
+    signature: Vec<u8>,
+    message_digest: Option<Vec<u8>>,
+    entries: Vec<TlogEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct TlogEntry {
+    log_index: i64,
+    log_id: Vec<u8>,
+    kind: String,
+    integrated_time: i64,
+    signed_entry_timestamp: Option<Vec<u8>>,
+    inclusion_proof: Option<InclusionProof>,
+    body: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct InclusionProof {
+    log_index: u64,
+    tree_size: u64,
+    root_hash: Vec<u8>,
+    hashes: Vec<Vec<u8>>,
+    checkpoint: String,
+}
+
+impl SigstoreBundle {
+    /// Parse a bundle in the v0.1, v0.2 or v0.3 JSON format.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let bundle: BundleJson =
+            serde_json::from_slice(data).map_err(|e| invalid(&e.to_string()))?;
+        let material = bundle.verification_material;
+
+        let certificate = match (material.certificate, material.x509_certificate_chain) {
+            (Some(certificate), _) => certificate,
+            (None, Some(chain)) => chain
+                .certificates
+                .into_iter()
+                .next()
+                .ok_or_else(|| invalid("empty certificate chain"))?,
+            (None, None) => {
+                return Err(invalid(
+                    "no signing certificate; public key bundles are not supported",
+                ));
+            }
+        };
+        let certificate_der = decode_field(&certificate.raw_bytes, "certificate")?;
+        let message = bundle
+            .message_signature
+            .ok_or_else(|| invalid("only message signature bundles are supported"))?;
+        let message_digest = match message.message_digest {
+            Some(digest) if digest.algorithm == "SHA2_256" => {
+                Some(decode_field(&digest.digest, "message digest")?)
+            }
+            Some(digest) => {
+                return Err(invalid(&format!(
+                    "unsupported message digest algorithm {}",
+                    digest.algorithm
+                )));
+            }
+            None => None,
+        };
+
+        let entries = material
+            .tlog_entries
+            .into_iter()
+            .map(TlogEntry::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            media_type: bundle.media_type,
+            certificate: parse_certificate(&certificate_der)?,
+            certificate_der,
+            signature: decode_field(&message.signature, "signature")?,
+            message_digest,
+            entries,
+        })
+    }
+
+    /// Get the bundle media type.
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+}
+
+impl TryFrom<TlogEntryJson> for TlogEntry {
+    type Error = Error;
+
+    fn try_from(entry: TlogEntryJson) -> Result<Self> {
+        let inclusion_proof = entry
+            .inclusion_proof
+            .map(|proof| {
+                Ok::<_, Error>(InclusionProof {
+                    log_index: to_u64(proof.log_index.0)?,
+                    tree_size: to_u64(proof.tree_size.0)?,
+                    root_hash: decode_field(&proof.root_hash, "inclusion proof root hash")?,
+                    hashes: proof
+                        .hashes
+                        .iter()
+                        .map(|hash| decode_field(hash, "inclusion proof hash"))
+                        .collect::<Result<Vec<_>>>()?,
+                    checkpoint: proof.checkpoint.envelope,
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            log_index: entry.log_index.0,
+            log_id: decode_field(&entry.log_id.key_id, "log id")?,
+            kind: format!("{}/{}", entry.kind_version.kind, entry.kind_version.version),
+            integrated_time: entry.integrated_time.0,
+            signed_entry_timestamp: entry
+                .inclusion_promise
+                .map(|promise| decode_field(&promise.signed_entry_timestamp, "inclusion promise"))
+                .transpose()?,
+            inclusion_proof,
+            body: decode_field(&entry.canonicalized_body, "entry body")?,
+        })
+    }
+}
+
+/// Result of a successful Sigstore bundle check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigstoreVerification {
+    /// Certificate identity: the SAN URI (workflow) or email of the signer.
+    pub identity: String,
+    /// OIDC issuer that authenticated the signer.
+    pub issuer: String,
+    /// Rekor log index of the entry.
+    pub log_index: i64,
+    /// Time the entry was integrated into the log (Unix timestamp).
+    pub integrated_time: i64,
+    /// SHA-256 digest of the signed artifact.
+    pub digest: ValidDigest,
+}
+
+impl SigstoreVerification {
+    /// Describe this verification for `pulith-resource` trust evaluation.
+    ///
+    /// The key id binds the certificate identity to its issuer, see
+    /// [`sigstore_key_id`].
+    pub fn verified_signature(&self) -> VerifiedSignature {
+        VerifiedSignature {
+            scheme: SignatureScheme::Sigstore,
+            key_id: sigstore_key_id(&self.issuer, &self.identity),
+            public_key: None,
+            digest: Some(self.digest.clone()),
+        }
+    }
+
+    /// Identity and issuer as metadata facts for `TrustPolicy::evaluate`,
+    /// to be matched by `TrustAnchor::Metadata` anchors.
+    pub fn metadata(&self) -> Metadata {
+        Metadata::from([
+            (
+                SIGSTORE_IDENTITY_METADATA_KEY.to_string(),
+                self.identity.clone(),
+            ),
+            (
+                SIGSTORE_ISSUER_METADATA_KEY.to_string(),
+                self.issuer.clone(),
+            ),
+        ])
+    }
+}
+
+/// Sigstore bundle verifier backed by a pinned trust root.
+#[derive(Debug, Clone, Default)]
+pub struct SigstoreVerifier {
+    trust_root: SigstoreTrustRoot,
+}
+
+impl SigstoreVerifier {
+    /// Create a verifier for the given trust root.
+    pub fn new(trust_root: SigstoreTrustRoot) -> Self {
+        Self { trust_root }
+    }
+
+    /// Create a verifier from a `trusted_root.json` file.
+    pub fn from_trust_root_file(path: impl AsRef<Path>) -> Result<Self> {
+        SigstoreTrustRoot::from_file(path).map(Self::new)
+    }
+
+    /// Verify `data` against a JSON bundle.
+    ///
+    /// Returns `Ok(None)` when the bundle does not cover the data, and an
+    /// error when the bundle is malformed or not anchored in the trust root.
+    pub fn verify_detached(
+        &self,
+        data: &[u8],
+        bundle: &[u8],
+    ) -> Result<Option<SigstoreVerification>> {
+        self.verify_digest(&Sha256::digest(data), &SigstoreBundle::parse(bundle)?)
+    }
+
+    /// Verify a file against a JSON bundle.
+    pub fn verify_file(
+        &self,
+        path: impl AsRef<Path>,
+        bundle: &[u8],
+    ) -> Result<Option<SigstoreVerification>> {
+        let path = path.as_ref();
+        let mut file = std::fs::File::open(path).map_err(|source| {
+            Error::Fs(pulith_fs::Error::Read {
+                path: path.to_path_buf(),
+                source,
+            })
+        })?;
+
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        self.verify_digest(&hasher.finalize(), &SigstoreBundle::parse(bundle)?)
+    }
+
+    /// Verify a fetched file and record the signer identity in its receipt.
+    ///
+    /// A bundle that does not cover the fetched content is an error.
+    pub fn verify_receipt(
+        &self,
+        receipt: &mut FetchReceipt,
+        bundle: &[u8],
+    ) -> Result<SigstoreVerification> {
+        let verification = self
+            .verify_file(&receipt.destination, bundle)?
+            .ok_or_else(|| {
+                Error::InvalidState(format!(
+                    "Sigstore bundle does not match {}",
+                    receipt.destination.display()
+                ))
+            })?;
+        receipt.signatures.push(verification.verified_signature());
+        Ok(verification)
+    }
+
+    /// Verify a bundle against the SHA-256 digest of the artifact.
+    pub fn verify_digest(
+        &self,
+        digest: &[u8],
+        bundle: &SigstoreBundle,
+    ) -> Result<Option<SigstoreVerification>> {
+        let (entry, log) = bundle
+            .entries
+            .iter()
+            .find_map(|entry| Some((entry, self.trust_root.log(&entry.log_id)?)))
+            .ok_or_else(|| {
+                if bundle.entries.is_empty() {
+                    invalid("no transparency log entry")
+                } else {
+                    Error::InvalidState(
+                        "Sigstore bundle is not recorded in a trusted transparency log".to_string(),
+                    )
+                }
+            })?;
+        // The integration time picks the log key and the certificate validity
+        // window, and only the signed entry timestamp vouches for it.
+        let timestamp = entry.signed_entry_timestamp.as_ref().ok_or_else(|| {
+            untrusted("no signed entry timestamp vouches for the integration time")
+        })?;
+        verify_signed_entry_timestamp(entry, log, timestamp)?;
+        let time = entry.integrated_time;
+        if !log.valid_for.contains(time) {
+            return Err(untrusted(
+                "transparency log key is not valid at integration time",
+            ));
+        }
+        verify_inclusion(entry, log)?;
+
+        let certificate = &bundle.certificate;
+        self.verify_certificate(certificate, time)?;
+        let body = HashedRekord::parse(entry)?;
+        if body.signature != bundle.signature || body.certificate_der != bundle.certificate_der {
+            return Err(untrusted(
+                "transparency log entry does not match the bundle signature",
+            ));
+        }
+
+        let key = EcdsaKey::from_public_key_der(
+            &certificate
+                .tbs_certificate
+                .subject_public_key_info
+                .to_der()
+                .map_err(|e| invalid(&e.to_string()))?,
+        )?;
+        if body.digest != digest
+            || bundle
+                .message_digest
+                .as_ref()
+                .is_some_and(|expected| expected != digest)
+            || !key.verify_prehash(digest, &bundle.signature)
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(SigstoreVerification {
+            identity: certificate_identity(certificate)?,
+            issuer: certificate_issuer(certificate)?,
+            log_index: entry.log_index,
+            integrated_time: time,
+            digest: sha256_digest(digest),
+        }))
+    }
+
+    fn verify_certificate(&self, certificate: &Certificate, time: i64) -> Result<()> {
+        if !certificate_valid_at(certificate, time) {
+            return Err(untrusted(
+                "certificate was not valid when the entry was logged",
+            ));
+        }
+
+        let has_code_signing = find_extension(certificate, EXTENDED_KEY_USAGE)
+            .map(ExtendedKeyUsage::from_der)
+            .transpose()
+            .map_err(|e| invalid(&e.to_string()))?
+            .is_some_and(|usage| usage.0.contains(&CODE_SIGNING));
+        if !has_code_signing {
+            return Err(untrusted("certificate is not valid for code signing"));
+        }
+
+        let issued = self.trust_root.authorities.iter().any(|authority| {
+            authority.valid_for.contains(time)
+                && authority
+                    .chain
+                    .iter()
+                    .all(|ca| certificate_valid_at(ca, time))
+                && certificate.tbs_certificate.issuer == authority.chain[0].tbs_certificate.subject
+                && verify_certificate_signature(certificate, &authority.chain[0])
+        });
+        if issued {
+            Ok(())
+        } else {
+            Err(untrusted(
+                "certificate is not issued by a trusted certificate authority",
+            ))
+        }
+    }
+}
+
+/// The parts of a `hashedrekord` entry body that bind it to the bundle.
+struct HashedRekord {
+    digest: Vec<u8>,
+    signature: Vec<u8>,
+    certificate_der: Vec<u8>,
+}
+
+impl HashedRekord {
+    fn parse(entry: &TlogEntry) -> Result<Self> {
+        if entry.kind != "hashedrekord/0.0.1" {
+            return Err(invalid(&format!(
+                "unsupported transparency log entry kind {}",
+                entry.kind
+            )));
+        }
+        let body: HashedRekordJson =
+            serde_json::from_slice(&entry.body).map_err(|e| invalid(&e.to_string()))?;
+        if body.spec.data.hash.algorithm != "sha256" {
+            return Err(invalid(&format!(
+                "unsupported entry hash algorithm {}",
+                body.spec.data.hash.algorithm
+            )));
+        }
+
+        let pem = decode_field(&body.spec.signature.public_key.content, "entry public key")?;
+        let (label, certificate_der) = decode_pem_block(&pem).map_err(|e| invalid(&e))?;
+        if label != "CERTIFICATE" {
+            return Err(invalid("entry public key is not a certificate"));
+        }
+
+        Ok(Self {
+            digest: hex::decode(&body.spec.data.hash.value).map_err(|e| invalid(&e.to_string()))?,
+            signature: decode_field(&body.spec.signature.content, "entry signature")?,
+            certificate_der,
+        })
+    }
+}
+
+fn verify_inclusion(entry: &TlogEntry, log: &TransparencyLog) -> Result<()> {
+    let proof = entry
+        .inclusion_proof
+        .as_ref()
+        .ok_or_else(|| invalid("no transparency log inclusion proof"))?;
+
+    let leaf = Sha256::new()
+        .chain_update([0u8])
+        .chain_update(&entry.body)
+        .finalize();
+    let root = root_from_inclusion_proof(proof.log_index, proof.tree_size, &leaf, &proof.hashes);
+    if root.as_deref() != Some(proof.root_hash.as_slice()) {
+        return Err(untrusted("inclusion proof does not match the log root"));
+    }
+
+    let checkpoint = Checkpoint::parse(&proof.checkpoint)?;
+    if checkpoint.tree_size != proof.tree_size || checkpoint.root_hash != proof.root_hash {
+        return Err(untrusted("checkpoint does not match the inclusion proof"));
+    }
+    if !checkpoint.signed_by(log) {
+        return Err(untrusted(
+            "checkpoint is not signed by the transparency log",
+        ));
+    }
+    Ok(())
+}
+
+/// Recompute a Merkle tree root from an RFC 9162 inclusion proof.
+fn root_from_inclusion_proof(
+    index: u64,
+    size: u64,
+    leaf: &[u8],
+    proof: &[Vec<u8>],
+) -> Option<Vec<u8>> {
+    if index >= size {
+        return None;
+    }
+
+    let node = |left: &[u8], right: &[u8]| {
+        Sha256::new()
+            .chain_update([1u8])
+            .chain_update(left)
+            .chain_update(right)
+            .finalize()
+            .to_vec()
+    };
+    let (mut fn_, mut sn) = (index, size - 1);
+    let mut root = leaf.to_vec();
+    for hash in proof {
+        if sn == 0 {
+            return None;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            root = node(hash, &root);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            root = node(&root, hash);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+
+    (sn == 0).then_some(root)
+}
+
+/// A signed note checkpoint: `origin`, tree size and root hash, followed by
+/// a blank line and `— <name> <base64(key hint || signature)>` lines.
+struct Checkpoint<'a> {
+    text: &'a str,
+    tree_size: u64,
+    root_hash: Vec<u8>,
+    signatures: Vec<Vec<u8>>,
+}
+
+impl<'a> Checkpoint<'a> {
+    fn parse(envelope: &'a str) -> Result<Self> {
+        let malformed = || invalid("malformed checkpoint");
+        let split = envelope.find("\n\n").ok_or_else(malformed)?;
+        let (text, signatures) = (&envelope[..=split], &envelope[split + 2..]);
+
+        let mut lines = text.lines();
+        let _origin = lines.next().ok_or_else(malformed)?;
+        let tree_size = lines
+            .next()
+            .and_then(|line| line.parse().ok())
+            .ok_or_else(malformed)?;
+        let root_hash = lines
+            .next()
+            .and_then(|line| decode_base64(line.as_bytes()).ok())
+            .ok_or_else(malformed)?;
+        let signatures = signatures
+            .lines()
+            .filter_map(|line| line.strip_prefix("\u{2014} "))
+            .filter_map(|line| line.rsplit_once(' '))
+            .filter_map(|(_, signature)| decode_base64(signature.as_bytes()).ok())
+            .collect();
+
+        Ok(Self {
+            text,
+            tree_size,
+            root_hash,
+            signatures,
+        })
+    }
+
+    fn signed_by(&self, log: &TransparencyLog) -> bool {
+        let digest = Sha256::digest(self.text.as_bytes());
+        self.signatures.iter().any(|signature| {
+            signature.len() > 4
+                && log.log_id.starts_with(&signature[..4])
+                && log.key.verify_prehash(&digest, &signature[4..])
+        })
+    }
+}
+
+fn verify_signed_entry_timestamp(
+    entry: &TlogEntry,
+    log: &TransparencyLog,
+    timestamp: &[u8],
+) -> Result<()> {
+    use base64::{Engine as _, engine::general_purpose};
+
+    // Canonical JSON: sorted keys, no whitespace.
+    let payload = format!(
+        r#"{{"body":"{}","integratedTime":{},"logID":"{}","logIndex":{}}}"#,
+        general_purpose::STANDARD.encode(&entry.body),
+        entry.integrated_time,
+        hex::encode(&log.log_id),
+        entry.log_index
+    );
+    if log
+        .key
+        .verify_prehash(&Sha256::digest(payload.as_bytes()), timestamp)
+    {
+        Ok(())
+    } else {
+        Err(untrusted("signed entry timestamp does not verify"))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum EcdsaKey {
+    P256(p256::ecdsa::VerifyingKey),
+    P384(p384::ecdsa::VerifyingKey),
+}
+
+impl EcdsaKey {
+    fn from_public_key_der(der: &[u8]) -> Result<Self> {
+        p256::ecdsa::VerifyingKey::from_public_key_der(der)
+            .map(Self::P256)
+            .or_else(|_| p384::ecdsa::VerifyingKey::from_public_key_der(der).map(Self::P384))
+            .map_err(|_| {
+                invalid("unsupported public key; only ECDSA P-256 and P-384 are supported")
+            })
+    }
+
+    fn verify_prehash(&self, digest: &[u8], signature: &[u8]) -> bool {
+        match self {
+            Self::P256(key) => p256::ecdsa::Signature::from_der(signature)
+                .is_ok_and(|signature| key.verify_prehash(digest, &signature).is_ok()),
+            Self::P384(key) => p384::ecdsa::Signature::from_der(signature)
+                .is_ok_and(|signature| key.verify_prehash(digest, &signature).is_ok()),
+        }
+    }
+}
+
+fn verify_certificate_signature(certificate: &Certificate, issuer: &Certificate) -> bool {
+    let Ok(tbs) = certificate.tbs_certificate.to_der() else {
+        return false;
+    };
+    let digest = match certificate.signature_algorithm.oid {
+        ECDSA_WITH_SHA256 => Sha256::digest(&tbs).to_vec(),
+        ECDSA_WITH_SHA384 => Sha384::digest(&tbs).to_vec(),
+        _ => return false,
+    };
+    let Some(signature) = certificate.signature.as_bytes() else {
+        return false;
+    };
+
+    issuer
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .ok()
+        .and_then(|der| EcdsaKey::from_public_key_der(&der).ok())
+        .is_some_and(|key| key.verify_prehash(&digest, signature))
+}
+
+fn certificate_valid_at(certificate: &Certificate, time: i64) -> bool {
+    let validity = &certificate.tbs_certificate.validity;
+    let not_before = validity.not_before.to_unix_duration().as_secs();
+    let not_after = validity.not_after.to_unix_duration().as_secs();
+    u64::try_from(time).is_ok_and(|time| not_before <= time && time <= not_after)
+}
+
+fn find_extension(certificate: &Certificate, oid: ObjectIdentifier) -> Option<&[u8]> {
+    certificate
+        .tbs_certificate
+        .extensions
+        .as_ref()?
+        .iter()
+        .find(|extension| extension.extn_id == oid)
+        .map(|extension| extension.extn_value.as_bytes())
+}
+
+fn certificate_identity(certificate: &Certificate) -> Result<String> {
+    let names = find_extension(certificate, SUBJECT_ALT_NAME)
+        .map(SubjectAltName::from_der)
+        .transpose()
+        .map_err(|e| invalid(&e.to_string()))?
+        .map(|names| names.0)
+        .unwrap_or_default();
+
+    let uri = names.iter().find_map(|name| match name {
+        GeneralName::UniformResourceIdentifier(uri) => Some(uri.to_string()),
+        _ => None,
+    });
+    let email = names.iter().find_map(|name| match name {
+        GeneralName::Rfc822Name(email) => Some(email.to_string()),
+        _ => None,
+    });
+    uri.or(email)
+        .ok_or_else(|| invalid("certificate has no URI or email identity"))
+}
+
+fn certificate_issuer(certificate: &Certificate) -> Result<String> {
+    if let Some(value) = find_extension(certificate, FULCIO_ISSUER_V2) {
+        return Utf8StringRef::from_der(value)
+            .map(|issuer| issuer.as_str().to_string())
+            .map_err(|e| invalid(&e.to_string()));
+    }
+    if let Some(value) = find_extension(certificate, FULCIO_ISSUER_V1) {
+        return String::from_utf8(value.to_vec()).map_err(|e| invalid(&e.to_string()));
+    }
+    Err(invalid("certificate has no OIDC issuer extension"))
+}
+
+fn parse_certificate(der: &[u8]) -> Result<Certificate> {
+    Certificate::from_der(der).map_err(|e| invalid(&format!("bad certificate: {}", e)))
+}
+
+fn decode_field(value: &str, field: &str) -> Result<Vec<u8>> {
+    decode_base64(value.as_bytes()).map_err(|e| invalid(&format!("{} is not base64: {}", field, e)))
+}
+
+fn to_u64(value: i64) -> Result<u64> {
+    u64::try_from(value).map_err(|_| invalid("negative inclusion proof index"))
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidState(format!("Invalid Sigstore bundle: {}", reason))
+}
+
+fn untrusted(reason: &str) -> Error {
+    Error::InvalidState(format!("Untrusted Sigstore bundle: {}", reason))
+}
+
+/// Protobuf JSON encodes 64-bit integers as strings; accept both forms.
+#[derive(Debug, Clone, Copy)]
+struct Int64(i64);
+
+impl<'de> Deserialize<'de> for Int64 {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(i64),
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(value) => Ok(Self(value)),
+            Repr::Text(value) => value.parse().map(Self).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrustRootJson {
+    #[serde(default)]
+    tlogs: Vec<TlogJson>,
+    #[serde(default)]
+    certificate_authorities: Vec<CertificateAuthorityJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TlogJson {
+    public_key: PublicKeyJson,
+    log_id: LogIdJson,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicKeyJson {
+    raw_bytes: String,
+    #[serde(default)]
+    valid_for: ValidForJson,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CertificateAuthorityJson {
+    cert_chain: CertificateChainJson,
+    #[serde(default)]
+    valid_for: ValidForJson,
+}
+
+#[derive(Deserialize, Default)]
+struct ValidForJson {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl From<ValidForJson> for ValidityPeriod {
+    fn from(value: ValidForJson) -> Self {
+        Self {
+            start: value.start.map(|time| time.timestamp()),
+            end: value.end.map(|time| time.timestamp()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleJson {
+    media_type: String,
+    verification_material: VerificationMaterialJson,
+    message_signature: Option<MessageSignatureJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationMaterialJson {
+    certificate: Option<RawBytesJson>,
+    x509_certificate_chain: Option<CertificateChainJson>,
+    #[serde(default)]
+    tlog_entries: Vec<TlogEntryJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBytesJson {
+    raw_bytes: String,
+}
+
+#[derive(Deserialize)]
+struct CertificateChainJson {
+    certificates: Vec<RawBytesJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogIdJson {
+    key_id: String,
+}
+
+#[derive(Deserialize)]
+struct KindVersionJson {
+    kind: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TlogEntryJson {
+    log_index: Int64,
+    log_id: LogIdJson,
+    kind_version: KindVersionJson,
+    integrated_time: Int64,
+    inclusion_promise: Option<InclusionPromiseJson>,
+    inclusion_proof: Option<InclusionProofJson>,
+    canonicalized_body: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InclusionPromiseJson {
+    signed_entry_timestamp: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InclusionProofJson {
+    log_index: Int64,
+    root_hash: String,
+    tree_size: Int64,
+    #[serde(default)]
+    hashes: Vec<String>,
+    checkpoint: CheckpointJson,
+}
+
+#[derive(Deserialize)]
+struct CheckpointJson {
+    envelope: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageSignatureJson {
+    message_digest: Option<MessageDigestJson>,
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct MessageDigestJson {
+    algorithm: String,
+    digest: String,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordJson {
+    spec: HashedRekordSpecJson,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordSpecJson {
+    data: HashedRekordDataJson,
+    signature: HashedRekordSignatureJson,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordDataJson {
+    hash: HashedRekordHashJson,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordHashJson {
+    algorithm: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HashedRekordSignatureJson {
+    content: String,
+    public_key: HashedRekordPublicKeyJson,
+}
+
+#[derive(Deserialize)]
+struct HashedRekordPublicKeyJson {
+    content: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_from_inclusion_proof() {
+        let leaf = |data: &[u8]| {
+            Sha256::new()
+                .chain_update([0u8])
+                .chain_update(data)
+                .finalize()
+                .to_vec()
+        };
+        let node = |left: &[u8], right: &[u8]| {
+            Sha256::new()
+                .chain_update([1u8])
+                .chain_update(left)
+                .chain_update(right)
+                .finalize()
+                .to_vec()
+        };
+        let leaves = (0u8..3).map(|i| leaf(&[i])).collect::<Vec<_>>();
+        let root = node(&node(&leaves[0], &leaves[1]), &leaves[2]);
+
+        assert_eq!(
+            root_from_inclusion_proof(2, 3, &leaves[2], &[node(&leaves[0], &leaves[1])]),
+            Some(root.clone())
+        );
+        assert_eq!(
+            root_from_inclusion_proof(1, 3, &leaves[1], &[leaves[0].clone(), leaves[2].clone()]),
+            Some(root.clone())
+        );
+        assert_ne!(
+            root_from_inclusion_proof(0, 3, &leaves[1], &[leaves[1].clone(), leaves[2].clone()]),
+            Some(root)
+        );
+        assert_eq!(root_from_inclusion_proof(3, 3, &leaves[0], &[]), None);
+        assert_eq!(
+            root_from_inclusion_proof(0, 1, &leaves[0], &[leaves[1].clone()]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_parse() {
+        let envelope = "rekor.example.test - 1\n7\nAAAA\n\n\u{2014} rekor.example.test AQIDBAU=\n";
+        let checkpoint = Checkpoint::parse(envelope).unwrap();
+
+        assert_eq!(checkpoint.text, "rekor.example.test - 1\n7\nAAAA\n");
+        assert_eq!(checkpoint.tree_size, 7);
+        assert_eq!(checkpoint.root_hash, vec![0, 0, 0]);
+        assert_eq!(checkpoint.signatures, vec![vec![1, 2, 3, 4, 5]]);
+        assert!(Checkpoint::parse("rekor.example.test\n7\nAAAA\n").is_err());
+    }
+
+    #[test]
+    fn test_int64_accepts_strings_and_numbers() {
+        let values: Vec<Int64> = serde_json::from_str(r#"["12", 34]"#).unwrap();
+        assert_eq!(values[0].0, 12);
+        assert_eq!(values[1].0, 34);
+        assert!(serde_json::from_str::<Int64>(r#""x""#).is_err());
+    }
+
+    #[test]
+    fn test_bundle_requires_message_signature() {
+        let bundle = br#"{
+            "mediaType": "application/vnd.dev.sigstore.bundle.v0.3+json",
+            "verificationMaterial": {"certificate": {"rawBytes": "AA=="}, "tlogEntries": []},
+            "dsseEnvelope": {}
+        }"#;
+        let error = SigstoreBundle::parse(bundle).unwrap_err();
+        assert!(error.to_string().contains("message signature"));
+    }
+}
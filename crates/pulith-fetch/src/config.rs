@@ -1,6 +1,8 @@
+pub mod cancellation;
 pub mod fetch_options;
 pub mod sources;
 
+pub use cancellation::CancellationToken;
 pub use fetch_options::{
     FetchOptions, FetchPhase, RetryDelayFuture, RetryDelayProvider, RetryPolicy,
 };
@@ -0,0 +1,156 @@
+//! Cooperative cancellation and deadlines for fetch operations.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use tokio::sync::Notify;
+
+use crate::error::{Error, Result};
+
+/// Signal shared between a caller and the fetches it wants to stop.
+///
+/// Clones observe the same state. Cancelling aborts running transfers at
+/// their next await point, drops their staging workspaces, and makes them
+/// fail with [`Error::Cancelled`].
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every operation observing this token.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            // Register before checking so a concurrent `cancel` is not missed.
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Why an operation must stop now, if it must.
+pub(crate) fn interruption(
+    cancellation: Option<&CancellationToken>,
+    deadline: Option<Instant>,
+) -> Option<Error> {
+    if cancellation.is_some_and(CancellationToken::is_cancelled) {
+        Some(Error::Cancelled)
+    } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        Some(Error::DeadlineExceeded)
+    } else {
+        None
+    }
+}
+
+/// Run `operation` until it finishes, `cancellation` fires, or `deadline`
+/// passes, whichever comes first.
+///
+/// An interrupted operation is dropped, which releases its staging state.
+pub(crate) async fn interruptible<T>(
+    cancellation: Option<&CancellationToken>,
+    deadline: Option<Instant>,
+    operation: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    if cancellation.is_none() && deadline.is_none() {
+        return operation.await;
+    }
+
+    let cancelled = async {
+        match cancellation {
+            Some(token) => token.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    let expired = async {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        biased;
+        () = cancelled => Err(Error::Cancelled),
+        () = expired => Err(Error::DeadlineExceeded),
+        result = operation => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_wakes_waiters_and_interrupts() {
+        let token = CancellationToken::new();
+        let waiter = tokio::spawn({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+        let stalled = tokio::spawn({
+            let token = token.clone();
+            async move { interruptible(Some(&token), None, std::future::pending::<Result<()>>()).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        token.cancel();
+
+        waiter.await.unwrap();
+        assert!(matches!(stalled.await.unwrap(), Err(Error::Cancelled)));
+        assert!(matches!(
+            interruption(Some(&token), None),
+            Some(Error::Cancelled)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_interrupts_and_completed_work_wins() {
+        let deadline = Instant::now() + Duration::from_millis(20);
+        let result = interruptible(None, Some(deadline), std::future::pending::<Result<()>>());
+        assert!(matches!(result.await, Err(Error::DeadlineExceeded)));
+        assert!(matches!(
+            interruption(None, Some(deadline)),
+            Some(Error::DeadlineExceeded)
+        ));
+
+        let token = CancellationToken::new();
+        let later = Instant::now() + Duration::from_secs(60);
+        let result = interruptible(Some(&token), Some(later), async { Ok(7) }).await;
+        assert_eq!(result.unwrap(), 7);
+        assert!(interruption(Some(&token), Some(later)).is_none());
+    }
+}
@@ -2,9 +2,13 @@ use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use crate::config::cancellation::{CancellationToken, interruptible};
+use crate::error::{Error, Result};
+use crate::net::credentials::{CredentialProvider, authorized_headers, is_sensitive_header};
 use crate::progress::Progress;
+use crate::rate::{TokenBucket, jittered_delay, retry_delay};
 
 pub type ProgressCallback = Arc<dyn Fn(&Progress) + Send + Sync>;
 pub type RetryDelayFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
@@ -12,13 +16,26 @@ pub type RetryDelayProvider = Arc<dyn Fn(Duration) -> RetryDelayFuture + Send +
 
 /// Explicit retry behavior for transient transfer failures.
 ///
-/// Total attempts are `1 + max_retries`.
+/// Total attempts are `1 + max_retries`. Only transient failures are retried:
+/// connection errors, timeouts, and HTTP 408, 425, 429 and 5xx responses
+/// other than 501 and 505. A server `Retry-After`, capped at
+/// `max_retry_after`, replaces the computed backoff for the following
+/// attempt.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RetryPolicy {
     /// Number of retries after the initial attempt.
     pub max_retries: u32,
     /// Base exponential backoff duration.
     pub base_backoff: Duration,
+    /// Upper bound of the random delay added to each computed backoff.
+    pub max_jitter: Duration,
+    /// Total time budget across all attempts and delays.
+    ///
+    /// When the next delay would overrun the budget, the fetch fails with
+    /// [`Error::Timeout`] instead of waiting.
+    pub max_elapsed: Option<Duration>,
+    /// Longest server `Retry-After` waited for; longer values are clamped.
+    pub max_retry_after: Duration,
 }
 
 impl Default for RetryPolicy {
@@ -26,6 +43,68 @@ impl Default for RetryPolicy {
         Self {
             max_retries: 3,
             base_backoff: Duration::from_millis(100),
+            max_jitter: Duration::ZERO,
+            max_elapsed: None,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    #[must_use]
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    #[must_use]
+    pub fn with_base_backoff(mut self, base_backoff: Duration) -> Self {
+        self.base_backoff = base_backoff;
+        self
+    }
+
+    #[must_use]
+    pub fn with_jitter(mut self, max_jitter: Duration) -> Self {
+        self.max_jitter = max_jitter;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Whether `error` may succeed if the same request is attempted again.
+    pub fn is_transient(&self, error: &Error) -> bool {
+        match error {
+            Error::Network(_) | Error::Timeout(_) => true,
+            Error::Http { status, .. } => {
+                matches!(status, 408 | 425 | 429)
+                    || (*status >= 500 && !matches!(status, 501 | 505))
+            }
+            _ => false,
+        }
+    }
+
+    /// Delay before retry number `retry` (0-based) after `error`.
+    ///
+    /// A `Retry-After` carried by `error` is honored up to
+    /// `max_retry_after`; otherwise the exponential backoff is used with
+    /// jitter added.
+    pub fn delay_after(&self, retry: u32, error: &Error) -> Duration {
+        match error {
+            Error::Http {
+                retry_after: Some(after),
+                ..
+            } => (*after).min(self.max_retry_after),
+            _ => jittered_delay(retry_delay(retry, self.base_backoff), self.max_jitter),
         }
     }
 }
@@ -116,6 +195,32 @@ pub struct FetchOptions {
     /// Default: empty
     pub headers: Arc<[(String, String)]>,
 
+    /// Source of the `Authorization` header, consulted for each request's
+    /// URL. An explicit `Authorization` in `headers` takes precedence.
+    ///
+    /// Default: None
+    pub credentials: Option<Arc<dyn CredentialProvider>>,
+
+    /// Bandwidth budget applied to response bodies as they are written.
+    ///
+    /// The bucket is shared by every fetch whose options were cloned from
+    /// these, so concurrent downloads split one budget. Adaptive buckets
+    /// have their rate adjusted as bytes flow through them.
+    ///
+    /// Default: None (unlimited)
+    pub bandwidth_limit: Option<Arc<TokenBucket>>,
+
+    /// Token that aborts the fetch with [`Error::Cancelled`] when cancelled.
+    ///
+    /// Default: None
+    pub cancellation: Option<CancellationToken>,
+
+    /// Point in time after which the fetch fails with
+    /// [`Error::DeadlineExceeded`], retries and backoff included.
+    ///
+    /// Default: None
+    pub deadline: Option<Instant>,
+
     /// Progress callback invoked on state transitions and chunk writes.
     ///
     /// The callback is invoked:
@@ -141,7 +246,11 @@ impl fmt::Debug for FetchOptions {
             .field("retry_policy", &self.retry_policy)
             .field("expected_bytes", &self.expected_bytes)
             .field("resume_offset", &self.resume_offset)
-            .field("headers", &self.headers)
+            .field("headers", &RedactedHeaders(&self.headers))
+            .field("credentials", &self.credentials.as_ref().map(|_| "{ ... }"))
+            .field("bandwidth_limit", &self.bandwidth_limit)
+            .field("cancellation", &self.cancellation)
+            .field("deadline", &self.deadline)
             .field("on_progress", &"{ ... }")
             .field("retry_delay_provider", &"{ ... }")
             .finish()
@@ -156,6 +265,10 @@ impl Default for FetchOptions {
             expected_bytes: None,
             resume_offset: None,
             headers: Arc::new([]),
+            credentials: None,
+            bandwidth_limit: None,
+            cancellation: None,
+            deadline: None,
             on_progress: None,
             retry_delay_provider: None,
         }
@@ -272,6 +385,69 @@ impl FetchOptions {
         self
     }
 
+    /// Set the credential provider consulted for each request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pulith_fetch::{CredentialChain, EnvTokenCredentials, FetchOptions, NetrcCredentials};
+    /// use std::sync::Arc;
+    ///
+    /// let credentials = CredentialChain::new()
+    ///     .with_provider(EnvTokenCredentials::new().with_token("artifacts.corp.example", "ARTIFACTS_TOKEN"))
+    ///     .with_provider(NetrcCredentials::from_default_location()?);
+    /// let options = FetchOptions::default().credentials(Arc::new(credentials));
+    /// # Ok::<(), pulith_fetch::Error>(())
+    /// ```
+    #[must_use]
+    pub fn credentials(mut self, credentials: Arc<dyn CredentialProvider>) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Share a bandwidth budget with every fetch using these options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pulith_fetch::{FetchOptions, TokenBucket};
+    /// use std::sync::Arc;
+    ///
+    /// // 4 MiB/s across all downloads, with up to 1 MiB of burst.
+    /// let budget = Arc::new(TokenBucket::new(1024 * 1024, 4 * 1024 * 1024));
+    /// let options = FetchOptions::default().bandwidth_limit(budget);
+    /// ```
+    #[must_use]
+    pub fn bandwidth_limit(mut self, limiter: Arc<TokenBucket>) -> Self {
+        self.bandwidth_limit = Some(limiter);
+        self
+    }
+
+    /// Abort the fetch when `token` is cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pulith_fetch::{CancellationToken, FetchOptions};
+    ///
+    /// let token = CancellationToken::new();
+    /// let options = FetchOptions::default().cancellation(token.clone());
+    /// // From a Ctrl-C handler:
+    /// token.cancel();
+    /// ```
+    #[must_use]
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Abort the fetch once `deadline` has passed.
+    #[must_use]
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
     /// Set the progress callback.
     ///
     /// # Examples
@@ -305,6 +481,47 @@ impl FetchOptions {
         self.retry_delay_provider = Some(provider);
         self
     }
+
+    /// Headers for a request to `url`, including its credential.
+    pub(crate) async fn request_headers(&self, url: &str) -> Result<Vec<(String, String)>> {
+        authorized_headers(&self.headers, self.credentials.as_ref(), url).await
+    }
+
+    /// Run `operation` unless cancelled or out of time first.
+    pub(crate) async fn interruptible<T>(
+        &self,
+        operation: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        interruptible(self.cancellation.as_ref(), self.deadline, operation).await
+    }
+
+    /// Wait until the bandwidth budget allows `bytes` more to be written.
+    pub(crate) async fn throttle(&self, bytes: usize) {
+        if let Some(limiter) = &self.bandwidth_limit {
+            limiter.acquire(bytes).await;
+            if limiter.is_adaptive() {
+                limiter.check_and_adjust_rate();
+            }
+        }
+    }
+}
+
+/// Header list whose secret values print as `<redacted>`.
+struct RedactedHeaders<'a>(&'a [(String, String)]);
+
+impl fmt::Debug for RedactedHeaders<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|(name, value)| {
+                let value = if is_sensitive_header(name) {
+                    "<redacted>"
+                } else {
+                    value.as_str()
+                };
+                (name, value)
+            }))
+            .finish()
+    }
 }
 
 #[cfg(test)]
@@ -312,6 +529,57 @@ mod tests {
     use super::*;
     use std::sync::atomic::{AtomicU32, Ordering};
 
+    #[test]
+    fn test_retry_policy_classifies_failures() {
+        let policy = RetryPolicy::default();
+        let http = |status| Error::Http {
+            status,
+            message: String::new(),
+            retry_after: None,
+        };
+
+        assert!(policy.is_transient(&Error::Network("connection reset".to_string())));
+        assert!(policy.is_transient(&Error::Timeout("read".to_string())));
+        for status in [408, 425, 429, 500, 502, 503, 504] {
+            assert!(policy.is_transient(&http(status)), "{status}");
+        }
+        for status in [400, 401, 403, 404, 410, 501, 505] {
+            assert!(!policy.is_transient(&http(status)), "{status}");
+        }
+        assert!(!policy.is_transient(&Error::ChecksumMismatch {
+            expected: "a".to_string(),
+            actual: "b".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_retry_policy_delay_prefers_retry_after() {
+        let policy = RetryPolicy::default().with_base_backoff(Duration::from_millis(100));
+        let throttled = Error::Http {
+            status: 429,
+            message: String::new(),
+            retry_after: Some(Duration::from_secs(3)),
+        };
+
+        assert_eq!(policy.delay_after(2, &throttled), Duration::from_secs(3));
+        let stalled = Error::Http {
+            status: 503,
+            message: String::new(),
+            retry_after: Some(Duration::from_secs(86_400)),
+        };
+        assert_eq!(policy.delay_after(0, &stalled), Duration::from_secs(60));
+        assert_eq!(
+            policy
+                .with_max_retry_after(Duration::from_secs(5))
+                .delay_after(0, &stalled),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            policy.delay_after(2, &Error::Network("reset".to_string())),
+            Duration::from_millis(400)
+        );
+    }
+
     #[test]
     fn test_fetch_phase_display() {
         assert_eq!(FetchPhase::Connecting.to_string(), "Connecting");
@@ -338,6 +606,9 @@ mod tests {
         assert_eq!(options.expected_bytes, None);
         assert_eq!(options.resume_offset, None);
         assert!(options.headers.is_empty());
+        assert!(options.bandwidth_limit.is_none());
+        assert!(options.cancellation.is_none());
+        assert!(options.deadline.is_none());
         assert!(options.on_progress.is_none());
         assert!(options.retry_delay_provider.is_none());
     }
@@ -451,6 +722,19 @@ mod tests {
         assert!(debug_str.contains("{ ... }"));
     }
 
+    #[test]
+    fn test_fetch_options_debug_redacts_secrets() {
+        let options = FetchOptions::default()
+            .header("Authorization", "Bearer s3cr3t")
+            .header("X-Registry-Token", "t0k3n")
+            .header("Accept", "application/json");
+
+        let debug_str = format!("{options:?}");
+        assert!(!debug_str.contains("s3cr3t"));
+        assert!(!debug_str.contains("t0k3n"));
+        assert!(debug_str.contains("application/json"));
+    }
+
     #[test]
     fn test_fetch_options_builder_pattern() {
         let hash = [2u8; 32];
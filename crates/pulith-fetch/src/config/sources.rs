@@ -74,6 +74,9 @@ pub struct MultiSourceOptions {
     pub strategy: SourceSelectionStrategy,
 
     /// Whether to verify all sources have same content
+    ///
+    /// When set, every source is downloaded regardless of `strategy` and the
+    /// fetch fails with `Error::InconsistentMirror` if any of them differ.
     pub verify_consistency: bool,
 
     /// Timeout for each source attempt
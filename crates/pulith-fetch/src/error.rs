@@ -8,13 +8,39 @@ pub enum Error {
     InvalidUrl(String),
 
     #[error("HTTP error: {status} {message}")]
-    Http { status: u16, message: String },
+    Http {
+        status: u16,
+        message: String,
+        /// Delay the server asked for before the next request.
+        retry_after: Option<std::time::Duration>,
+    },
 
     #[error("checksum mismatch: expected {expected}, got {actual}")]
     ChecksumMismatch { expected: String, actual: String },
 
-    #[error("max retries exceeded ({count} attempts)")]
-    MaxRetriesExceeded { count: u32 },
+    #[error(
+        "mirror {mirror} served different content: expected {expected_bytes} bytes with sha256 {expected}, got {actual_bytes} bytes with sha256 {actual}"
+    )]
+    InconsistentMirror {
+        mirror: String,
+        expected: String,
+        actual: String,
+        expected_bytes: u64,
+        actual_bytes: u64,
+    },
+
+    #[error("mirrors disagree and no content was served by a majority of the {mirrors} mirrors")]
+    NoMirrorMajority { mirrors: usize },
+
+    #[error("max retries exceeded ({count} attempts): {last_error}")]
+    MaxRetriesExceeded {
+        count: u32,
+        /// Failure of the final attempt.
+        #[source]
+        last_error: Box<Error>,
+        /// Earlier failures, each followed by a retry.
+        attempts: Vec<crate::fetch::FetchAttempt>,
+    },
 
     #[error("too many redirects ({count})")]
     TooManyRedirects { count: u32 },
@@ -28,6 +54,19 @@ pub enum Error {
     #[error("invalid state: {0}")]
     InvalidState(String),
 
+    #[error("verification failed: {0}")]
+    VerificationFailed(String),
+
+    #[error("batch job {job} failed: {source}")]
+    BatchJobFailed {
+        job: String,
+        #[source]
+        source: Box<Error>,
+    },
+
+    #[error("batch job {job} skipped: dependency {dependency} failed")]
+    BatchDependencyFailed { job: String, dependency: String },
+
     #[error(transparent)]
     Fs(#[from] pulith_fs::Error),
 
@@ -37,6 +76,15 @@ pub enum Error {
     #[error("timeout: {0}")]
     Timeout(String),
 
+    #[error("operation cancelled")]
+    Cancelled,
+
+    #[error("deadline exceeded")]
+    DeadlineExceeded,
+
+    #[error("shared fetch failed: {0}")]
+    SharedFetchFailed(String),
+
     #[error("transform error: {0}")]
     Transform(#[from] crate::codec::decompress::TransformError),
 }
@@ -87,7 +135,8 @@ mod tests {
         assert_eq!(
             Error::Http {
                 status: 404,
-                message: "Not Found".to_string()
+                message: "Not Found".to_string(),
+                retry_after: None,
             }
             .to_string(),
             "HTTP error: 404 Not Found"
@@ -103,8 +152,25 @@ mod tests {
         );
 
         assert_eq!(
-            Error::MaxRetriesExceeded { count: 3 }.to_string(),
-            "max retries exceeded (3 attempts)"
+            Error::InconsistentMirror {
+                mirror: "https://mirror.example.com/a".to_string(),
+                expected: "abc123".to_string(),
+                actual: "def456".to_string(),
+                expected_bytes: 10,
+                actual_bytes: 11,
+            }
+            .to_string(),
+            "mirror https://mirror.example.com/a served different content: expected 10 bytes with sha256 abc123, got 11 bytes with sha256 def456"
+        );
+
+        assert_eq!(
+            Error::MaxRetriesExceeded {
+                count: 3,
+                last_error: Box::new(Error::Network("reset".to_string())),
+                attempts: Vec::new(),
+            }
+            .to_string(),
+            "max retries exceeded (3 attempts): network error: reset"
         );
 
         assert_eq!(
@@ -217,6 +283,7 @@ mod tests {
     #[cfg(feature = "reqwest")]
     #[test]
     fn test_from_reqwest_error() {
+        crate::install_crypto_provider();
         let client = reqwest::Client::new();
         let _ = client.get("invalid-url");
         // The error would be returned when trying to send the request
@@ -1,15 +1,34 @@
 //! Download strategies and fetch implementations.
 
 pub mod batch;
+pub mod caching;
+pub mod coalescing;
 pub mod conditional;
 pub mod fetcher;
+pub mod git;
+pub mod health;
+pub mod manifest;
 pub mod multi_source;
+pub mod oci;
 pub mod resumable;
+pub mod s3;
 pub mod segmented;
 
-pub use batch::{BatchDownloadJob, BatchFetcher, BatchOptions};
+pub use batch::{BatchDownloadJob, BatchFetcher, BatchOptions, BatchResult};
+pub use caching::CachingFetcher;
+pub use coalescing::CoalescingFetcher;
 pub use conditional::{ConditionalFetcher, ConditionalOptions, RemoteMetadata};
-pub use fetcher::{FetchReceipt, FetchSource, Fetcher};
+pub use fetcher::{FetchAttempt, FetchReceipt, FetchSource, Fetcher};
+pub use git::GitFetcher;
+pub use health::{
+    HostHealth, LossReason, LostSource, MirrorHealth, SourceProbe, SourceSelection, rank_candidates,
+};
+pub use manifest::{ManifestDigest, ManifestTrust};
 pub use multi_source::MultiSourceFetcher;
-pub use resumable::{DownloadCheckpoint, ResumableFetcher};
+pub use oci::{
+    AnonymousOciAuth, OciAuth, OciBearerToken, OciDescriptor, OciFetcher, OciPlatform,
+    OciTokenService,
+};
+pub use resumable::{DownloadCheckpoint, ResumableFetcher, Sha256State};
+pub use s3::S3Fetcher;
 pub use segmented::{SegmentedFetcher, SegmentedOptions};
@@ -3,24 +3,47 @@
 //! This module provides the ability to download multiple files
 //! with dependency resolution and concurrency control.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Instant;
 
 use futures_util::{StreamExt, stream::FuturesUnordered};
+use pulith_resource::{DigestAlgorithm, ValidUrl, VerificationRequirement};
+use pulith_source::{
+    HttpAssetSource, PlannedSources, RemoteSource, SelectionStrategy, SourceDefinition, SourceSet,
+    SourceSpec,
+};
 use tokio::sync::Semaphore;
 
+use crate::FetchOptions;
+use crate::config::cancellation::{CancellationToken, interruptible, interruption};
 use crate::error::{Error, Result};
-use crate::{DownloadSource, FetchOptions, SourceType};
-use crate::{Fetcher, HttpClient};
+use crate::fetch::fetcher::{FetchReceipt, Fetcher, replace_destination_file, staging_dir};
+use crate::fetch::git::place;
+use crate::fetch::health::host_key;
+use crate::fetch::multi_source::{MultiSourceFetcher, candidate_label};
+use crate::net::http::HttpClient;
+use crate::rate::TokenBucket;
 
 /// Configuration for batch downloads.
 #[derive(Debug, Clone)]
 pub struct BatchOptions {
     /// Maximum number of concurrent downloads
     pub max_concurrent: usize,
+    /// Maximum number of concurrent jobs that may contact one `host[:port]`;
+    /// a job counts against every host in its planned sources
+    pub max_per_host: Option<usize>,
+    /// Bandwidth budget shared by every job that does not set its own
+    pub bandwidth_limit: Option<Arc<TokenBucket>>,
+    /// Token that stops the batch; running and unstarted jobs fail with
+    /// [`Error::Cancelled`]
+    pub cancellation: Option<CancellationToken>,
+    /// Point in time after which running and unstarted jobs fail with
+    /// [`Error::DeadlineExceeded`]
+    pub deadline: Option<Instant>,
     /// Whether to fail fast on first error or continue with other downloads
     pub fail_fast: bool,
     /// Retry policy for batch operations
@@ -31,6 +54,10 @@ impl Default for BatchOptions {
     fn default() -> Self {
         Self {
             max_concurrent: 4,
+            max_per_host: None,
+            bandwidth_limit: None,
+            cancellation: None,
+            deadline: None,
             fail_fast: false,
             retry_policy: BatchRetryPolicy::RetryCount(3),
         }
@@ -53,47 +80,122 @@ pub enum BatchRetryPolicy {
 pub struct BatchDownloadJob {
     /// Unique identifier for this job
     pub id: String,
-    /// URL to download from
-    pub url: String,
+    /// Candidates to download from, tried according to their strategy
+    pub sources: PlannedSources,
     /// Destination path
     pub destination: PathBuf,
-    /// Optional checksum for verification
-    pub checksum: Option<[u8; 32]>,
+    /// Requirement the download must meet before it is placed
+    pub verification: VerificationRequirement,
     /// Jobs that must complete before this one can start
     pub dependencies: Vec<String>,
     /// Fetch options specific to this job
     pub options: Option<FetchOptions>,
 }
 
+impl BatchDownloadJob {
+    /// Create a job fetching `sources` into `destination` without verification.
+    pub fn new(
+        id: impl Into<String>,
+        sources: PlannedSources,
+        destination: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            sources,
+            destination: destination.into(),
+            verification: VerificationRequirement::None,
+            dependencies: Vec::new(),
+            options: None,
+        }
+    }
+
+    /// Create a job fetching a single URL.
+    pub fn from_url(
+        id: impl Into<String>,
+        url: &str,
+        destination: impl Into<PathBuf>,
+    ) -> Result<Self> {
+        let url = ValidUrl::parse(url).map_err(|error| Error::InvalidUrl(error.to_string()))?;
+        let set = SourceSet::new(vec![SourceDefinition::Remote(RemoteSource::HttpAsset(
+            HttpAssetSource {
+                url,
+                file_name: None,
+            },
+        ))])
+        .map_err(|error| Error::InvalidState(error.to_string()))?;
+        Ok(Self::new(
+            id,
+            SourceSpec::new(set).plan(SelectionStrategy::OrderedFallback),
+            destination,
+        ))
+    }
+
+    #[must_use]
+    pub fn with_verification(mut self, verification: VerificationRequirement) -> Self {
+        self.verification = verification;
+        self
+    }
+
+    #[must_use]
+    pub fn with_dependency(mut self, id: impl Into<String>) -> Self {
+        self.dependencies.push(id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_options(mut self, options: FetchOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Every `host[:port]` the plan may contact, in a stable order, used to
+    /// key per-host limits.
+    fn hosts(&self) -> BTreeSet<String> {
+        self.sources
+            .candidates()
+            .iter()
+            .map(|candidate| host_key(&candidate_label(candidate)))
+            .collect()
+    }
+}
+
 /// Result of a batch download job.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct BatchResult {
     /// Job ID
     pub id: String,
-    /// Whether the download succeeded
-    pub success: bool,
-    /// Path to the downloaded file (if successful)
-    pub path: Option<PathBuf>,
-    /// Error message (if failed)
-    pub error: Option<String>,
+    /// Receipt of the placed download, or why the job failed
+    pub outcome: Result<FetchReceipt>,
     /// Time taken to download
     pub duration_ms: u64,
 }
 
+impl BatchResult {
+    /// Whether the download succeeded.
+    pub fn is_success(&self) -> bool {
+        self.outcome.is_ok()
+    }
+
+    /// Receipt of the download, if it succeeded.
+    pub fn receipt(&self) -> Option<&FetchReceipt> {
+        self.outcome.as_ref().ok()
+    }
+}
+
 /// Batch fetcher implementation.
 pub struct BatchFetcher<C: HttpClient> {
     fetcher: Arc<Fetcher<C>>,
-    _workspace_root: PathBuf,
+    workspace_root: PathBuf,
 }
 
-type JobFuture = Pin<Box<dyn Future<Output = (String, BatchResult)> + Send>>;
+type JobFuture = Pin<Box<dyn Future<Output = BatchResult> + Send>>;
 
 impl<C: HttpClient + 'static> BatchFetcher<C> {
     /// Create a new batch fetcher.
     pub fn new(fetcher: Fetcher<C>, workspace_root: impl Into<PathBuf>) -> Self {
         Self {
             fetcher: Arc::new(fetcher),
-            _workspace_root: workspace_root.into(),
+            workspace_root: workspace_root.into(),
         }
     }
 
@@ -103,6 +205,18 @@ impl<C: HttpClient + 'static> BatchFetcher<C> {
         jobs: Vec<BatchDownloadJob>,
         options: BatchOptions,
     ) -> Result<Vec<BatchResult>> {
+        // Batch jobs fetch no signature material, so these could never pass
+        if let Some(job) = jobs
+            .iter()
+            .find(|job| matches!(job.verification, VerificationRequirement::Signature(_)))
+        {
+            return Err(Error::InvalidState(format!(
+                "Batch job '{}' requires a signature, which batch downloads cannot verify; \
+                 fetch it with a signature verifier instead",
+                job.id
+            )));
+        }
+
         // Validate no circular dependencies
         self.validate_dependencies(&jobs)?;
 
@@ -239,62 +353,121 @@ impl<C: HttpClient + 'static> BatchFetcher<C> {
         jobs: Vec<BatchDownloadJob>,
         options: BatchOptions,
     ) -> Result<Vec<BatchResult>> {
-        let semaphore = Arc::new(Semaphore::new(options.max_concurrent));
+        let semaphore = Arc::new(Semaphore::new(options.max_concurrent.max(1)));
+        let mut host_semaphores: HashMap<String, Arc<Semaphore>> = HashMap::new();
         let mut futures: FuturesUnordered<JobFuture> = FuturesUnordered::new();
         let mut results = Vec::new();
-        let mut job_results = HashMap::new();
-        let mut pending_jobs = jobs.into_iter().enumerate().collect::<Vec<_>>();
+        let mut job_results: HashMap<String, bool> = HashMap::new();
+        let mut pending_jobs = jobs;
+
+        let interrupted = || interruption(options.cancellation.as_ref(), options.deadline);
 
         while !pending_jobs.is_empty() || !futures.is_empty() {
-            // Start jobs that have no unmet dependencies
+            // Jobs that have not started share the fate of an interrupted batch
+            while !pending_jobs.is_empty()
+                && let Some(error) = interrupted()
+            {
+                if options.fail_fast {
+                    return Err(error);
+                }
+                let job = pending_jobs.remove(0);
+                job_results.insert(job.id.clone(), false);
+                results.push(BatchResult {
+                    id: job.id,
+                    outcome: Err(error),
+                    duration_ms: 0,
+                });
+            }
+
+            // Start jobs whose dependencies succeeded and fail the ones whose
+            // dependencies did not
             let mut i = 0;
             while i < pending_jobs.len() {
-                let (_index, job) = &pending_jobs[i];
+                let job = &pending_jobs[i];
+
+                if let Some(dependency) = job
+                    .dependencies
+                    .iter()
+                    .find(|dep| job_results.get(*dep) == Some(&false))
+                    .cloned()
+                {
+                    let job = pending_jobs.remove(i);
+                    let error = Error::BatchDependencyFailed {
+                        job: job.id.clone(),
+                        dependency,
+                    };
+                    job_results.insert(job.id.clone(), false);
+                    results.push(BatchResult {
+                        id: job.id,
+                        outcome: Err(error),
+                        duration_ms: 0,
+                    });
+                    continue;
+                }
 
                 // Check if all dependencies are satisfied
-                let deps_satisfied = job.dependencies.iter().all(|dep| {
-                    job_results
-                        .get(dep)
-                        .is_some_and(|r: &BatchResult| r.success)
-                });
+                let deps_satisfied = job
+                    .dependencies
+                    .iter()
+                    .all(|dep| job_results.get(dep) == Some(&true));
 
                 if deps_satisfied {
-                    let job = pending_jobs.remove(i).1;
+                    let job = pending_jobs.remove(i);
                     let fetcher = Arc::clone(&self.fetcher);
+                    let workspace_root = self.workspace_root.clone();
                     let semaphore = Arc::clone(&semaphore);
-                    let _fail_fast = options.fail_fast;
+                    let host_semaphores = match options.max_per_host {
+                        Some(limit) => job
+                            .hosts()
+                            .into_iter()
+                            .map(|host| {
+                                Arc::clone(
+                                    host_semaphores
+                                        .entry(host)
+                                        .or_insert_with(|| Arc::new(Semaphore::new(limit.max(1)))),
+                                )
+                            })
+                            .collect(),
+                        None => Vec::new(),
+                    };
+                    let bandwidth_limit = options.bandwidth_limit.clone();
+                    let cancellation = options.cancellation.clone();
+                    let deadline = options.deadline;
 
                     let future: JobFuture = Box::pin(async move {
-                        let permit = semaphore.acquire().await;
-                        let start = std::time::Instant::now();
-
-                        let result = match permit {
-                            Ok(_permit) => match Self::execute_single_job(&fetcher, &job).await {
-                                Ok(path) => BatchResult {
-                                    id: job.id.clone(),
-                                    success: true,
-                                    path: Some(path),
-                                    error: None,
-                                    duration_ms: start.elapsed().as_millis() as u64,
-                                },
-                                Err(e) => BatchResult {
-                                    id: job.id.clone(),
-                                    success: false,
-                                    path: None,
-                                    error: Some(e.to_string()),
-                                    duration_ms: start.elapsed().as_millis() as u64,
-                                },
-                            },
-                            Err(e) => BatchResult {
-                                id: job.id.clone(),
-                                success: false,
-                                path: None,
-                                error: Some(format!("semaphore acquire error: {e}")),
-                                duration_ms: start.elapsed().as_millis() as u64,
-                            },
-                        };
-
-                        (job.id, result)
+                        let mut start = None;
+                        let outcome = interruptible(cancellation.as_ref(), deadline, async {
+                            // Wait for the hosts first so a job queued behind a busy
+                            // host does not hold one of the global slots. Hosts are
+                            // acquired in sorted order, so jobs sharing several hosts
+                            // cannot deadlock.
+                            let acquire_error = |e: tokio::sync::AcquireError| {
+                                Error::InvalidState(format!("semaphore acquire error: {e}"))
+                            };
+                            let mut host_permits = Vec::with_capacity(host_semaphores.len());
+                            for host_semaphore in &host_semaphores {
+                                host_permits
+                                    .push(host_semaphore.acquire().await.map_err(acquire_error)?);
+                            }
+                            let _permit = semaphore.acquire().await.map_err(acquire_error)?;
+                            start = Some(std::time::Instant::now());
+
+                            Self::execute_single_job(
+                                &fetcher,
+                                &workspace_root,
+                                &job,
+                                bandwidth_limit,
+                            )
+                            .await
+                        })
+                        .await;
+
+                        BatchResult {
+                            id: job.id,
+                            outcome,
+                            duration_ms: start
+                                .map_or(0, |start| start.elapsed().as_millis() as u64),
+                        }
                     });
 
                     futures.push(future);
@@ -304,19 +477,22 @@ impl<C: HttpClient + 'static> BatchFetcher<C> {
             }
 
             // Wait for at least one job to complete
-            if let Some(result) = futures.next().await {
-                let (job_id, job_result): (String, BatchResult) = result;
-
-                job_results.insert(job_id.clone(), job_result.clone());
-                results.push(job_result.clone());
+            if let Some(job_result) = futures.next().await {
+                job_results.insert(job_result.id.clone(), job_result.is_success());
 
                 // If fail_fast is enabled and this job failed, return error
-                if options.fail_fast && !job_result.success {
-                    return Err(Error::Network(format!(
-                        "Batch download failed (fail_fast enabled): {}",
-                        job_result.error.as_deref().unwrap_or_default()
-                    )));
+                if options.fail_fast
+                    && let Err(error) = job_result.outcome
+                {
+                    if matches!(error, Error::Cancelled | Error::DeadlineExceeded) {
+                        return Err(error);
+                    }
+                    return Err(Error::BatchJobFailed {
+                        job: job_result.id,
+                        source: Box::new(error),
+                    });
                 }
+                results.push(job_result);
             }
         }
 
@@ -324,36 +500,124 @@ impl<C: HttpClient + 'static> BatchFetcher<C> {
     }
 
     /// Execute a single download job.
+    ///
+    /// The download is staged in the batch workspace and only placed at the
+    /// job destination once it meets the job's verification requirement.
     async fn execute_single_job(
         fetcher: &Arc<Fetcher<C>>,
+        workspace_root: &Path,
         job: &BatchDownloadJob,
-    ) -> Result<PathBuf> {
-        let source = DownloadSource {
-            url: job.url.clone(),
-            priority: 0,
-            checksum: job.checksum,
-            source_type: SourceType::Primary,
-            region: None,
-        };
+        bandwidth_limit: Option<Arc<TokenBucket>>,
+    ) -> Result<FetchReceipt> {
+        let mut options = job.options.clone().unwrap_or_default();
+        if options.bandwidth_limit.is_none() {
+            options.bandwidth_limit = bandwidth_limit;
+        }
+        // A single pinned digest lets the fetcher reject a bad candidate and
+        // move on to the next one instead of failing the whole job.
+        if options.checksum.is_none()
+            && let VerificationRequirement::Digest(digest) = &job.verification
+            && digest.algorithm == DigestAlgorithm::Sha256
+        {
+            options.checksum = digest.bytes.as_slice().try_into().ok();
+        }
 
-        let options = job.options.clone().unwrap_or_default();
+        let staging = staging_dir(workspace_root, ".pulith-batch.")?;
+        let staged = staging.path().join(
+            job.destination
+                .file_name()
+                .unwrap_or_else(|| std::ffi::OsStr::new("download")),
+        );
+        let mut receipt = MultiSourceFetcher::new(Arc::clone(fetcher))
+            .fetch_planned_sources_with_receipt(&job.sources, &staged, &options)
+            .await?;
+        verify_requirement(&job.verification, &receipt)?;
+
+        // Only a staged directory may replace a directory at the destination;
+        // a file download refuses one like the plain fetcher does.
+        if std::fs::symlink_metadata(&staged).is_ok_and(|meta| meta.is_dir()) {
+            place(&staged, true, &job.destination)?;
+        } else {
+            if let Some(parent) = job.destination.parent() {
+                std::fs::create_dir_all(parent).map_err(|source| {
+                    Error::Fs(pulith_fs::Error::Write {
+                        path: parent.to_path_buf(),
+                        source,
+                    })
+                })?;
+            }
+            replace_destination_file(&staged, &job.destination)?;
+        }
+        receipt.destination = job.destination.clone();
+        Ok(receipt)
+    }
+}
 
-        Ok(fetcher
-            .try_source(&source, &job.destination, &options)
-            .await?
-            .destination)
+/// Check a fetched artifact against `requirement`.
+///
+/// Digests are compared with the SHA-256 recorded on the receipt, so other
+/// algorithms are only accepted as alternatives in `AnyOf`. Signature
+/// requirements are rejected by [`BatchFetcher::fetch_batch`] before any job
+/// runs.
+fn verify_requirement(requirement: &VerificationRequirement, receipt: &FetchReceipt) -> Result<()> {
+    let digests = match requirement {
+        VerificationRequirement::None => return Ok(()),
+        VerificationRequirement::Signature(_) => {
+            return Err(Error::VerificationFailed(
+                "batch downloads cannot verify signatures".to_string(),
+            ));
+        }
+        VerificationRequirement::Digest(digest) => std::slice::from_ref(digest),
+        VerificationRequirement::AnyOf(digests) | VerificationRequirement::AllOf(digests) => {
+            digests.as_slice()
+        }
+    };
+
+    let expected: Vec<String> = digests
+        .iter()
+        .filter(|digest| digest.algorithm == DigestAlgorithm::Sha256)
+        .map(|digest| digest.hex())
+        .collect();
+    let require_all = matches!(requirement, VerificationRequirement::AllOf(_));
+    if expected.is_empty() || (require_all && expected.len() != digests.len()) {
+        return Err(Error::VerificationFailed(
+            "only sha256 digests can be checked against a fetch receipt".to_string(),
+        ));
+    }
+    let Some(actual) = receipt.sha256_hex.as_deref() else {
+        return Err(Error::VerificationFailed(
+            "no sha256 digest was recorded for the fetched artifact".to_string(),
+        ));
+    };
+
+    let matches = |digest: &String| digest.eq_ignore_ascii_case(actual);
+    let satisfied = if require_all {
+        expected.iter().all(matches)
+    } else {
+        expected.iter().any(matches)
+    };
+    if satisfied {
+        Ok(())
+    } else {
+        Err(Error::ChecksumMismatch {
+            expected: expected.join(" or "),
+            actual: actual.to_string(),
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     #[test]
     fn test_batch_options_default() {
         let options = BatchOptions::default();
         assert_eq!(options.max_concurrent, 4);
+        assert_eq!(options.max_per_host, None);
+        assert!(options.bandwidth_limit.is_none());
+        assert!(options.cancellation.is_none());
+        assert!(options.deadline.is_none());
         assert!(!options.fail_fast);
         assert!(matches!(
             options.retry_policy,
@@ -361,25 +625,398 @@ mod tests {
         ));
     }
 
+    /// Serves 100-byte bodies after a short delay, recording the peak number
+    /// of requests in flight per host.
+    struct SlowHostClient {
+        in_flight: std::sync::Mutex<HashMap<String, (usize, usize)>>,
+    }
+
+    impl HttpClient for SlowHostClient {
+        type Error = std::io::Error;
+
+        async fn stream(
+            &self,
+            url: &str,
+            _headers: &[(String, String)],
+        ) -> std::result::Result<
+            crate::net::http::BoxStream<'static, std::result::Result<bytes::Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            let host = host_key(url);
+            {
+                let mut in_flight = self.in_flight.lock().unwrap();
+                let (current, peak) = in_flight.entry(host.clone()).or_default();
+                *current += 1;
+                *peak = (*peak).max(*current);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            self.in_flight.lock().unwrap().get_mut(&host).unwrap().0 -= 1;
+            Ok(Box::pin(futures_util::stream::once(async {
+                Ok(bytes::Bytes::from(vec![0u8; 100]))
+            })))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(Some(100))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_caps_hosts_and_shares_bandwidth() {
+        let temp = tempfile::tempdir().unwrap();
+        let client = SlowHostClient {
+            in_flight: std::sync::Mutex::new(HashMap::new()),
+        };
+        let batch = BatchFetcher::new(
+            Fetcher::new(client, temp.path().join("workspace")),
+            temp.path().join("workspace"),
+        );
+        let jobs = ["a", "a", "a", "a", "b", "b"]
+            .iter()
+            .enumerate()
+            .map(|(index, host)| {
+                BatchDownloadJob::from_url(
+                    format!("job{index}"),
+                    &format!("http://{host}.example/{index}"),
+                    temp.path().join(format!("{index}.bin")),
+                )
+                .unwrap()
+            })
+            .collect();
+        // 100 bytes of burst, then 1000 bytes/second for everyone.
+        let budget = Arc::new(TokenBucket::new(100, 1000));
+        let options = BatchOptions {
+            max_concurrent: 6,
+            max_per_host: Some(2),
+            bandwidth_limit: Some(Arc::clone(&budget)),
+            ..BatchOptions::default()
+        };
+
+        let start = std::time::Instant::now();
+        let results = batch.fetch_batch(jobs, options).await.unwrap();
+
+        assert!(results.iter().all(BatchResult::is_success), "{results:?}");
+        let in_flight = batch.fetcher.client.in_flight.lock().unwrap();
+        assert_eq!(in_flight["a.example"].1, 2);
+        assert_eq!(in_flight["b.example"].1, 2);
+        // 600 bytes through one budget: 100 up front, 500 at 1000 bytes/second.
+        assert!(start.elapsed() >= std::time::Duration::from_millis(450));
+        assert_eq!(
+            budget
+                .get_metrics()
+                .total_bytes
+                .load(std::sync::atomic::Ordering::Relaxed),
+            600
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_host_caps_cover_every_planned_mirror() {
+        let temp = tempfile::tempdir().unwrap();
+        let client = SlowHostClient {
+            in_flight: std::sync::Mutex::new(HashMap::new()),
+        };
+        let batch = BatchFetcher::new(
+            Fetcher::new(client, temp.path().join("workspace")),
+            temp.path().join("workspace"),
+        );
+        let raced = SourceSpec::new(
+            SourceSet::new(
+                ["http://a.example/tool", "http://b.example/tool"]
+                    .into_iter()
+                    .map(|url| {
+                        SourceDefinition::Remote(RemoteSource::HttpAsset(HttpAssetSource {
+                            url: ValidUrl::parse(url).unwrap(),
+                            file_name: None,
+                        }))
+                    })
+                    .collect(),
+            )
+            .unwrap(),
+        )
+        .plan(SelectionStrategy::Race);
+        let mut jobs = vec![BatchDownloadJob::new(
+            "raced",
+            raced,
+            temp.path().join("tool"),
+        )];
+        jobs.extend((0..2).map(|index| {
+            BatchDownloadJob::from_url(
+                format!("b{index}"),
+                &format!("http://b.example/{index}"),
+                temp.path().join(format!("b{index}")),
+            )
+            .unwrap()
+        }));
+        let options = BatchOptions {
+            max_per_host: Some(1),
+            ..BatchOptions::default()
+        };
+
+        let results = batch.fetch_batch(jobs, options).await.unwrap();
+
+        assert!(results.iter().all(BatchResult::is_success), "{results:?}");
+        let in_flight = batch.fetcher.client.in_flight.lock().unwrap();
+        assert_eq!(in_flight["a.example"].1, 1);
+        assert_eq!(in_flight["b.example"].1, 1);
+    }
+
+    /// Serves fixed bodies by URL and answers 404 for anything else; GETs
+    /// carrying `x-stall` never finish their body.
+    struct MirrorClient;
+
+    impl HttpClient for MirrorClient {
+        type Error = std::io::Error;
+
+        async fn stream(
+            &self,
+            _url: &str,
+            _headers: &[(String, String)],
+        ) -> std::result::Result<
+            crate::net::http::BoxStream<'static, std::result::Result<bytes::Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            Err(std::io::Error::other("stream is not used"))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn send(
+            &self,
+            method: crate::net::response::Method,
+            url: &str,
+            headers: &[(String, String)],
+        ) -> std::result::Result<crate::net::response::HttpResponse<Self::Error>, Self::Error>
+        {
+            if method == crate::net::response::Method::Get
+                && headers.iter().any(|(name, _)| name == "x-stall")
+            {
+                return Ok(crate::net::response::HttpResponse {
+                    head: crate::net::response::ResponseHead::new(200, url),
+                    body: Box::pin(futures_util::stream::pending()),
+                });
+            }
+            let (status, body) = match url {
+                "http://mirror.example/tool" => (200, "tool-bytes"),
+                "http://mirror.example/lib" => (200, "lib-bytes"),
+                _ => (404, "not found"),
+            };
+            Ok(crate::net::response::HttpResponse {
+                head: crate::net::response::ResponseHead::new(status, url),
+                body: Box::pin(futures_util::stream::once(async move {
+                    Ok(bytes::Bytes::from(body))
+                })),
+            })
+        }
+    }
+
+    fn sha256(data: &[u8]) -> pulith_resource::ValidDigest {
+        use sha2::Digest;
+        pulith_resource::ValidDigest::from_bytes(
+            DigestAlgorithm::Sha256,
+            sha2::Sha256::digest(data).to_vec(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_returns_verified_receipts_and_typed_errors() {
+        let temp = tempfile::tempdir().unwrap();
+        let batch = BatchFetcher::new(
+            Fetcher::new(MirrorClient, temp.path().join("workspace")),
+            temp.path().join("workspace"),
+        );
+        let tool_sources = SourceSpec::new(
+            SourceSet::new(
+                ["http://broken.example/tool", "http://mirror.example/tool"]
+                    .into_iter()
+                    .map(|url| {
+                        SourceDefinition::Remote(RemoteSource::HttpAsset(HttpAssetSource {
+                            url: ValidUrl::parse(url).unwrap(),
+                            file_name: None,
+                        }))
+                    })
+                    .collect(),
+            )
+            .unwrap(),
+        )
+        .plan(SelectionStrategy::OrderedFallback);
+        let jobs = vec![
+            BatchDownloadJob::new("tool", tool_sources, temp.path().join("bin/tool"))
+                .with_verification(VerificationRequirement::Digest(sha256(b"tool-bytes"))),
+            BatchDownloadJob::from_url("lib", "http://mirror.example/lib", temp.path().join("lib"))
+                .unwrap()
+                .with_verification(VerificationRequirement::AnyOf(vec![
+                    pulith_resource::ValidDigest::from_bytes(DigestAlgorithm::Blake3, vec![0; 32])
+                        .unwrap(),
+                    sha256(b"other-bytes"),
+                ])),
+            BatchDownloadJob::from_url(
+                "app",
+                "http://mirror.example/tool",
+                temp.path().join("app"),
+            )
+            .unwrap()
+            .with_dependency("lib"),
+            BatchDownloadJob::from_url(
+                "mistyped",
+                "http://mirror.example/tool",
+                temp.path().join("home"),
+            )
+            .unwrap(),
+        ];
+        std::fs::create_dir_all(temp.path().join("home")).unwrap();
+        std::fs::write(temp.path().join("home/notes"), "keep").unwrap();
+
+        let results = batch
+            .fetch_batch(jobs, BatchOptions::default())
+            .await
+            .unwrap();
+        let result = |id: &str| results.iter().find(|result| result.id == id).unwrap();
+
+        let receipt = result("tool").receipt().unwrap();
+        assert_eq!(receipt.destination, temp.path().join("bin/tool"));
+        assert_eq!(
+            receipt.source,
+            crate::FetchSource::Url("http://mirror.example/tool".to_string())
+        );
+        assert_eq!(
+            receipt.sha256_hex.as_deref(),
+            Some(sha256(b"tool-bytes").hex().as_str())
+        );
+        assert_eq!(
+            std::fs::read(temp.path().join("bin/tool")).unwrap(),
+            b"tool-bytes"
+        );
+
+        assert!(matches!(
+            result("lib").outcome,
+            Err(Error::ChecksumMismatch { .. })
+        ));
+        assert!(!temp.path().join("lib").exists());
+        assert!(matches!(
+            &result("app").outcome,
+            Err(Error::BatchDependencyFailed { dependency, .. }) if dependency == "lib"
+        ));
+        assert!(!temp.path().join("app").exists());
+
+        assert!(matches!(
+            result("mistyped").outcome,
+            Err(Error::DestinationIsDirectory)
+        ));
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("home/notes")).unwrap(),
+            "keep"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_rejects_signature_requirements_up_front() {
+        let temp = tempfile::tempdir().unwrap();
+        let batch = BatchFetcher::new(
+            Fetcher::new(MirrorClient, temp.path().join("workspace")),
+            temp.path().join("workspace"),
+        );
+        let requirement = pulith_resource::SignatureRequirement::new(
+            pulith_resource::SignatureScheme::Minisign,
+            ["RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3"],
+        )
+        .unwrap();
+        let jobs = vec![
+            BatchDownloadJob::from_url("lib", "http://mirror.example/lib", temp.path().join("lib"))
+                .unwrap(),
+            BatchDownloadJob::from_url(
+                "tool",
+                "http://mirror.example/tool",
+                temp.path().join("tool"),
+            )
+            .unwrap()
+            .with_verification(VerificationRequirement::Signature(requirement)),
+        ];
+
+        let error = batch
+            .fetch_batch(jobs, BatchOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(&error, Error::InvalidState(message) if message.contains("'tool'")));
+        assert!(!temp.path().join("lib").exists());
+    }
+
+    #[tokio::test]
+    async fn test_batch_deadline_stops_running_and_pending_jobs() {
+        let temp = tempfile::tempdir().unwrap();
+        let batch = BatchFetcher::new(
+            Fetcher::new(MirrorClient, temp.path().join("workspace")),
+            temp.path().join("workspace"),
+        );
+        // The mirror answers HEAD but the stalled body never ends.
+        let stalled = BatchDownloadJob::from_url(
+            "stalled",
+            "http://mirror.example/tool",
+            temp.path().join("stalled"),
+        )
+        .unwrap()
+        .with_options(
+            FetchOptions::default().headers(vec![("x-stall".to_string(), "1".to_string())]),
+        );
+        let jobs = vec![
+            stalled,
+            BatchDownloadJob::from_url(
+                "after",
+                "http://mirror.example/lib",
+                temp.path().join("after"),
+            )
+            .unwrap()
+            .with_dependency("stalled"),
+        ];
+        let options = BatchOptions {
+            deadline: Some(std::time::Instant::now() + std::time::Duration::from_millis(100)),
+            ..BatchOptions::default()
+        };
+
+        let results = batch.fetch_batch(jobs, options).await.unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(
+            results
+                .iter()
+                .all(|result| matches!(result.outcome, Err(Error::DeadlineExceeded))),
+            "{results:?}"
+        );
+        assert!(!temp.path().join("stalled").exists());
+        assert!(
+            std::fs::read_dir(temp.path().join("workspace/staging"))
+                .unwrap()
+                .next()
+                .is_none()
+        );
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let options = BatchOptions {
+            cancellation: Some(token),
+            fail_fast: true,
+            ..BatchOptions::default()
+        };
+        let jobs = vec![
+            BatchDownloadJob::from_url("lib", "http://mirror.example/lib", temp.path().join("lib"))
+                .unwrap(),
+        ];
+        assert!(matches!(
+            batch.fetch_batch(jobs, options).await,
+            Err(Error::Cancelled)
+        ));
+    }
+
     #[test]
     fn test_validate_dependencies_no_cycle() {
         let jobs = vec![
-            BatchDownloadJob {
-                id: "job1".to_string(),
-                url: "http://example.com/1".to_string(),
-                destination: PathBuf::from("/tmp/1"),
-                checksum: None,
-                dependencies: vec![],
-                options: None,
-            },
-            BatchDownloadJob {
-                id: "job2".to_string(),
-                url: "http://example.com/2".to_string(),
-                destination: PathBuf::from("/tmp/2"),
-                checksum: None,
-                dependencies: vec!["job1".to_string()],
-                options: None,
-            },
+            BatchDownloadJob::from_url("job1", "http://example.com/1", "/tmp/1").unwrap(),
+            BatchDownloadJob::from_url("job2", "http://example.com/2", "/tmp/2")
+                .unwrap()
+                .with_dependency("job1"),
         ];
 
         // Create a mock fetcher for testing
@@ -399,22 +1036,12 @@ mod tests {
     #[test]
     fn test_validate_dependencies_cycle() {
         let jobs = vec![
-            BatchDownloadJob {
-                id: "job1".to_string(),
-                url: "http://example.com/1".to_string(),
-                destination: PathBuf::from("/tmp/1"),
-                checksum: None,
-                dependencies: vec!["job2".to_string()],
-                options: None,
-            },
-            BatchDownloadJob {
-                id: "job2".to_string(),
-                url: "http://example.com/2".to_string(),
-                destination: PathBuf::from("/tmp/2"),
-                checksum: None,
-                dependencies: vec!["job1".to_string()],
-                options: None,
-            },
+            BatchDownloadJob::from_url("job1", "http://example.com/1", "/tmp/1")
+                .unwrap()
+                .with_dependency("job2"),
+            BatchDownloadJob::from_url("job2", "http://example.com/2", "/tmp/2")
+                .unwrap()
+                .with_dependency("job1"),
         ];
 
         // Create a mock fetcher for testing
@@ -436,30 +1063,13 @@ mod tests {
     #[test]
     fn test_topological_sort() {
         let jobs = vec![
-            BatchDownloadJob {
-                id: "job1".to_string(),
-                url: "http://example.com/1".to_string(),
-                destination: PathBuf::from("/tmp/1"),
-                checksum: None,
-                dependencies: vec![],
-                options: None,
-            },
-            BatchDownloadJob {
-                id: "job2".to_string(),
-                url: "http://example.com/2".to_string(),
-                destination: PathBuf::from("/tmp/2"),
-                checksum: None,
-                dependencies: vec!["job1".to_string()],
-                options: None,
-            },
-            BatchDownloadJob {
-                id: "job3".to_string(),
-                url: "http://example.com/3".to_string(),
-                destination: PathBuf::from("/tmp/3"),
-                checksum: None,
-                dependencies: vec!["job2".to_string()],
-                options: None,
-            },
+            BatchDownloadJob::from_url("job1", "http://example.com/1", "/tmp/1").unwrap(),
+            BatchDownloadJob::from_url("job2", "http://example.com/2", "/tmp/2")
+                .unwrap()
+                .with_dependency("job1"),
+            BatchDownloadJob::from_url("job3", "http://example.com/3", "/tmp/3")
+                .unwrap()
+                .with_dependency("job2"),
         ];
 
         // Create a mock fetcher for testing
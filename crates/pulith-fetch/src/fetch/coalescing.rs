@@ -0,0 +1,349 @@
+//! Single-flight coalescing of identical concurrent fetches.
+//!
+//! [`CoalescingFetcher`] lets callers that ask for content already being
+//! downloaded in this process join the running transfer instead of starting
+//! their own. The content is staged once in the workspace and hard-linked
+//! (or copied, across filesystems) to every caller's destination.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures_util::future::{BoxFuture, FutureExt, Shared, WeakShared};
+
+use crate::config::FetchOptions;
+use crate::error::{Error, Result};
+use crate::fetch::fetcher::{FetchReceipt, Fetcher, replace_destination_file, staging_dir};
+use crate::net::credentials::redact_url;
+use crate::net::http::HttpClient;
+
+type FlightFuture = BoxFuture<'static, std::result::Result<Arc<Flight>, Arc<FlightError>>>;
+
+/// What makes two fetches interchangeable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FlightKey {
+    /// Same pinned SHA-256, wherever it is downloaded from.
+    Digest([u8; 32]),
+    Url(String),
+}
+
+/// A finished transfer; the staged copy lives as long as any caller holds it.
+struct Flight {
+    _staging: tempfile::TempDir,
+    staged: PathBuf,
+    receipt: FetchReceipt,
+}
+
+/// A failed transfer. The caller that started it takes the typed error,
+/// callers that joined it see the message.
+struct FlightError {
+    message: String,
+    error: Mutex<Option<Error>>,
+}
+
+struct InFlight {
+    id: u64,
+    flight: WeakShared<FlightFuture>,
+}
+
+/// Fetcher that runs at most one transfer per URL or pinned digest at a time.
+///
+/// Fetches with the same `options.checksum`, or without a checksum for the
+/// same URL, share a single transfer while it is running. Joining callers
+/// get content fetched with the options of the caller that started the
+/// transfer; their own cancellation token and deadline only end their own
+/// wait. The transfer is dropped once every caller has gone, and a fetch
+/// issued after it finished starts a new one.
+pub struct CoalescingFetcher<C: HttpClient> {
+    fetcher: Arc<Fetcher<C>>,
+    flights: Arc<Mutex<HashMap<FlightKey, InFlight>>>,
+    next_id: AtomicU64,
+}
+
+impl<C: HttpClient + 'static> CoalescingFetcher<C> {
+    pub fn new(fetcher: Arc<Fetcher<C>>) -> Self {
+        Self {
+            fetcher,
+            flights: Arc::default(),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Fetch `url` to `destination`, joining an identical fetch in flight.
+    ///
+    /// Receipts of joined fetches are marked `coalesced`. When a shared
+    /// transfer fails, callers that joined it get
+    /// [`Error::SharedFetchFailed`].
+    #[tracing::instrument(skip(self, options), fields(url = %redact_url(url), destination = %destination.display()))]
+    pub async fn fetch_with_receipt(
+        &self,
+        url: &str,
+        destination: &Path,
+        options: FetchOptions,
+    ) -> Result<FetchReceipt> {
+        let (flight, started) = self.join_or_start(url, destination, &options);
+        let outcome = options.interruptible(flight.map(Ok)).await?;
+
+        match outcome {
+            Ok(flight) => {
+                link_into(&flight.staged, destination)?;
+                let mut receipt = flight.receipt.clone();
+                receipt.destination = destination.to_path_buf();
+                receipt.coalesced = !started;
+                Ok(receipt)
+            }
+            Err(failure) => {
+                let typed = started.then(|| lock(&failure.error).take()).flatten();
+                Err(typed.unwrap_or_else(|| Error::SharedFetchFailed(failure.message.clone())))
+            }
+        }
+    }
+
+    /// Join the running transfer for this fetch, or start one. The flag
+    /// tells whether this call started it.
+    fn join_or_start(
+        &self,
+        url: &str,
+        destination: &Path,
+        options: &FetchOptions,
+    ) -> (Shared<FlightFuture>, bool) {
+        let key = match options.checksum {
+            Some(digest) => FlightKey::Digest(digest),
+            None => FlightKey::Url(url.to_string()),
+        };
+
+        let mut flights = lock(&self.flights);
+        if let Some(running) = flights.get(&key).and_then(|entry| entry.flight.upgrade()) {
+            return (running, false);
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut flight_options = options.clone();
+        flight_options.cancellation = None;
+        flight_options.deadline = None;
+        let name = destination
+            .file_name()
+            .map_or_else(|| OsString::from("download"), OsString::from);
+        let fetcher = Arc::clone(&self.fetcher);
+        let registry = Arc::clone(&self.flights);
+        let (url, finished_key) = (url.to_string(), key.clone());
+
+        let flight = async move {
+            let outcome = transfer(&fetcher, &url, &name, flight_options).await;
+            // Later fetches start afresh instead of joining a finished flight.
+            let mut flights = lock(&registry);
+            if flights
+                .get(&finished_key)
+                .is_some_and(|entry| entry.id == id)
+            {
+                flights.remove(&finished_key);
+            }
+            outcome.map(Arc::new).map_err(|error| {
+                Arc::new(FlightError {
+                    message: error.to_string(),
+                    error: Mutex::new(Some(error)),
+                })
+            })
+        }
+        .boxed()
+        .shared();
+
+        if let Some(weak) = flight.downgrade() {
+            flights.insert(key, InFlight { id, flight: weak });
+        }
+        (flight, true)
+    }
+}
+
+async fn transfer<C: HttpClient>(
+    fetcher: &Fetcher<C>,
+    url: &str,
+    name: &OsString,
+    options: FetchOptions,
+) -> Result<Flight> {
+    let staging = staging_dir(fetcher.workspace_root(), ".pulith-coalesce.")?;
+    let staged = staging.path().join(name);
+    let receipt = fetcher.fetch_with_receipt(url, &staged, options).await?;
+    Ok(Flight {
+        _staging: staging,
+        staged,
+        receipt,
+    })
+}
+
+/// Link `staged` into place at `destination`, replacing what is there.
+fn link_into(staged: &Path, destination: &Path) -> Result<()> {
+    let parent = destination.parent().unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(parent).map_err(|source| pulith_fs::Error::Write {
+        path: parent.to_path_buf(),
+        source,
+    })?;
+    let scratch = tempfile::Builder::new()
+        .prefix(".pulith-coalesce.")
+        .tempdir_in(parent)
+        .map_err(|source| pulith_fs::Error::Write {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    let linked = scratch.path().join("content");
+    pulith_fs::hardlink_or_copy(staged, &linked, pulith_fs::HardlinkOrCopyOptions::new())?;
+    replace_destination_file(&linked, destination)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CancellationToken;
+    use crate::net::response::{HttpResponse, Method, ResponseHead};
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    /// Serves `tool-bytes` after a short delay, 404 for `/missing`, and
+    /// counts GETs.
+    #[derive(Default)]
+    struct SlowClient {
+        gets: AtomicUsize,
+    }
+
+    impl HttpClient for SlowClient {
+        type Error = std::io::Error;
+
+        async fn stream(
+            &self,
+            _url: &str,
+            _headers: &[(String, String)],
+        ) -> std::result::Result<
+            crate::net::http::BoxStream<'static, std::result::Result<bytes::Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            Err(std::io::Error::other("stream is not used"))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn send(
+            &self,
+            method: Method,
+            url: &str,
+            _headers: &[(String, String)],
+        ) -> std::result::Result<HttpResponse<Self::Error>, Self::Error> {
+            if method == Method::Get {
+                self.gets.fetch_add(1, Ordering::SeqCst);
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let (status, body) = if url.ends_with("/missing") {
+                (404, "not found")
+            } else {
+                (200, "tool-bytes")
+            };
+            Ok(HttpResponse {
+                head: ResponseHead::new(status, url),
+                body: Box::pin(futures_util::stream::once(async move {
+                    Ok(bytes::Bytes::from(body))
+                })),
+            })
+        }
+    }
+
+    fn coalescing(workspace: &Path) -> CoalescingFetcher<SlowClient> {
+        CoalescingFetcher::new(Arc::new(Fetcher::new(SlowClient::default(), workspace)))
+    }
+
+    fn gets(fetcher: &CoalescingFetcher<SlowClient>) -> usize {
+        fetcher.fetcher.client.gets.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fetches_share_one_transfer() {
+        let temp = tempfile::tempdir().unwrap();
+        let workspace = temp.path().join("workspace");
+        let fetcher = coalescing(&workspace);
+        let url = "http://mirror.example/tool";
+        let destinations: Vec<_> = (0..3)
+            .map(|i| temp.path().join(format!("out{i}/tool")))
+            .collect();
+
+        let (a, b, c) = tokio::join!(
+            fetcher.fetch_with_receipt(url, &destinations[0], FetchOptions::default()),
+            fetcher.fetch_with_receipt(url, &destinations[1], FetchOptions::default()),
+            fetcher.fetch_with_receipt(url, &destinations[2], FetchOptions::default()),
+        );
+        let receipts = [a.unwrap(), b.unwrap(), c.unwrap()];
+        assert_eq!(gets(&fetcher), 1);
+        assert_eq!(receipts.iter().filter(|r| !r.coalesced).count(), 1);
+        for (receipt, destination) in receipts.iter().zip(&destinations) {
+            assert_eq!(&receipt.destination, destination);
+            assert_eq!(receipt.sha256_hex, receipts[0].sha256_hex);
+            assert_eq!(std::fs::read(destination).unwrap(), b"tool-bytes");
+        }
+        assert_eq!(
+            std::fs::read_dir(workspace.join("staging"))
+                .unwrap()
+                .count(),
+            0
+        );
+
+        // A pinned digest coalesces across URLs; a finished flight is not reused.
+        let checksum: [u8; 32] = hex::decode(receipts[0].sha256_hex.as_ref().unwrap())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let pinned = FetchOptions::default().checksum(Some(checksum));
+        let (a, b) = tokio::join!(
+            fetcher.fetch_with_receipt(url, &destinations[0], pinned.clone()),
+            fetcher.fetch_with_receipt("http://other.example/tool", &destinations[1], pinned),
+        );
+        assert!(a.unwrap().coalesced != b.unwrap().coalesced);
+        assert_eq!(gets(&fetcher), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_caller_leaves_transfer_and_failures_are_shared() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = coalescing(&temp.path().join("workspace"));
+        let url = "http://mirror.example/tool";
+        let token = CancellationToken::new();
+        let (first, second) = (temp.path().join("first"), temp.path().join("second"));
+
+        let starter = fetcher.fetch_with_receipt(
+            url,
+            &first,
+            FetchOptions::default().cancellation(token.clone()),
+        );
+        let joiner = async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            fetcher
+                .fetch_with_receipt(url, &second, FetchOptions::default())
+                .await
+        };
+        let cancel = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            token.cancel();
+        };
+        let (started, joined, ()) = tokio::join!(starter, joiner, cancel);
+        assert!(matches!(started, Err(Error::Cancelled)));
+        assert!(joined.unwrap().coalesced);
+        assert_eq!(std::fs::read(&second).unwrap(), b"tool-bytes");
+        assert!(!first.exists());
+        assert_eq!(gets(&fetcher), 1);
+
+        let missing = "http://mirror.example/missing";
+        let (a, b) = (temp.path().join("a"), temp.path().join("b"));
+        let (started, joined) = tokio::join!(
+            fetcher.fetch_with_receipt(missing, &a, FetchOptions::default()),
+            fetcher.fetch_with_receipt(missing, &b, FetchOptions::default()),
+        );
+        assert!(matches!(started, Err(Error::Http { status: 404, .. })));
+        assert!(matches!(joined, Err(Error::SharedFetchFailed(_))));
+    }
+}
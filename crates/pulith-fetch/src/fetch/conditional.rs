@@ -13,7 +13,7 @@ use crate::fetch::fetcher::Fetcher;
 use crate::net::http::HttpClient;
 
 /// Metadata about a remote file for conditional requests.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RemoteMetadata {
     /// ETag header value if present
     pub etag: Option<String>,
@@ -21,6 +21,8 @@ pub struct RemoteMetadata {
     pub last_modified: Option<String>,
     /// Content-Length header value if present
     pub content_length: Option<u64>,
+    /// Cache-Control header value if present
+    pub cache_control: Option<String>,
 }
 
 /// Conditional download configuration.
@@ -103,19 +105,7 @@ impl<C: HttpClient + 'static> ConditionalFetcher<C> {
 
     /// Get metadata from remote server using HEAD request.
     async fn get_remote_metadata(&self, url: &str) -> Result<RemoteMetadata> {
-        // This would need to be implemented in the HttpClient trait
-        // For now, we'll simulate with a basic implementation
-        let total_bytes = self
-            .base_fetcher
-            .head(url)
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
-
-        Ok(RemoteMetadata {
-            etag: None,          // Would be parsed from HEAD response
-            last_modified: None, // Would be parsed from HEAD response
-            content_length: total_bytes,
-        })
+        self.base_fetcher.head_metadata(url).await
     }
 
     /// Load stored metadata for a URL/destination pair.
@@ -139,6 +129,7 @@ impl<C: HttpClient + 'static> ConditionalFetcher<C> {
             etag: None,
             last_modified: None,
             content_length: content.parse().ok(),
+            cache_control: None,
         }))
     }
 
@@ -322,6 +313,7 @@ mod tests {
             etag: Some("\"abc123\"".to_string()),
             last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
             content_length: Some(1024),
+            cache_control: None,
         };
 
         assert_eq!(metadata.etag, Some("\"abc123\"".to_string()));
@@ -351,16 +343,19 @@ mod tests {
             etag: Some("\"abc123\"".to_string()),
             last_modified: None,
             content_length: None,
+            cache_control: None,
         };
         let remote_same = RemoteMetadata {
             etag: Some("\"abc123\"".to_string()),
             last_modified: None,
             content_length: None,
+            cache_control: None,
         };
         let remote_different = RemoteMetadata {
             etag: Some("\"def456\"".to_string()),
             last_modified: None,
             content_length: None,
+            cache_control: None,
         };
 
         assert!(fetcher.is_content_unchanged(&local, &remote_same));
@@ -371,16 +366,19 @@ mod tests {
             etag: None,
             last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
             content_length: None,
+            cache_control: None,
         };
         let remote_same = RemoteMetadata {
             etag: None,
             last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
             content_length: None,
+            cache_control: None,
         };
         let remote_different = RemoteMetadata {
             etag: None,
             last_modified: Some("Thu, 22 Oct 2015 07:28:00 GMT".to_string()),
             content_length: None,
+            cache_control: None,
         };
 
         assert!(fetcher.is_content_unchanged(&local, &remote_same));
@@ -391,16 +389,19 @@ mod tests {
             etag: None,
             last_modified: None,
             content_length: Some(1024),
+            cache_control: None,
         };
         let remote_same = RemoteMetadata {
             etag: None,
             last_modified: None,
             content_length: Some(1024),
+            cache_control: None,
         };
         let remote_different = RemoteMetadata {
             etag: None,
             last_modified: None,
             content_length: Some(2048),
+            cache_control: None,
         };
 
         assert!(fetcher.is_content_unchanged(&local, &remote_same));
@@ -449,6 +450,7 @@ mod tests {
             etag: Some("\"abc123\"".to_string()),
             last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
             content_length: Some(1024),
+            cache_control: None,
         };
 
         // Store metadata
@@ -478,6 +480,7 @@ mod tests {
             etag: None,
             last_modified: None,
             content_length: Some(1024),
+            cache_control: None,
         };
 
         // Store metadata
@@ -1,16 +1,22 @@
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
+use bytes::Bytes;
 use futures_util::StreamExt;
 use pulith_fs::workflow::Workspace;
+use pulith_resource::{ValidUrl, VerifiedSignature};
 use pulith_verify::{Hasher, Sha256Hasher};
 use serde::{Deserialize, Serialize};
 
 use crate::config::{FetchOptions, FetchPhase};
 use crate::error::{Error, Result};
-use crate::net::http::HttpClient;
+use crate::fetch::conditional::RemoteMetadata;
+use crate::fetch::health::SourceSelection;
+use crate::net::credentials::redact_url;
+use crate::net::http::{BoxStream, HttpClient, ensure_success};
+use crate::net::response::{HttpResponse, Method};
 use crate::progress::PerformanceMetrics;
 use crate::progress::Progress;
-use crate::rate::retry_delay;
 
 /// The main fetcher implementation that handles downloading files with verification.
 pub struct Fetcher<C: HttpClient> {
@@ -22,6 +28,12 @@ pub struct Fetcher<C: HttpClient> {
 pub enum FetchSource {
     Url(String),
     LocalPath(PathBuf),
+    /// A git checkout; `commit` is the full hash `rev` resolved to.
+    Git {
+        url: String,
+        rev: Option<String>,
+        commit: String,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -31,6 +43,34 @@ pub struct FetchReceipt {
     pub bytes_downloaded: u64,
     pub total_bytes: Option<u64>,
     pub sha256_hex: Option<String>,
+    /// Detached signatures verified against the fetched content.
+    #[serde(default)]
+    pub signatures: Vec<VerifiedSignature>,
+    /// How the source was chosen when several candidates were ranked.
+    #[serde(default)]
+    pub selection: Option<SourceSelection>,
+    /// The content was served from a local cache.
+    #[serde(default)]
+    pub cache_hit: bool,
+    /// A stale cache entry was confirmed by the server before being served.
+    #[serde(default)]
+    pub revalidated: bool,
+    /// The content came from a transfer started by another concurrent caller.
+    #[serde(default)]
+    pub coalesced: bool,
+    /// Failed attempts that were retried before this transfer succeeded.
+    #[serde(default)]
+    pub attempts: Vec<FetchAttempt>,
+}
+
+/// A failed transfer attempt that was followed by a retry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchAttempt {
+    pub error: String,
+    /// HTTP status of the failed response, when the server answered.
+    pub status: Option<u16>,
+    /// Time waited before the next attempt.
+    pub delay_ms: u64,
 }
 
 impl<C: HttpClient> Fetcher<C> {
@@ -42,8 +82,13 @@ impl<C: HttpClient> Fetcher<C> {
         }
     }
 
+    /// Root directory used for staging downloads.
+    pub(crate) fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
     /// Get the total bytes from a HEAD request.
-    #[tracing::instrument(skip(self), fields(url = %url))]
+    #[tracing::instrument(skip(self), fields(url = %redact_url(url)))]
     pub async fn head(&self, url: &str) -> Result<Option<u64>> {
         self.client
             .head(url)
@@ -51,53 +96,92 @@ impl<C: HttpClient> Fetcher<C> {
             .map_err(|e| Error::Network(e.to_string()))
     }
 
+    /// Get validators and caching directives from a HEAD request.
+    #[tracing::instrument(skip(self), fields(url = %redact_url(url)))]
+    pub async fn head_metadata(&self, url: &str) -> Result<RemoteMetadata> {
+        self.client
+            .head_metadata(url)
+            .await
+            .map_err(|e| Error::Network(e.to_string()))
+    }
+
     /// Fetch a file from the given URL and return a typed receipt.
     ///
     /// This function downloads the file with progress reporting, verification,
-    /// and atomic placement using pulith-fs workspace.
-    #[tracing::instrument(skip(self, options), fields(url = %url, destination = %destination.display()))]
+    /// and atomic placement using pulith-fs workspace. `file://` URLs are read
+    /// through [`Fetcher::fetch_local_with_receipt`].
+    #[tracing::instrument(skip(self, options), fields(url = %redact_url(url), destination = %destination.display()))]
     pub async fn fetch_with_receipt(
         &self,
         url: &str,
         destination: &Path,
         options: FetchOptions,
     ) -> Result<FetchReceipt> {
-        let mut attempt = 0u32;
-        loop {
-            match self
-                .fetch_with_receipt_attempt(url, destination, &options, attempt)
-                .await
-            {
-                Ok(receipt) => return Ok(receipt),
-                Err(error) => {
-                    if !matches!(error, Error::Network(_) | Error::Timeout(_)) {
-                        return Err(error);
-                    }
-
-                    if attempt >= options.retry_policy.max_retries {
-                        return Err(Error::MaxRetriesExceeded { count: attempt + 1 });
-                    }
-
-                    let delay = retry_delay(attempt, options.retry_policy.base_backoff);
-                    if let Some(provider) = &options.retry_delay_provider {
-                        (provider)(delay).await;
-                    } else {
-                        tokio::time::sleep(delay).await;
-                    }
-                    attempt += 1;
-                }
-            }
+        if let Some(path) = file_url_path(url)? {
+            return self
+                .fetch_local_with_receipt(&path, destination, options)
+                .await;
         }
+
+        self.fetch_remote(url, destination, options)
+            .await
+            .map(|(receipt, _)| receipt)
     }
 
-    #[tracing::instrument(skip(self, options), fields(url = %url, destination = %destination.display(), retry_count = retry_count))]
-    async fn fetch_with_receipt_attempt(
+    /// Download a remote `url` like [`Fetcher::fetch_with_receipt`], also
+    /// returning the validators and caching directives the server sent with
+    /// the content.
+    pub(crate) async fn fetch_remote(
         &self,
         url: &str,
         destination: &Path,
+        options: FetchOptions,
+    ) -> Result<(FetchReceipt, RemoteMetadata)> {
+        let policy = options.retry_policy;
+        let mut attempts = Vec::new();
+        let (mut receipt, remote) = options
+            .interruptible(retry_with(
+                &options,
+                &mut attempts,
+                |error| policy.is_transient(error),
+                |attempt| self.transfer(Origin::Url(url), destination, &options, attempt),
+            ))
+            .await?;
+        receipt.attempts = attempts;
+        Ok((receipt, remote.unwrap_or_default()))
+    }
+
+    /// Fetch a local file through the same pipeline as remote downloads.
+    ///
+    /// The file is hashed, size-checked, verified and placed exactly like a
+    /// download, so the receipt only differs in its [`FetchSource`]. Local
+    /// reads are not retried.
+    #[tracing::instrument(skip(self, options), fields(path = %path.display(), destination = %destination.display()))]
+    pub async fn fetch_local_with_receipt(
+        &self,
+        path: &Path,
+        destination: &Path,
+        options: FetchOptions,
+    ) -> Result<FetchReceipt> {
+        if path.is_dir() {
+            return Err(Error::InvalidState(
+                "local directory candidates are not executable by pulith-fetch".to_string(),
+            ));
+        }
+        options
+            .interruptible(self.transfer(Origin::Local(path), destination, &options, 0))
+            .await
+            .map(|(receipt, _)| receipt)
+    }
+
+    #[tracing::instrument(skip(self, origin, options), fields(destination = %destination.display(), retry_count = retry_count))]
+    async fn transfer(
+        &self,
+        origin: Origin<'_>,
+        destination: &Path,
         options: &FetchOptions,
         retry_count: u32,
-    ) -> Result<FetchReceipt> {
+    ) -> Result<(FetchReceipt, Option<RemoteMetadata>)> {
         let start_time = std::time::Instant::now();
         let mut performance_metrics = PerformanceMetrics::default();
 
@@ -113,11 +197,35 @@ impl<C: HttpClient> Fetcher<C> {
             },
         );
 
-        let total_bytes = options.expected_bytes.or(self
-            .client
-            .head(url)
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?);
+        let mut request_headers = match origin {
+            Origin::Url(url) => options.request_headers(url).await?,
+            Origin::Local(_) => options.headers.to_vec(),
+        };
+        let total_bytes = match origin {
+            _ if options.expected_bytes.is_some() => options.expected_bytes,
+            Origin::Url(url) => {
+                let response = self
+                    .client
+                    .send(Method::Head, url, &request_headers)
+                    .await
+                    .map_err(|e| Error::Network(e.to_string()))?;
+                response
+                    .head
+                    .is_success()
+                    .then(|| response.head.content_length())
+                    .flatten()
+            }
+            Origin::Local(path) => Some(
+                std::fs::metadata(path)
+                    .map_err(|source| {
+                        Error::Fs(pulith_fs::Error::Read {
+                            path: path.to_path_buf(),
+                            source,
+                        })
+                    })?
+                    .len(),
+            ),
+        };
 
         let connecting_duration = connecting_start.elapsed();
         performance_metrics.phase_timings.connecting_ms = connecting_duration.as_millis() as u64;
@@ -134,25 +242,101 @@ impl<C: HttpClient> Fetcher<C> {
             },
         );
 
-        let mut request_headers: Vec<(String, String)> = options.headers.iter().cloned().collect();
         if let Some(offset) = options.resume_offset {
             request_headers.push(("Range".to_string(), format!("bytes={offset}-")));
         }
 
-        let staging_dir = self.workspace_root.join("staging");
-        let dest_dir = destination.parent().unwrap_or_else(|| Path::new("."));
-        let workspace = Workspace::new(&staging_dir, dest_dir)?;
+        let (stream, remote): (BoxStream<'static, Result<Bytes>>, _) = match origin {
+            Origin::Url(url) => {
+                let response = self
+                    .client
+                    .send(Method::Get, url, &request_headers)
+                    .await
+                    .map_err(|e| Error::Network(e.to_string()))?;
+                ensure_success(&response.head)?;
+                let remote = response.head.metadata();
+                (
+                    Box::pin(
+                        response
+                            .body
+                            .map(|chunk| chunk.map_err(|e| Error::Network(e.to_string()))),
+                    ),
+                    Some(remote),
+                )
+            }
+            Origin::Local(path) => (
+                local_stream(path, options.resume_offset.unwrap_or(0)).await?,
+                None,
+            ),
+        };
+        let state = TransferState {
+            start_time,
+            total_bytes,
+            retry_count,
+            performance_metrics,
+        };
+        let receipt = self
+            .write_stream(origin.fetch_source(), stream, destination, options, state)
+            .await?;
+        Ok((receipt, remote))
+    }
+
+    /// Stream an already-open response for `url` through the download
+    /// pipeline, returning the receipt and the response's cache validators.
+    pub(crate) async fn fetch_response(
+        &self,
+        url: &str,
+        response: HttpResponse<C::Error>,
+        destination: &Path,
+        options: &FetchOptions,
+    ) -> Result<(FetchReceipt, RemoteMetadata)> {
+        ensure_success(&response.head)?;
+        let remote = response.head.metadata();
+        let state = TransferState {
+            start_time: Instant::now(),
+            total_bytes: options.expected_bytes.or(remote.content_length),
+            retry_count: 0,
+            performance_metrics: PerformanceMetrics::default(),
+        };
+        let stream = Box::pin(
+            response
+                .body
+                .map(|chunk| chunk.map_err(|e| Error::Network(e.to_string()))),
+        );
+        let receipt = options
+            .interruptible(self.write_stream(
+                Origin::Url(url).fetch_source(),
+                stream,
+                destination,
+                options,
+                state,
+            ))
+            .await?;
+        Ok((receipt, remote))
+    }
+
+    /// Stage `stream`, verify it against `options` and move it to `destination`.
+    async fn write_stream(
+        &self,
+        source: FetchSource,
+        mut stream: BoxStream<'static, Result<Bytes>>,
+        destination: &Path,
+        options: &FetchOptions,
+        state: TransferState,
+    ) -> Result<FetchReceipt> {
+        let TransferState {
+            start_time,
+            total_bytes,
+            retry_count,
+            mut performance_metrics,
+        } = state;
+        let workspace = self.staging_workspace(destination)?;
         let staging_file_path = workspace.path().join(
             destination
                 .file_name()
                 .unwrap_or_else(|| std::ffi::OsStr::new("download")),
         );
 
-        let mut stream = self
-            .client
-            .stream(url, &request_headers)
-            .await
-            .map_err(|e| Error::Network(e.to_string()))?;
         let mut hasher = Sha256Hasher::new();
 
         let downloading_start = std::time::Instant::now();
@@ -176,7 +360,8 @@ impl<C: HttpClient> Fetcher<C> {
             .map_err(|e| Error::Network(e.to_string()))?;
 
         while let Some(chunk_result) = stream.next().await {
-            let chunk = chunk_result.map_err(|e| Error::Network(e.to_string()))?;
+            let chunk = chunk_result?;
+            options.throttle(chunk.len()).await;
             hasher.update(&chunk);
             file.write_all(&chunk)
                 .await
@@ -226,6 +411,14 @@ impl<C: HttpClient> Fetcher<C> {
             },
         );
 
+        if let Some(expected_bytes) = options.expected_bytes
+            && bytes_downloaded != expected_bytes
+        {
+            return Err(Error::InvalidState(format!(
+                "fetched length mismatch: expected {expected_bytes} bytes, got {bytes_downloaded}"
+            )));
+        }
+
         let actual_checksum = hasher.finalize();
         if let Some(expected_checksum) = options.checksum
             && actual_checksum != expected_checksum
@@ -253,9 +446,8 @@ impl<C: HttpClient> Fetcher<C> {
             },
         );
 
-        workspace
-            .commit()
-            .map_err(|e| Error::Network(e.to_string()))?;
+        replace_destination_file(&staging_file_path, destination)?;
+        drop(workspace);
 
         let committing_duration = committing_start.elapsed();
         performance_metrics.phase_timings.committing_ms = committing_duration.as_millis() as u64;
@@ -272,14 +464,43 @@ impl<C: HttpClient> Fetcher<C> {
         );
 
         Ok(FetchReceipt {
-            source: FetchSource::Url(url.to_string()),
+            source,
             destination: destination.to_path_buf(),
             bytes_downloaded,
             total_bytes,
             sha256_hex: Some(hex::encode(actual_checksum)),
+            signatures: Vec::new(),
+            selection: None,
+            cache_hit: false,
+            revalidated: false,
+            coalesced: false,
+            attempts: Vec::new(),
         })
     }
 
+    /// Create a private staging workspace for one transfer into `destination`.
+    fn staging_workspace(&self, destination: &Path) -> Result<Workspace> {
+        let staging_root = self.workspace_root.join("staging");
+        std::fs::create_dir_all(&staging_root).map_err(|source| pulith_fs::Error::Write {
+            path: staging_root.clone(),
+            source,
+        })?;
+        let staging_dir = tempfile::Builder::new()
+            .prefix(".pulith-fetch.")
+            .tempdir_in(&staging_root)
+            .map_err(|source| pulith_fs::Error::Write {
+                path: staging_root.clone(),
+                source,
+            })?
+            .keep();
+        let dest_dir = destination.parent().unwrap_or_else(|| Path::new("."));
+        std::fs::create_dir_all(dest_dir).map_err(|source| pulith_fs::Error::Write {
+            path: dest_dir.to_path_buf(),
+            source,
+        })?;
+        Ok(Workspace::new(staging_dir, dest_dir)?)
+    }
+
     /// Report progress if callback is configured.
     fn report_progress(&self, options: &FetchOptions, progress: Progress) {
         if let Some(ref callback) = options.on_progress {
@@ -288,7 +509,7 @@ impl<C: HttpClient> Fetcher<C> {
     }
 
     /// Try to fetch from a single source with verification.
-    #[tracing::instrument(skip(self, source, options), fields(source = %source.url, destination = %destination.display()))]
+    #[tracing::instrument(skip(self, source, options), fields(source = %redact_url(&source.url), destination = %destination.display()))]
     pub async fn try_source(
         &self,
         source: &crate::DownloadSource,
@@ -305,6 +526,213 @@ impl<C: HttpClient> Fetcher<C> {
     }
 }
 
+/// Where a transfer reads its bytes from.
+/// Progress of a transfer carried from connecting into
+/// [`Fetcher::write_stream`].
+struct TransferState {
+    start_time: Instant,
+    total_bytes: Option<u64>,
+    retry_count: u32,
+    performance_metrics: PerformanceMetrics,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Origin<'a> {
+    Url(&'a str),
+    Local(&'a Path),
+}
+
+impl Origin<'_> {
+    fn fetch_source(self) -> FetchSource {
+        match self {
+            Self::Url(url) => FetchSource::Url(redact_url(url)),
+            Self::Local(path) => FetchSource::LocalPath(path.to_path_buf()),
+        }
+    }
+}
+
+/// Filesystem path named by a `file://` URL, or `None` for other schemes.
+pub(crate) fn file_url_path(url: &str) -> Result<Option<PathBuf>> {
+    let Ok(parsed) = ValidUrl::parse(url) else {
+        return Ok(None);
+    };
+    if parsed.as_url().scheme() != "file" {
+        return Ok(None);
+    }
+    parsed
+        .as_url()
+        .to_file_path()
+        .map(Some)
+        .map_err(|()| Error::InvalidUrl(url.to_string()))
+}
+
+/// Stream a local file in fixed-size chunks starting at `offset`.
+async fn local_stream(path: &Path, offset: u64) -> Result<BoxStream<'static, Result<Bytes>>> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let read_error = |path: &Path, source| {
+        Error::Fs(pulith_fs::Error::Read {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|source| read_error(path, source))?;
+    if offset > 0 {
+        file.seek(std::io::SeekFrom::Start(offset))
+            .await
+            .map_err(|source| read_error(path, source))?;
+    }
+
+    let path = path.to_path_buf();
+    Ok(Box::pin(futures_util::stream::try_unfold(
+        file,
+        move |mut file| {
+            let path = path.clone();
+            async move {
+                let mut buffer = vec![0u8; LOCAL_CHUNK_SIZE];
+                let read = file
+                    .read(&mut buffer)
+                    .await
+                    .map_err(|source| read_error(&path, source))?;
+                if read == 0 {
+                    return Ok(None);
+                }
+                buffer.truncate(read);
+                Ok(Some((Bytes::from(buffer), file)))
+            }
+        },
+    )))
+}
+
+const LOCAL_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Run `transfer` until it succeeds, retrying transient failures under
+/// `options.retry_policy` and recording each retried failure in the receipt.
+pub(crate) async fn retrying<F, Fut>(options: &FetchOptions, transfer: F) -> Result<FetchReceipt>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<FetchReceipt>>,
+{
+    let policy = options.retry_policy;
+    let mut attempts = Vec::new();
+    let mut receipt = retry_with(
+        options,
+        &mut attempts,
+        |error| policy.is_transient(error),
+        transfer,
+    )
+    .await?;
+    receipt.attempts = attempts;
+    Ok(receipt)
+}
+
+/// Run `transfer` until it succeeds or fails with an error `retryable`
+/// rejects, waiting between attempts as `options.retry_policy` prescribes.
+///
+/// Each retried failure is appended to `attempts`. Once retries run out, the
+/// final error and those attempts are returned in
+/// [`Error::MaxRetriesExceeded`].
+pub(crate) async fn retry_with<T, R, F, Fut>(
+    options: &FetchOptions,
+    attempts: &mut Vec<FetchAttempt>,
+    retryable: R,
+    mut transfer: F,
+) -> Result<T>
+where
+    R: Fn(&Error) -> bool,
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let policy = options.retry_policy;
+    let started = Instant::now();
+    let mut attempt = 0u32;
+    loop {
+        let error = match transfer(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        if !retryable(&error) {
+            return Err(error);
+        }
+        if attempt >= policy.max_retries {
+            return Err(Error::MaxRetriesExceeded {
+                count: attempt + 1,
+                last_error: Box::new(error),
+                attempts: attempts.clone(),
+            });
+        }
+
+        let delay = policy.delay_after(attempt, &error);
+        if let Some(budget) = policy.max_elapsed
+            && started.elapsed() + delay > budget
+        {
+            return Err(Error::Timeout(format!(
+                "retry budget of {budget:?} exhausted after {} attempts: {error}",
+                attempt + 1
+            )));
+        }
+
+        tracing::debug!(attempt, ?delay, %error, "retrying transfer");
+        attempts.push(FetchAttempt {
+            status: match &error {
+                Error::Http { status, .. } => Some(*status),
+                _ => None,
+            },
+            error: error.to_string(),
+            delay_ms: u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+        });
+        if let Some(provider) = &options.retry_delay_provider {
+            (provider)(delay).await;
+        } else {
+            tokio::time::sleep(delay).await;
+        }
+        attempt += 1;
+    }
+}
+
+/// Scratch directory under `<workspace>/staging`, removed on drop.
+pub(crate) fn staging_dir(workspace_root: &Path, prefix: &str) -> Result<tempfile::TempDir> {
+    let staging_root = workspace_root.join("staging");
+    std::fs::create_dir_all(&staging_root).map_err(|source| pulith_fs::Error::Write {
+        path: staging_root.clone(),
+        source,
+    })?;
+    Ok(tempfile::Builder::new()
+        .prefix(prefix)
+        .tempdir_in(&staging_root)
+        .map_err(|source| pulith_fs::Error::Write {
+            path: staging_root,
+            source,
+        })?)
+}
+
+/// Atomically move a staged file over `destination`.
+pub(crate) fn replace_destination_file(staged_path: &Path, destination: &Path) -> Result<()> {
+    if let Ok(metadata) = std::fs::symlink_metadata(destination) {
+        if metadata.file_type().is_dir() {
+            return Err(Error::DestinationIsDirectory);
+        }
+
+        std::fs::remove_file(destination).map_err(|source| {
+            Error::Fs(pulith_fs::Error::Write {
+                path: destination.to_path_buf(),
+                source,
+            })
+        })?;
+    }
+
+    std::fs::rename(staged_path, destination).map_err(|source| {
+        Error::Fs(pulith_fs::Error::Write {
+            path: destination.to_path_buf(),
+            source,
+        })
+    })?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -524,6 +952,7 @@ mod tests {
         let options = FetchOptions::default().retry_policy(crate::RetryPolicy {
             max_retries: 1,
             base_backoff: std::time::Duration::from_millis(1),
+            ..crate::RetryPolicy::default()
         });
 
         let error = fetcher
@@ -535,7 +964,7 @@ mod tests {
             .await
             .unwrap_err();
 
-        assert!(matches!(error, Error::MaxRetriesExceeded { count: 2 }));
+        assert!(matches!(error, Error::MaxRetriesExceeded { count: 2, .. }));
         assert_eq!(stream_calls.load(Ordering::SeqCst), 2);
     }
 
@@ -573,6 +1002,7 @@ mod tests {
             .retry_policy(crate::RetryPolicy {
                 max_retries: 2,
                 base_backoff: std::time::Duration::from_millis(1),
+                ..crate::RetryPolicy::default()
             })
             .retry_delay_provider(Arc::new(move |_delay| {
                 let delay_calls_for_provider = Arc::clone(&delay_calls_for_provider);
@@ -590,10 +1020,239 @@ mod tests {
             .await
             .unwrap_err();
 
-        assert!(matches!(error, Error::MaxRetriesExceeded { count: 3 }));
+        let Error::MaxRetriesExceeded {
+            count: 3,
+            last_error,
+            attempts,
+        } = error
+        else {
+            panic!("unexpected error: {error}");
+        };
+        assert!(matches!(*last_error, Error::Network(_)), "{last_error}");
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts.iter().all(|attempt| attempt.status.is_none()));
         assert_eq!(delay_calls.load(Ordering::SeqCst), 2);
     }
 
+    #[tokio::test]
+    async fn fetch_rejects_error_status_without_placing_body() {
+        use crate::net::response::{HttpResponse, ResponseHead};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct StatusHttpClient {
+            requests: AtomicUsize,
+        }
+
+        impl HttpClient for StatusHttpClient {
+            type Error = MockError;
+
+            async fn stream(
+                &self,
+                _url: &str,
+                _headers: &[(String, String)],
+            ) -> std::result::Result<
+                BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+                Self::Error,
+            > {
+                Err(MockError("stream is not used".to_string()))
+            }
+
+            async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+                Ok(None)
+            }
+
+            async fn send(
+                &self,
+                method: Method,
+                url: &str,
+                _headers: &[(String, String)],
+            ) -> std::result::Result<HttpResponse<Self::Error>, Self::Error> {
+                if method == Method::Head {
+                    return Ok(HttpResponse {
+                        head: ResponseHead::new(200, url),
+                        body: Box::pin(futures_util::stream::empty()),
+                    });
+                }
+                self.requests.fetch_add(1, Ordering::SeqCst);
+                Ok(HttpResponse {
+                    head: ResponseHead::new(404, format!("{url}/moved")),
+                    body: Box::pin(futures_util::stream::once(async {
+                        Ok(Bytes::from("not found"))
+                    })),
+                })
+            }
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Fetcher::new(
+            StatusHttpClient {
+                requests: AtomicUsize::new(0),
+            },
+            temp.path(),
+        );
+        let destination = temp.path().join("missing.bin");
+
+        let error = fetcher
+            .fetch_with_receipt(
+                "http://example.com/missing.bin",
+                &destination,
+                FetchOptions::default(),
+            )
+            .await
+            .unwrap_err();
+
+        match error {
+            Error::Http {
+                status, message, ..
+            } => {
+                assert_eq!(status, 404);
+                assert!(message.contains("http://example.com/missing.bin/moved"));
+            }
+            other => panic!("expected HTTP error, got {other:?}"),
+        }
+        assert!(!destination.exists());
+        assert_eq!(fetcher.client.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn fetch_honors_retry_after_and_records_attempts() {
+        use crate::net::response::{HttpResponse, ResponseHead};
+        use std::sync::Mutex;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct FlakyHttpClient {
+            requests: AtomicUsize,
+        }
+
+        impl HttpClient for FlakyHttpClient {
+            type Error = MockError;
+
+            async fn stream(
+                &self,
+                _url: &str,
+                _headers: &[(String, String)],
+            ) -> std::result::Result<
+                BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+                Self::Error,
+            > {
+                Err(MockError("stream is not used".to_string()))
+            }
+
+            async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+                Ok(None)
+            }
+
+            async fn send(
+                &self,
+                method: Method,
+                url: &str,
+                _headers: &[(String, String)],
+            ) -> std::result::Result<HttpResponse<Self::Error>, Self::Error> {
+                if method == Method::Head {
+                    return Ok(HttpResponse {
+                        head: ResponseHead::new(200, url),
+                        body: Box::pin(futures_util::stream::empty()),
+                    });
+                }
+                let head = match self.requests.fetch_add(1, Ordering::SeqCst) {
+                    0 => ResponseHead::new(503, url).with_header("Retry-After", "7"),
+                    1 => ResponseHead::new(429, url),
+                    _ => ResponseHead::new(200, url),
+                };
+                Ok(HttpResponse {
+                    head,
+                    body: Box::pin(futures_util::stream::once(async {
+                        Ok(Bytes::from("payload"))
+                    })),
+                })
+            }
+        }
+
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&delays);
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Fetcher::new(
+            FlakyHttpClient {
+                requests: AtomicUsize::new(0),
+            },
+            temp.path(),
+        );
+        let options = FetchOptions::default()
+            .retry_policy(
+                crate::RetryPolicy::default()
+                    .with_base_backoff(std::time::Duration::from_millis(10))
+                    .with_jitter(std::time::Duration::from_millis(5)),
+            )
+            .retry_delay_provider(Arc::new(move |delay| {
+                recorded.lock().unwrap().push(delay);
+                Box::pin(async {})
+            }));
+
+        let receipt = fetcher
+            .fetch_with_receipt(
+                "http://example.com/flaky.bin",
+                &temp.path().join("flaky.bin"),
+                options,
+            )
+            .await
+            .unwrap();
+
+        let delays = delays.lock().unwrap().clone();
+        assert_eq!(delays[0], std::time::Duration::from_secs(7));
+        assert!(
+            delays[1] >= std::time::Duration::from_millis(20)
+                && delays[1] <= std::time::Duration::from_millis(25)
+        );
+        let statuses: Vec<_> = receipt.attempts.iter().map(|a| a.status).collect();
+        assert_eq!(statuses, vec![Some(503), Some(429)]);
+        assert_eq!(receipt.attempts[0].delay_ms, 7000);
+        assert_eq!(fetcher.client.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn fetch_stops_retrying_when_budget_is_exhausted() {
+        struct AlwaysFailingHttpClient;
+
+        impl HttpClient for AlwaysFailingHttpClient {
+            type Error = MockError;
+
+            async fn stream(
+                &self,
+                _url: &str,
+                _headers: &[(String, String)],
+            ) -> std::result::Result<
+                BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+                Self::Error,
+            > {
+                Err(MockError("connection reset".to_string()))
+            }
+
+            async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+                Ok(None)
+            }
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Fetcher::new(AlwaysFailingHttpClient, temp.path());
+        let options = FetchOptions::default().retry_policy(
+            crate::RetryPolicy::default()
+                .with_max_retries(10)
+                .with_base_backoff(std::time::Duration::from_secs(60))
+                .with_max_elapsed(std::time::Duration::from_secs(1)),
+        );
+
+        let error = fetcher
+            .fetch_with_receipt(
+                "http://example.com/down.bin",
+                &temp.path().join("down.bin"),
+                options,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, Error::Timeout(message) if message.contains("retry budget")));
+    }
+
     #[tokio::test]
     async fn fetch_applies_resume_offset_as_range_header() {
         use std::sync::Mutex;
@@ -633,6 +1292,7 @@ mod tests {
             .retry_policy(crate::RetryPolicy {
                 max_retries: 0,
                 base_backoff: std::time::Duration::from_millis(1),
+                ..crate::RetryPolicy::default()
             })
             .resume_offset(Some(128))
             .expected_bytes(Some(256));
@@ -646,7 +1306,7 @@ mod tests {
             .await
             .unwrap_err();
 
-        assert!(matches!(error, Error::MaxRetriesExceeded { count: 1 }));
+        assert!(matches!(error, Error::MaxRetriesExceeded { count: 1, .. }));
         let headers = seen_headers.lock().unwrap().clone();
         assert!(
             headers
@@ -699,4 +1359,175 @@ mod tests {
         fetcher.report_progress(&options, progress);
         assert!(callback_called.load(std::sync::atomic::Ordering::Relaxed));
     }
+
+    #[tokio::test]
+    async fn test_local_fetch_matches_remote_receipt() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Fetcher::new(MockHttpClient::new(), temp.path().join("workspace"));
+        let source = temp.path().join("offline/test.bin");
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::fs::write(&source, "test data").unwrap();
+        let options = FetchOptions::default().expected_bytes(Some(9));
+
+        let downloads = temp.path().join("downloads");
+        let remote = fetcher
+            .fetch_with_receipt(
+                "https://example.com/test.bin",
+                &downloads.join("remote.bin"),
+                options.clone(),
+            )
+            .await
+            .unwrap();
+
+        let phases = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&phases);
+        let local = fetcher
+            .fetch_local_with_receipt(
+                &source,
+                &downloads.join("local.bin"),
+                options
+                    .clone()
+                    .on_progress(Arc::new(move |progress: &Progress| {
+                        seen.lock().unwrap().push(progress.phase);
+                    })),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(local.source, FetchSource::LocalPath(source.clone()));
+        assert_eq!(local.bytes_downloaded, remote.bytes_downloaded);
+        assert_eq!(local.total_bytes, remote.total_bytes);
+        assert_eq!(local.sha256_hex, remote.sha256_hex);
+        assert_eq!(
+            std::fs::read(downloads.join("local.bin")).unwrap(),
+            b"test data"
+        );
+        assert_eq!(phases.lock().unwrap().last(), Some(&FetchPhase::Completed));
+        assert!(phases.lock().unwrap().contains(&FetchPhase::Verifying));
+        assert_eq!(
+            std::fs::read_dir(temp.path().join("workspace/staging"))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_url_fetch_is_verified_and_replaces_destination() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Fetcher::new(MockHttpClient::with_error(), temp.path().join("workspace"));
+        let source = temp.path().join("mirror.bin");
+        std::fs::write(&source, "offline bytes").unwrap();
+        let destination = temp.path().join("downloads/artifact.bin");
+        std::fs::create_dir_all(destination.parent().unwrap()).unwrap();
+        std::fs::write(&destination, "old").unwrap();
+        std::fs::write(temp.path().join("downloads/sibling"), "kept").unwrap();
+
+        let url = format!("file://{}", source.display());
+        let receipt = fetcher
+            .fetch_with_receipt(&url, &destination, FetchOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(receipt.source, FetchSource::LocalPath(source.clone()));
+        assert_eq!(receipt.total_bytes, Some(13));
+        assert_eq!(std::fs::read(&destination).unwrap(), b"offline bytes");
+        assert!(temp.path().join("downloads/sibling").exists());
+
+        let mismatch = fetcher
+            .fetch_with_receipt(
+                &url,
+                &destination,
+                FetchOptions::default().checksum(Some([0u8; 32])),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(mismatch, Error::ChecksumMismatch { .. }));
+        let short = fetcher
+            .fetch_local_with_receipt(
+                &source,
+                &destination,
+                FetchOptions::default().expected_bytes(Some(64)),
+            )
+            .await
+            .unwrap_err();
+        assert!(short.to_string().contains("expected 64 bytes, got 13"));
+        assert_eq!(std::fs::read(&destination).unwrap(), b"offline bytes");
+
+        assert!(
+            fetcher
+                .fetch_local_with_receipt(temp.path(), &destination, FetchOptions::default())
+                .await
+                .is_err()
+        );
+    }
+
+    /// Sends one chunk, then never finishes the body.
+    struct StallingHttpClient;
+
+    impl HttpClient for StallingHttpClient {
+        type Error = MockError;
+
+        async fn stream(
+            &self,
+            _url: &str,
+            _headers: &[(String, String)],
+        ) -> std::result::Result<
+            BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            let first = futures_util::stream::once(async { Ok(Bytes::from("partial")) });
+            Ok(Box::pin(first.chain(futures_util::stream::pending())))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(Some(1024))
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_cancellation_and_deadline_remove_staging() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Fetcher::new(StallingHttpClient, temp.path().join("workspace"));
+        let destination = temp.path().join("stalled.bin");
+        let staging_is_empty = || {
+            std::fs::read_dir(temp.path().join("workspace/staging"))
+                .unwrap()
+                .next()
+                .is_none()
+        };
+
+        let token = crate::CancellationToken::new();
+        let canceller = tokio::spawn({
+            let token = token.clone();
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                token.cancel();
+            }
+        });
+        let error = fetcher
+            .fetch_with_receipt(
+                "http://example.com/stalled.bin",
+                &destination,
+                FetchOptions::default().cancellation(token),
+            )
+            .await
+            .unwrap_err();
+        canceller.await.unwrap();
+        assert!(matches!(error, Error::Cancelled), "{error}");
+        assert!(!destination.exists());
+        assert!(staging_is_empty());
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(50);
+        let error = fetcher
+            .fetch_with_receipt(
+                "http://example.com/stalled.bin",
+                &destination,
+                FetchOptions::default().deadline(deadline),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::DeadlineExceeded), "{error}");
+        assert!(!destination.exists());
+        assert!(staging_is_empty());
+    }
 }
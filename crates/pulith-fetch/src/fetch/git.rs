@@ -0,0 +1,759 @@
+//! Git source materialization.
+//!
+//! Git candidates are fetched by shelling out to the `git` executable: the
+//! requested revision is fetched into a scratch repository under the
+//! workspace root, checked out detached, and the selected tree (or a single
+//! file when `subpath` names one) is moved to the destination without the
+//! `.git` directory. The resolved commit is recorded in the receipt.
+//!
+//! Cancellation and deadlines from [`FetchOptions`] kill a running `git`
+//! process; a checksum is checked when the selection is a single file.
+
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+use pulith_platform::command::Command;
+use pulith_verify::Sha256Hasher;
+
+use crate::FetchOptions;
+use crate::config::cancellation::CancellationToken;
+use crate::error::{Error, Result};
+use crate::fetch::fetcher::{FetchReceipt, FetchSource};
+use crate::net::credentials::redact_url;
+
+/// Fetches git revisions into the filesystem.
+#[derive(Debug, Clone)]
+pub struct GitFetcher {
+    program: String,
+    workspace_root: PathBuf,
+}
+
+impl GitFetcher {
+    /// Create a git fetcher staging checkouts under `workspace_root`.
+    pub fn new(workspace_root: impl Into<PathBuf>) -> Self {
+        Self {
+            program: "git".to_string(),
+            workspace_root: workspace_root.into(),
+        }
+    }
+
+    /// Use a specific git executable instead of `git` from `PATH`.
+    #[must_use]
+    pub fn with_program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Fetch `rev` (or the remote `HEAD`) of `url` and place `subpath` at `destination`.
+    ///
+    /// A directory subpath (or the whole tree) replaces `destination` as a
+    /// directory; a file subpath replaces it as a file. `options.checksum`
+    /// requires a file subpath.
+    ///
+    /// Git and the filesystem work run on the blocking thread pool.
+    #[tracing::instrument(skip(self, options), fields(url = %redact_url(url)))]
+    pub async fn fetch_with_receipt(
+        &self,
+        url: &str,
+        rev: Option<&str>,
+        subpath: Option<&Path>,
+        destination: &Path,
+        options: FetchOptions,
+    ) -> Result<FetchReceipt> {
+        reject_option_like("url", url)?;
+        if let Some(rev) = rev {
+            reject_option_like("rev", rev)?;
+        }
+        let subpath = subpath.map(validate_subpath).transpose()?;
+
+        // Stops the blocking checkout however this future ends, including
+        // when it is dropped by a race or an interruption.
+        let abort = AbortOnDrop(CancellationToken::new());
+        let checkout = {
+            let fetcher = self.clone();
+            let abort = abort.0.clone();
+            let url = url.to_string();
+            let rev = rev.map(str::to_string);
+            let destination = destination.to_path_buf();
+            let checksum = options.checksum;
+            tokio::task::spawn_blocking(move || {
+                fetcher.materialize(
+                    &url,
+                    rev.as_deref(),
+                    subpath.as_deref(),
+                    &destination,
+                    checksum,
+                    &abort,
+                )
+            })
+        };
+        options
+            .interruptible(async {
+                checkout
+                    .await
+                    .map_err(|error| Error::InvalidState(format!("git checkout failed: {error}")))?
+            })
+            .await
+    }
+
+    /// Blocking body of [`Self::fetch_with_receipt`] for validated inputs.
+    fn materialize(
+        &self,
+        url: &str,
+        rev: Option<&str>,
+        subpath: Option<&Path>,
+        destination: &Path,
+        checksum: Option<[u8; 32]>,
+        abort: &CancellationToken,
+    ) -> Result<FetchReceipt> {
+        std::fs::create_dir_all(&self.workspace_root).map_err(|source| {
+            Error::Fs(pulith_fs::Error::Write {
+                path: self.workspace_root.clone(),
+                source,
+            })
+        })?;
+        let scratch = tempfile::Builder::new()
+            .prefix(".pulith-git.")
+            .tempdir_in(&self.workspace_root)
+            .map_err(|source| {
+                Error::Fs(pulith_fs::Error::Write {
+                    path: self.workspace_root.clone(),
+                    source,
+                })
+            })?;
+        let repo = scratch.path().join("repo");
+
+        self.git(
+            abort,
+            None,
+            [OsStr::new("init"), OsStr::new("-q"), repo.as_os_str()],
+        )?;
+        let commit = self.fetch_revision(abort, &repo, url, rev)?;
+        self.git(
+            abort,
+            Some(&repo),
+            ["checkout", "-q", "--detach", commit.as_str()],
+        )?;
+        std::fs::remove_dir_all(repo.join(".git")).map_err(|source| {
+            Error::Fs(pulith_fs::Error::Write {
+                path: repo.join(".git"),
+                source,
+            })
+        })?;
+
+        let selected = resolve_in_checkout(&repo, subpath, &commit)?;
+        let metadata = std::fs::symlink_metadata(&selected).map_err(|source| {
+            Error::Fs(pulith_fs::Error::Read {
+                path: selected.clone(),
+                source,
+            })
+        })?;
+        let sha256_hex = match checksum {
+            Some(expected) => Some(verify_checksum(&selected, metadata.is_dir(), &expected)?),
+            None => None,
+        };
+        let bytes = tree_size(&selected)?;
+        if abort.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        place(&selected, metadata.is_dir(), destination)?;
+
+        tracing::debug!(%commit, bytes, "materialized git checkout");
+        Ok(FetchReceipt {
+            source: FetchSource::Git {
+                url: redact_url(url),
+                rev: rev.map(str::to_string),
+                commit,
+            },
+            destination: destination.to_path_buf(),
+            bytes_downloaded: bytes,
+            total_bytes: Some(bytes),
+            sha256_hex,
+            signatures: Vec::new(),
+            selection: None,
+            cache_hit: false,
+            revalidated: false,
+            coalesced: false,
+            attempts: Vec::new(),
+        })
+    }
+
+    /// Fetch `rev` into `repo` and return the full commit hash it names.
+    ///
+    /// A shallow fetch covers branches, tags and full hashes on servers that
+    /// allow them; abbreviated hashes fall back to fetching all refs.
+    fn fetch_revision(
+        &self,
+        abort: &CancellationToken,
+        repo: &Path,
+        url: &str,
+        rev: Option<&str>,
+    ) -> Result<String> {
+        let target = rev.unwrap_or("HEAD");
+        let shallow = self.git(
+            abort,
+            Some(repo),
+            [
+                "fetch",
+                "-q",
+                "--depth",
+                "1",
+                "--no-tags",
+                "--",
+                url,
+                target,
+            ],
+        );
+        match shallow {
+            Ok(_) => return self.rev_parse(abort, repo, "FETCH_HEAD^{commit}"),
+            Err(Error::Cancelled) => return Err(Error::Cancelled),
+            Err(_) => {}
+        }
+
+        self.git(
+            abort,
+            Some(repo),
+            [
+                "fetch",
+                "-q",
+                "--tags",
+                "--",
+                url,
+                "+refs/heads/*:refs/remotes/origin/*",
+            ],
+        )?;
+        self.rev_parse(abort, repo, &format!("{target}^{{commit}}"))
+            .map_err(|error| match error {
+                Error::Cancelled => Error::Cancelled,
+                _ => Error::InvalidState(format!("git revision {target} not found in {url}")),
+            })
+    }
+
+    fn rev_parse(&self, abort: &CancellationToken, repo: &Path, rev: &str) -> Result<String> {
+        let output = self.git(abort, Some(repo), ["rev-parse", "--verify", "-q", rev])?;
+        Ok(output.trim().to_string())
+    }
+
+    /// Run git, killing it once `abort` is cancelled.
+    fn git<I, S>(&self, abort: &CancellationToken, repo: Option<&Path>, args: I) -> Result<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut command = Command::new(&self.program)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .args(["-c", "advice.detachedHead=false"]);
+        if let Some(repo) = repo {
+            command = command.arg("-C").arg(repo);
+        }
+        let args = args
+            .into_iter()
+            .map(|arg| arg.as_ref().to_os_string())
+            .collect::<Vec<_>>();
+        let output = command
+            .args(&args)
+            .output_until(|| abort.is_cancelled())
+            .map_err(|error| Error::Network(format!("failed to run git: {error}")))?
+            .ok_or(Error::Cancelled)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Network(format!(
+                "git {} failed: {}",
+                args.first()
+                    .map(|arg| arg.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                stderr.trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Cancels the wrapped token when dropped.
+struct AbortOnDrop(CancellationToken);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Check the selected file against `expected` and return its SHA-256 in hex.
+fn verify_checksum(selected: &Path, is_dir: bool, expected: &[u8; 32]) -> Result<String> {
+    if is_dir {
+        return Err(Error::VerificationFailed(
+            "a checksum can only be checked against a single git file".to_string(),
+        ));
+    }
+    let file = std::fs::File::open(selected).map_err(|source| {
+        Error::Fs(pulith_fs::Error::Read {
+            path: selected.to_path_buf(),
+            source,
+        })
+    })?;
+    let receipt = pulith_verify::verify_stream(file, Sha256Hasher::new(), expected, None)?;
+    Ok(hex::encode(receipt.actual_digest))
+}
+
+/// Reject arguments git would parse as options, such as `--upload-pack=<cmd>`.
+fn reject_option_like(what: &str, value: &str) -> Result<()> {
+    if value.is_empty() || value.starts_with('-') {
+        return Err(Error::InvalidState(format!("Invalid git {what}: {value}")));
+    }
+    Ok(())
+}
+
+/// Reject subpaths that would escape the checkout.
+fn validate_subpath(subpath: &Path) -> Result<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in subpath.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(Error::InvalidState(format!(
+                    "Invalid git subpath: {}",
+                    subpath.display()
+                )));
+            }
+        }
+    }
+    if normalized.starts_with(".git") {
+        return Err(Error::InvalidState(format!(
+            "Invalid git subpath: {}",
+            subpath.display()
+        )));
+    }
+    Ok(normalized)
+}
+
+/// Resolve `subpath` inside the checkout at `repo`.
+///
+/// Symlinks committed to the repository are followed, but the result must
+/// stay within the checkout so a link cannot hand host files to `place`.
+fn resolve_in_checkout(repo: &Path, subpath: Option<&Path>, commit: &str) -> Result<PathBuf> {
+    let subpath = subpath.unwrap_or(Path::new("."));
+    let missing = || {
+        Error::InvalidState(format!(
+            "git subpath {} does not exist at {commit}",
+            subpath.display()
+        ))
+    };
+    let root = repo.canonicalize().map_err(|source| {
+        Error::Fs(pulith_fs::Error::Read {
+            path: repo.to_path_buf(),
+            source,
+        })
+    })?;
+    let resolved = repo.join(subpath).canonicalize().map_err(|_| missing())?;
+    if !resolved.starts_with(&root) {
+        return Err(Error::InvalidState(format!(
+            "git subpath {} resolves outside the checkout",
+            subpath.display()
+        )));
+    }
+    Ok(resolved)
+}
+
+fn tree_size(path: &Path) -> Result<u64> {
+    let metadata = std::fs::symlink_metadata(path).map_err(|source| {
+        Error::Fs(pulith_fs::Error::Read {
+            path: path.to_path_buf(),
+            source,
+        })
+    })?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut total = 0;
+    let entries = std::fs::read_dir(path).map_err(|source| {
+        Error::Fs(pulith_fs::Error::Read {
+            path: path.to_path_buf(),
+            source,
+        })
+    })?;
+    for entry in entries {
+        total += tree_size(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+/// Move the selected checkout entry to `destination`, replacing what is there.
+///
+/// The entry is first moved (or, across filesystems, copied) into a staging
+/// directory beside `destination`, then swapped in. The previous content is
+/// only discarded once the new content is in place, and is restored if the
+/// swap fails.
+pub(crate) fn place(selected: &Path, is_dir: bool, destination: &Path) -> Result<()> {
+    let parent = destination.parent().unwrap_or_else(|| Path::new("."));
+    let write_error = |path: &Path, source| {
+        Error::Fs(pulith_fs::Error::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+    std::fs::create_dir_all(parent).map_err(|source| write_error(parent, source))?;
+
+    let swap = tempfile::Builder::new()
+        .prefix(".pulith-place.")
+        .tempdir_in(parent)
+        .map_err(|source| write_error(parent, source))?;
+    let incoming = swap.path().join("new");
+    match std::fs::rename(selected, &incoming) {
+        Ok(()) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::CrossesDevices => {
+            if is_dir {
+                pulith_fs::copy_dir_all(selected, &incoming)?;
+            } else {
+                std::fs::copy(selected, &incoming)
+                    .map_err(|source| write_error(&incoming, source))?;
+            }
+        }
+        Err(source) => return Err(write_error(&incoming, source)),
+    }
+
+    let backup = swap.path().join("old");
+    let replaced = std::fs::symlink_metadata(destination).is_ok();
+    if replaced {
+        std::fs::rename(destination, &backup).map_err(|source| write_error(destination, source))?;
+    }
+    let swapped = if is_dir {
+        pulith_fs::replace_dir(&incoming, destination, Default::default()).map_err(Error::from)
+    } else {
+        std::fs::rename(&incoming, destination).map_err(|source| write_error(destination, source))
+    };
+    if let Err(error) = swapped {
+        if replaced && let Err(restore) = std::fs::rename(&backup, destination) {
+            tracing::warn!(
+                destination = %destination.display(),
+                backup = %backup.display(),
+                %restore,
+                "failed to restore the previous destination"
+            );
+            // Keep the previous content on disk for manual recovery.
+            let _ = swap.keep();
+        }
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args([
+                "-c",
+                "user.name=pulith",
+                "-c",
+                "user.email=pulith@example.com",
+            ])
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .capture()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+    }
+
+    /// Create a repository with two commits and return it with the first commit.
+    fn upstream(dir: &Path) -> String {
+        std::fs::create_dir_all(dir.join("tools")).unwrap();
+        run(dir, &["init", "-q", "-b", "main"]);
+        std::fs::write(dir.join("tools/run.sh"), "echo v1\n").unwrap();
+        std::fs::write(dir.join("README"), "readme\n").unwrap();
+        run(dir, &["add", "."]);
+        run(dir, &["commit", "-q", "-m", "v1"]);
+        run(dir, &["tag", "v1"]);
+        let first = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["rev-parse", "HEAD"])
+            .capture()
+            .unwrap();
+        std::fs::write(dir.join("tools/run.sh"), "echo v2\n").unwrap();
+        run(dir, &["commit", "-q", "-am", "v2"]);
+        String::from_utf8(first.stdout).unwrap().trim().to_string()
+    }
+
+    fn file_url(path: &Path) -> String {
+        format!("file://{}", path.display())
+    }
+
+    #[test]
+    fn test_validate_subpath_rejects_escapes() {
+        assert_eq!(
+            validate_subpath(Path::new("./tools/bin")).unwrap(),
+            PathBuf::from("tools/bin")
+        );
+        assert!(validate_subpath(Path::new("../outside")).is_err());
+        assert!(validate_subpath(Path::new("/etc")).is_err());
+        assert!(validate_subpath(Path::new(".git/config")).is_err());
+    }
+
+    #[test]
+    fn test_place_swaps_destination_and_keeps_it_on_failure() {
+        let temp = tempfile::tempdir().unwrap();
+        let destination = temp.path().join("out/tree");
+        std::fs::create_dir_all(&destination).unwrap();
+        std::fs::write(destination.join("stale"), "old").unwrap();
+
+        let error = place(&temp.path().join("missing"), true, &destination).unwrap_err();
+        assert!(matches!(error, Error::Fs(_)), "{error}");
+        assert_eq!(
+            std::fs::read_to_string(destination.join("stale")).unwrap(),
+            "old"
+        );
+
+        let selected = temp.path().join("selected");
+        std::fs::create_dir_all(&selected).unwrap();
+        std::fs::write(selected.join("fresh"), "new").unwrap();
+        place(&selected, true, &destination).unwrap();
+        assert!(!destination.join("stale").exists());
+        assert_eq!(
+            std::fs::read_to_string(destination.join("fresh")).unwrap(),
+            "new"
+        );
+        let leftovers = std::fs::read_dir(temp.path().join("out")).unwrap().count();
+        assert_eq!(leftovers, 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlinked_subpath_cannot_leave_checkout() {
+        let temp = tempfile::tempdir().unwrap();
+        let outside = temp.path().join("host");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret"), "host file").unwrap();
+
+        let upstream_dir = temp.path().join("upstream");
+        upstream(&upstream_dir);
+        std::os::unix::fs::symlink(&outside, upstream_dir.join("escape")).unwrap();
+        std::os::unix::fs::symlink("tools", upstream_dir.join("alias")).unwrap();
+        run(&upstream_dir, &["add", "."]);
+        run(&upstream_dir, &["commit", "-q", "-m", "links"]);
+
+        let fetcher = GitFetcher::new(temp.path().join("workspace"));
+        let url = file_url(&upstream_dir);
+        for subpath in ["escape", "escape/secret"] {
+            let error = fetcher
+                .fetch_with_receipt(
+                    &url,
+                    None,
+                    Some(Path::new(subpath)),
+                    &temp.path().join("out"),
+                    FetchOptions::default(),
+                )
+                .await
+                .unwrap_err();
+            assert!(
+                error.to_string().contains("outside the checkout"),
+                "{error}"
+            );
+        }
+        assert_eq!(
+            std::fs::read_to_string(outside.join("secret")).unwrap(),
+            "host file"
+        );
+
+        // Links that stay inside the checkout are placed as their target.
+        let destination = temp.path().join("alias");
+        fetcher
+            .fetch_with_receipt(
+                &url,
+                None,
+                Some(Path::new("alias")),
+                &destination,
+                FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(destination.join("run.sh")).unwrap(),
+            "echo v2\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_option_like_url_and_rev_are_rejected() {
+        let temp = tempfile::tempdir().unwrap();
+        let marker = temp.path().join("pwned");
+        let fetcher = GitFetcher::new(temp.path().join("workspace"));
+        let injected = format!("--upload-pack=touch {}", marker.display());
+
+        for (url, rev) in [
+            (injected.as_str(), None),
+            ("file:///nonexistent", Some(injected.as_str())),
+        ] {
+            let error = fetcher
+                .fetch_with_receipt(
+                    url,
+                    rev,
+                    None,
+                    &temp.path().join("out"),
+                    FetchOptions::default(),
+                )
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("Invalid git"), "{error}");
+        }
+        assert!(!marker.exists());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tag_and_subpath_from_file_url() {
+        let temp = tempfile::tempdir().unwrap();
+        let first = upstream(&temp.path().join("upstream"));
+        let fetcher = GitFetcher::new(temp.path().join("workspace"));
+        let url = file_url(&temp.path().join("upstream"));
+
+        let destination = temp.path().join("out/tools");
+        let receipt = fetcher
+            .fetch_with_receipt(
+                &url,
+                Some("v1"),
+                Some(Path::new("tools")),
+                &destination,
+                FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(destination.join("run.sh")).unwrap(),
+            "echo v1\n"
+        );
+        assert!(!destination.join(".git").exists());
+        assert_eq!(receipt.bytes_downloaded, 8);
+        assert_eq!(
+            receipt.source,
+            FetchSource::Git {
+                url: url.clone(),
+                rev: Some("v1".to_string()),
+                commit: first.clone(),
+            }
+        );
+
+        let file = temp.path().join("out/run.sh");
+        fetcher
+            .fetch_with_receipt(
+                &url,
+                Some(&first[..10]),
+                Some(Path::new("tools/run.sh")),
+                &file,
+                FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "echo v1\n");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_checks_checksum_and_honors_cancellation() {
+        let temp = tempfile::tempdir().unwrap();
+        upstream(&temp.path().join("upstream"));
+        let fetcher = GitFetcher::new(temp.path().join("workspace"));
+        let url = file_url(&temp.path().join("upstream"));
+        let file = temp.path().join("run.sh");
+        let checksum = |content: &[u8]| {
+            let mut hasher = Sha256Hasher::new();
+            pulith_verify::Hasher::update(&mut hasher, content);
+            <[u8; 32]>::try_from(pulith_verify::Hasher::finalize(hasher)).unwrap()
+        };
+
+        let receipt = fetcher
+            .fetch_with_receipt(
+                &url,
+                None,
+                Some(Path::new("tools/run.sh")),
+                &file,
+                FetchOptions::default().checksum(Some(checksum(b"echo v2\n"))),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            receipt.sha256_hex,
+            Some(hex::encode(checksum(b"echo v2\n")))
+        );
+
+        let mismatch = fetcher
+            .fetch_with_receipt(
+                &url,
+                Some("v1"),
+                Some(Path::new("tools/run.sh")),
+                &file,
+                FetchOptions::default().checksum(Some(checksum(b"echo v2\n"))),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(mismatch, Error::ChecksumMismatch { .. }),
+            "{mismatch}"
+        );
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "echo v2\n");
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = fetcher
+            .fetch_with_receipt(
+                &url,
+                None,
+                None,
+                &temp.path().join("tree"),
+                FetchOptions::default().cancellation(token),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(cancelled, Error::Cancelled));
+        assert!(!temp.path().join("tree").exists());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_head_replaces_destination_tree() {
+        let temp = tempfile::tempdir().unwrap();
+        upstream(&temp.path().join("upstream"));
+        let fetcher = GitFetcher::new(temp.path().join("workspace"));
+        let url = file_url(&temp.path().join("upstream"));
+        let destination = temp.path().join("checkout");
+        std::fs::create_dir_all(&destination).unwrap();
+        std::fs::write(destination.join("stale"), "old").unwrap();
+
+        let receipt = fetcher
+            .fetch_with_receipt(&url, None, None, &destination, FetchOptions::default())
+            .await
+            .unwrap();
+        assert!(!destination.join("stale").exists());
+        assert_eq!(
+            std::fs::read_to_string(destination.join("tools/run.sh")).unwrap(),
+            "echo v2\n"
+        );
+        assert!(matches!(receipt.source, FetchSource::Git { commit, .. } if commit.len() == 40));
+
+        let missing = fetcher
+            .fetch_with_receipt(
+                &url,
+                Some("no-such-branch"),
+                None,
+                &destination,
+                FetchOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(missing.to_string().contains("no-such-branch"));
+        let no_subpath = fetcher
+            .fetch_with_receipt(
+                &url,
+                None,
+                Some(Path::new("missing")),
+                &destination,
+                FetchOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(no_subpath.to_string().contains("missing"));
+    }
+}
@@ -0,0 +1,380 @@
+//! Mirror health tracking and latency probing for source selection.
+//!
+//! [`MirrorHealth`] keeps per-host success, failure and throughput
+//! statistics, optionally persisted as a small JSON file so that later runs
+//! start from what earlier runs observed. [`rank_candidates`] combines those
+//! statistics with fresh probe latencies into a try order, and the result is
+//! reported as a [`SourceSelection`] on the fetch receipt. Races report their
+//! losing candidates the same way.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use pulith_fs::atomic_write;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Default period during which a host that failed is tried last.
+pub const DEFAULT_FAILURE_COOLDOWN: Duration = Duration::from_secs(15 * 60);
+
+/// Weight of the newest sample in the moving averages.
+const SMOOTHING: f64 = 0.3;
+
+/// Transfer size used to turn throughput into an expected duration.
+const REFERENCE_TRANSFER_BYTES: f64 = 1024.0 * 1024.0;
+
+/// Observed behaviour of one host.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostHealth {
+    pub successes: u64,
+    pub failures: u64,
+    /// Unix seconds of the most recent successful transfer.
+    #[serde(default)]
+    pub last_success: Option<u64>,
+    /// Unix seconds of the most recent failed transfer or probe.
+    #[serde(default)]
+    pub last_failure: Option<u64>,
+    /// Smoothed probe latency in milliseconds.
+    #[serde(default)]
+    pub latency_ms: Option<f64>,
+    /// Smoothed transfer throughput in bytes per second.
+    #[serde(default)]
+    pub throughput_bps: Option<f64>,
+}
+
+impl HostHealth {
+    /// Whether the last failure is newer than the last success and within `cooldown`.
+    pub fn recently_failed(&self, now: u64, cooldown: Duration) -> bool {
+        match self.last_failure {
+            Some(failed) => {
+                self.last_success.is_none_or(|succeeded| failed > succeeded)
+                    && now.saturating_sub(failed) < cooldown.as_secs()
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HealthFile {
+    #[serde(default)]
+    hosts: BTreeMap<String, HostHealth>,
+}
+
+/// Per-host health statistics, optionally backed by a file.
+#[derive(Debug)]
+pub struct MirrorHealth {
+    path: Option<PathBuf>,
+    cooldown: Duration,
+    hosts: Mutex<BTreeMap<String, HostHealth>>,
+}
+
+impl Default for MirrorHealth {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl MirrorHealth {
+    /// Track health for this process only.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            cooldown: DEFAULT_FAILURE_COOLDOWN,
+            hosts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Load health statistics from `path`, starting empty if it is missing or unreadable.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let hosts = match std::fs::read(&path) {
+            Ok(data) => match serde_json::from_slice::<HealthFile>(&data) {
+                Ok(file) => file.hosts,
+                Err(error) => {
+                    tracing::warn!(path = %path.display(), %error, "ignoring corrupt mirror health file");
+                    BTreeMap::new()
+                }
+            },
+            Err(_) => BTreeMap::new(),
+        };
+        Self {
+            path: Some(path),
+            cooldown: DEFAULT_FAILURE_COOLDOWN,
+            hosts: Mutex::new(hosts),
+        }
+    }
+
+    /// Demote hosts for `cooldown` after a failure.
+    #[must_use]
+    pub fn with_failure_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn failure_cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// Statistics recorded for the host serving `url`.
+    pub fn host(&self, url: &str) -> Option<HostHealth> {
+        self.lock().get(&host_key(url)).cloned()
+    }
+
+    pub fn record_probe(&self, url: &str, latency: Duration) {
+        let mut hosts = self.lock();
+        let entry = hosts.entry(host_key(url)).or_default();
+        entry.latency_ms = Some(smooth(entry.latency_ms, latency.as_secs_f64() * 1000.0));
+    }
+
+    pub fn record_success(&self, url: &str, bytes: u64, elapsed: Duration) {
+        let mut hosts = self.lock();
+        let entry = hosts.entry(host_key(url)).or_default();
+        entry.successes += 1;
+        entry.last_success = Some(unix_now());
+        if bytes > 0 && elapsed > Duration::ZERO {
+            entry.throughput_bps = Some(smooth(
+                entry.throughput_bps,
+                bytes as f64 / elapsed.as_secs_f64(),
+            ));
+        }
+    }
+
+    pub fn record_failure(&self, url: &str) {
+        let mut hosts = self.lock();
+        let entry = hosts.entry(host_key(url)).or_default();
+        entry.failures += 1;
+        entry.last_failure = Some(unix_now());
+    }
+
+    /// Write the statistics back to the health file, if one is configured.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = HealthFile {
+            hosts: self.lock().clone(),
+        };
+        let data = serde_json::to_vec_pretty(&file)
+            .map_err(|e| Error::InvalidState(format!("Invalid mirror health: {e}")))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|source| pulith_fs::Error::Write {
+                path: parent.to_path_buf(),
+                source,
+            })?;
+        }
+        atomic_write(path, &data, Default::default())?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, HostHealth>> {
+        self.hosts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Outcome of probing one candidate before selection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceProbe {
+    pub url: String,
+    /// Probe round trip in milliseconds, if the probe succeeded.
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+    /// Smoothed throughput of the host from earlier transfers, in bytes per second.
+    pub throughput_bps: Option<u64>,
+    /// The host failed recently and was moved behind healthy candidates.
+    pub demoted: bool,
+    /// The candidate is in the preferred region.
+    #[serde(default)]
+    pub in_region: bool,
+}
+
+impl SourceProbe {
+    /// Expected cost of a reference transfer in milliseconds.
+    fn cost_ms(&self) -> f64 {
+        let latency = self.latency_ms.unwrap_or(u64::MAX) as f64;
+        match self.throughput_bps {
+            Some(bps) if bps > 0 => latency + REFERENCE_TRANSFER_BYTES / bps as f64 * 1000.0,
+            _ => latency,
+        }
+    }
+}
+
+/// Why a source was chosen, recorded on the receipt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceSelection {
+    /// Strategy that produced the ranking, e.g. `fastest-first`.
+    pub strategy: String,
+    /// URL of the candidate that served the transfer.
+    pub chosen: String,
+    /// Probes in the order candidates were tried.
+    pub probes: Vec<SourceProbe>,
+    /// Candidates that raced the chosen one and did not win.
+    #[serde(default)]
+    pub losers: Vec<LostSource>,
+}
+
+/// A raced candidate whose download was not used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LostSource {
+    pub source: String,
+    pub reason: LossReason,
+}
+
+/// Why a raced candidate lost.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LossReason {
+    /// The candidate failed before another one finished.
+    Failed { error: String },
+    /// Another candidate finished first and this download was cancelled.
+    Cancelled,
+}
+
+/// Order in which to try probed candidates: reachable probes, then hosts
+/// without a recent failure, then region matches, then lowest expected cost.
+/// Ties keep input order.
+pub fn rank_candidates(probes: &[SourceProbe]) -> Vec<usize> {
+    let mut order = (0..probes.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&probes[a], &probes[b]);
+        a.latency_ms
+            .is_none()
+            .cmp(&b.latency_ms.is_none())
+            .then(a.demoted.cmp(&b.demoted))
+            .then(b.in_region.cmp(&a.in_region))
+            .then(a.cost_ms().total_cmp(&b.cost_ms()))
+    });
+    order
+}
+
+/// Key statistics by `host[:port]`, falling back to the whole URL.
+pub(crate) fn host_key(url: &str) -> String {
+    pulith_resource::ValidUrl::parse(url)
+        .ok()
+        .and_then(|url| {
+            let parsed = url.as_url();
+            parsed.host_str().map(|host| match parsed.port() {
+                Some(port) => format!("{host}:{port}"),
+                None => host.to_string(),
+            })
+        })
+        .unwrap_or_else(|| url.to_string())
+}
+
+fn smooth(previous: Option<f64>, sample: f64) -> f64 {
+    match previous {
+        Some(previous) => previous + SMOOTHING * (sample - previous),
+        None => sample,
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(url: &str, latency_ms: Option<u64>) -> SourceProbe {
+        SourceProbe {
+            url: url.to_string(),
+            latency_ms,
+            error: None,
+            throughput_bps: None,
+            demoted: false,
+            in_region: false,
+        }
+    }
+
+    #[test]
+    fn test_rank_prefers_health_then_region_and_speed() {
+        let mut probes = vec![
+            probe("https://down.example.com/a", None),
+            SourceProbe {
+                demoted: true,
+                ..probe("https://flaky.example.com/a", Some(5))
+            },
+            probe("https://slow.example.com/a", Some(200)),
+            SourceProbe {
+                throughput_bps: Some(100_000_000),
+                ..probe("https://fast.example.com/a", Some(40))
+            },
+            SourceProbe {
+                throughput_bps: Some(10_000),
+                ..probe("https://thin.example.com/a", Some(10))
+            },
+        ];
+        let order = rank_candidates(&probes)
+            .into_iter()
+            .map(|index| probes[index].url.split('/').nth(2).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            [
+                "fast.example.com",
+                "slow.example.com",
+                "thin.example.com",
+                "flaky.example.com",
+                "down.example.com"
+            ]
+        );
+
+        // Region only breaks ties among healthy candidates.
+        probes[0].in_region = true;
+        probes[1].in_region = true;
+        probes[2].in_region = true;
+        let order = rank_candidates(&probes);
+        assert_eq!(order[0], 2);
+        assert_eq!(&order[3..], [1, 0]);
+    }
+
+    #[test]
+    fn test_health_round_trips_and_demotes_recent_failures() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("state/mirror-health.json");
+        let health = MirrorHealth::load(&path);
+        health.record_probe("https://a.example.com/x", Duration::from_millis(100));
+        health.record_probe("https://a.example.com/y", Duration::from_millis(200));
+        health.record_success("https://a.example.com/x", 2_000, Duration::from_secs(1));
+        health.record_failure("http://b.example.com:8080/x");
+        health.save().unwrap();
+
+        let reloaded = MirrorHealth::load(&path);
+        let a = reloaded.host("https://a.example.com/other").unwrap();
+        assert_eq!(a.successes, 1);
+        assert!((a.latency_ms.unwrap() - 130.0).abs() < 1e-6);
+        assert_eq!(a.throughput_bps, Some(2_000.0));
+        let b = reloaded.host("http://b.example.com:8080/").unwrap();
+        assert!(b.recently_failed(unix_now(), reloaded.failure_cooldown()));
+        assert!(!b.recently_failed(unix_now() + 3_600, reloaded.failure_cooldown()));
+        assert!(reloaded.host("http://b.example.com/").is_none());
+
+        reloaded.record_success("http://b.example.com:8080/x", 0, Duration::ZERO);
+        assert!(
+            !reloaded
+                .host("http://b.example.com:8080/")
+                .unwrap()
+                .recently_failed(unix_now(), DEFAULT_FAILURE_COOLDOWN)
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(
+            MirrorHealth::load(&path)
+                .host("https://a.example.com/")
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,584 @@
+//! Digest resolution from upstream checksum manifests.
+//!
+//! A [`ChecksumManifestSource`] names a manifest URL and, optionally, a
+//! detached signature over it. The manifest signature is checked before any
+//! digest is read from it, so a tampered manifest cannot vouch for a
+//! tampered artifact.
+
+use pulith_resource::{
+    DigestAlgorithm, Metadata, MinisignKey, SignatureScheme, ValidDigest, VerifiedSignature,
+};
+use pulith_source::{ChecksumManifestSource, ManifestSignature};
+use sha2::{Digest as _, Sha256};
+
+use crate::FetchOptions;
+use crate::codec::manifest::ChecksumManifest;
+use crate::codec::signature::{MinisignMode, MinisignSignature, OpenPgpVerifier, SigstoreVerifier};
+use crate::error::{Error, Result};
+use crate::fetch::fetcher::{Fetcher, retry_with};
+use crate::net::credentials::redact_url;
+use crate::net::http::{HttpClient, read_body_limited};
+
+/// Largest checksum manifest, manifest signature or version index read into
+/// memory.
+const MAX_MANIFEST_BYTES: usize = 16 * 1024 * 1024;
+
+/// A digest read from a checksum manifest, with what vouched for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDigest {
+    pub digest: ValidDigest,
+    /// The verified manifest signature, when the source requires one.
+    pub signature: Option<VerifiedSignature>,
+    /// Facts about the signer for `TrustPolicy::evaluate`, such as the
+    /// Sigstore certificate identity and issuer.
+    pub metadata: Metadata,
+}
+
+/// Keys available for checking checksum manifest signatures.
+#[derive(Debug, Clone, Default)]
+pub struct ManifestTrust {
+    openpgp: Option<OpenPgpVerifier>,
+    minisign_keys: Vec<(MinisignKey, MinisignMode)>,
+    sigstore: Option<SigstoreVerifier>,
+}
+
+impl ManifestTrust {
+    /// Create an empty trust set; only unsigned manifests can be used.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify OpenPGP manifest signatures with the given verifier.
+    #[must_use]
+    pub fn with_openpgp(mut self, verifier: OpenPgpVerifier) -> Self {
+        self.openpgp = Some(verifier);
+        self
+    }
+
+    /// Add a minisign key for manifest signatures, which must carry a
+    /// trusted comment.
+    #[must_use]
+    pub fn with_minisign_key(mut self, key: MinisignKey) -> Self {
+        self.minisign_keys.push((key, MinisignMode::Minisign));
+        self
+    }
+
+    /// Add a signify key for manifest signatures.
+    #[must_use]
+    pub fn with_signify_key(mut self, key: MinisignKey) -> Self {
+        self.minisign_keys.push((key, MinisignMode::Signify));
+        self
+    }
+
+    /// Verify Sigstore bundles over manifests with the given verifier.
+    #[must_use]
+    pub fn with_sigstore(mut self, verifier: SigstoreVerifier) -> Self {
+        self.sigstore = Some(verifier);
+        self
+    }
+
+    fn verify(
+        &self,
+        data: &[u8],
+        signature: &[u8],
+        scheme: SignatureScheme,
+    ) -> Result<(VerifiedSignature, Metadata)> {
+        let verified = match scheme {
+            SignatureScheme::OpenPgp => {
+                let verifier = self.openpgp.as_ref().ok_or_else(|| {
+                    Error::InvalidState(
+                        "No OpenPGP keyring configured for manifest signatures".to_string(),
+                    )
+                })?;
+                verifier
+                    .verify_detached(data, signature)?
+                    .map(|verification| (verification.verified_signature(), Metadata::new()))
+            }
+            SignatureScheme::Minisign => {
+                let text = std::str::from_utf8(signature).map_err(|e| {
+                    Error::InvalidState(format!("Invalid minisign signature: {}", e))
+                })?;
+                let signature = MinisignSignature::parse(text)?;
+                let (key, mode) = self
+                    .minisign_keys
+                    .iter()
+                    .find(|(key, _)| key.key_id() == signature.key_id())
+                    .ok_or_else(|| {
+                        Error::InvalidState(format!(
+                            "Minisign key {} is not trusted for manifest signatures",
+                            signature.key_id()
+                        ))
+                    })?;
+                signature
+                    .verified(key, *mode, data)?
+                    .map(|signature| (signature, Metadata::new()))
+            }
+            SignatureScheme::Sigstore => {
+                let verifier = self.sigstore.as_ref().ok_or_else(|| {
+                    Error::InvalidState(
+                        "No Sigstore trust root configured for manifest signatures".to_string(),
+                    )
+                })?;
+                verifier
+                    .verify_detached(data, signature)?
+                    .map(|verification| {
+                        (verification.verified_signature(), verification.metadata())
+                    })
+            }
+        };
+
+        verified.ok_or_else(|| {
+            Error::InvalidState("Checksum manifest signature does not match".to_string())
+        })
+    }
+}
+
+impl<C: HttpClient> Fetcher<C> {
+    /// Download a checksum manifest and return the digest it lists for `file_name`.
+    ///
+    /// When the manifest source carries a signature, the signature is
+    /// downloaded and must verify with a key from `trust` that satisfies the
+    /// source's [`pulith_resource::SignatureRequirement`]. The returned digest
+    /// is suitable for `VerificationRequirement::Digest`.
+    ///
+    /// Both downloads use the headers, credentials, retry policy,
+    /// cancellation and deadline of `options`, and are capped at 16 MiB.
+    pub async fn fetch_manifest_digest(
+        &self,
+        manifest: &ChecksumManifestSource,
+        file_name: &str,
+        trust: &ManifestTrust,
+        options: &FetchOptions,
+    ) -> Result<ValidDigest> {
+        self.fetch_verified_manifest_digest(manifest, file_name, trust, options)
+            .await
+            .map(|resolved| resolved.digest)
+    }
+
+    /// Like [`Fetcher::fetch_manifest_digest`], also returning the manifest
+    /// signature and the signer facts it established.
+    #[tracing::instrument(skip(self, manifest, trust, options), fields(url = %redact_url(manifest.url.as_url().as_str())))]
+    pub async fn fetch_verified_manifest_digest(
+        &self,
+        manifest: &ChecksumManifestSource,
+        file_name: &str,
+        trust: &ManifestTrust,
+        options: &FetchOptions,
+    ) -> Result<ManifestDigest> {
+        options
+            .interruptible(self.resolve_manifest_digest(manifest, file_name, trust, options))
+            .await
+    }
+
+    async fn resolve_manifest_digest(
+        &self,
+        manifest: &ChecksumManifestSource,
+        file_name: &str,
+        trust: &ManifestTrust,
+        options: &FetchOptions,
+    ) -> Result<ManifestDigest> {
+        let data = read_document(&self.client, manifest.url.as_url().as_str(), options).await?;
+
+        let (signature, metadata) = match &manifest.signature {
+            Some(ManifestSignature { url, requirement }) => {
+                let signature = read_document(&self.client, url.as_url().as_str(), options).await?;
+                let (verified, metadata) = trust.verify(&data, &signature, requirement.scheme)?;
+                let content = ValidDigest {
+                    algorithm: DigestAlgorithm::Sha256,
+                    bytes: Sha256::digest(&data).to_vec(),
+                };
+                requirement
+                    .validate(std::slice::from_ref(&verified), &content)
+                    .map_err(|e| {
+                        Error::InvalidState(format!("Checksum manifest signature: {}", e))
+                    })?;
+                (Some(verified), metadata)
+            }
+            None => (None, Metadata::new()),
+        };
+
+        let text = std::str::from_utf8(&data)
+            .map_err(|e| Error::InvalidState(format!("Checksum manifest is not UTF-8: {}", e)))?;
+        Ok(ManifestDigest {
+            digest: ChecksumManifest::parse(text)?.digest_for(file_name, &manifest.algorithm)?,
+            signature,
+            metadata,
+        })
+    }
+}
+
+/// Read a small document such as a manifest, signature or version index with
+/// the request headers and credentials of `options`, retrying transient
+/// failures and refusing bodies over 16 MiB.
+pub(crate) async fn read_document<C: HttpClient>(
+    client: &C,
+    url: &str,
+    options: &FetchOptions,
+) -> Result<Vec<u8>> {
+    let headers = options.request_headers(url).await?;
+    let policy = options.retry_policy;
+    retry_with(
+        options,
+        &mut Vec::new(),
+        |error| policy.is_transient(error),
+        |_| read_body_limited(client, url, &headers, MAX_MANIFEST_BYTES),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::http::BoxStream;
+    use bytes::Bytes;
+    use pulith_resource::{
+        DigestAlgorithm, SIGSTORE_ISSUER_METADATA_KEY, SignatureRequirement, ValidUrl,
+        sigstore_key_id,
+    };
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Debug)]
+    struct MockError(String);
+
+    impl std::fmt::Display for MockError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::error::Error for MockError {}
+
+    #[derive(Default)]
+    struct MockHttpClient {
+        files: HashMap<String, Vec<u8>>,
+        /// Requests to fail before serving files.
+        failures: AtomicU32,
+        headers: Mutex<Vec<Vec<(String, String)>>>,
+    }
+
+    impl HttpClient for MockHttpClient {
+        type Error = MockError;
+
+        async fn stream(
+            &self,
+            url: &str,
+            headers: &[(String, String)],
+        ) -> std::result::Result<
+            BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            self.headers.lock().unwrap().push(headers.to_vec());
+            if self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                    left.checked_sub(1)
+                })
+                .is_ok()
+            {
+                return Err(MockError("connection reset".to_string()));
+            }
+            let data = self
+                .files
+                .get(url)
+                .cloned()
+                .ok_or_else(|| MockError(format!("404 {url}")))?;
+            Ok(Box::pin(futures_util::stream::iter(vec![Ok(Bytes::from(
+                data,
+            ))])))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+    }
+
+    const MANIFEST_URL: &str = "https://example.com/releases/SHA256SUMS";
+    const SIGNATURE_URL: &str = "https://example.com/releases/SHA256SUMS.asc";
+    const PAYLOAD_DIGEST: &str = "21bc4a1ca40f952bc87096281da050b581b79781c83c100f74ef37f35da9183e";
+
+    fn fixture(name: &str) -> Vec<u8> {
+        std::fs::read(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures/signature")
+                .join(name),
+        )
+        .unwrap()
+    }
+
+    fn fetcher(manifest: Vec<u8>, signature: Vec<u8>) -> Fetcher<MockHttpClient> {
+        let files = HashMap::from([
+            (MANIFEST_URL.to_string(), manifest),
+            (SIGNATURE_URL.to_string(), signature),
+        ]);
+        Fetcher::new(
+            MockHttpClient {
+                files,
+                ..MockHttpClient::default()
+            },
+            std::env::temp_dir(),
+        )
+    }
+
+    fn manifest_source(fingerprint: &str) -> ChecksumManifestSource {
+        ChecksumManifestSource::new(
+            ValidUrl::parse(MANIFEST_URL).unwrap(),
+            DigestAlgorithm::Sha256,
+        )
+        .with_signature(
+            ValidUrl::parse(SIGNATURE_URL).unwrap(),
+            SignatureRequirement::new(SignatureScheme::OpenPgp, [fingerprint]).unwrap(),
+        )
+    }
+
+    fn openpgp_trust() -> ManifestTrust {
+        ManifestTrust::new().with_openpgp(
+            OpenPgpVerifier::from_keyring_dir(
+                Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/fixtures/signature/openpgp/keyring"),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_manifest_resolves_digest() {
+        let fetcher = fetcher(fixture("openpgp/SHA256SUMS"), Vec::new());
+        let source = ChecksumManifestSource::new(
+            ValidUrl::parse(MANIFEST_URL).unwrap(),
+            DigestAlgorithm::Sha256,
+        );
+
+        let digest = fetcher
+            .fetch_manifest_digest(
+                &source,
+                "payload.txt",
+                &ManifestTrust::new(),
+                &FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(digest.hex(), PAYLOAD_DIGEST);
+    }
+
+    #[tokio::test]
+    async fn test_manifest_requests_use_fetch_options() {
+        let fetcher = fetcher(fixture("openpgp/SHA256SUMS"), Vec::new());
+        fetcher.client.failures.store(1, Ordering::SeqCst);
+        let source = ChecksumManifestSource::new(
+            ValidUrl::parse(MANIFEST_URL).unwrap(),
+            DigestAlgorithm::Sha256,
+        );
+        let options = FetchOptions::default()
+            .header("Authorization", "Bearer private")
+            .retry_policy(
+                crate::RetryPolicy::default()
+                    .with_base_backoff(std::time::Duration::from_millis(1)),
+            );
+
+        let digest = fetcher
+            .fetch_manifest_digest(&source, "payload.txt", &ManifestTrust::new(), &options)
+            .await
+            .unwrap();
+        assert_eq!(digest.hex(), PAYLOAD_DIGEST);
+        {
+            let headers = fetcher.client.headers.lock().unwrap();
+            assert_eq!(headers.len(), 2);
+            assert!(headers.iter().all(|sent| {
+                sent.contains(&("Authorization".to_string(), "Bearer private".to_string()))
+            }));
+        }
+
+        let token = crate::CancellationToken::new();
+        token.cancel();
+        let cancelled = fetcher
+            .fetch_manifest_digest(
+                &source,
+                "payload.txt",
+                &ManifestTrust::new(),
+                &FetchOptions::default().cancellation(token),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(cancelled, Error::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_signed_manifest_requires_valid_signature() {
+        let source = manifest_source("30B65883B216EDD81528A31ADEF6B49AA2474D3E");
+
+        let signed = fetcher(
+            fixture("openpgp/SHA256SUMS"),
+            fixture("openpgp/SHA256SUMS.asc"),
+        );
+        let digest = signed
+            .fetch_manifest_digest(
+                &source,
+                "payload.txt",
+                &openpgp_trust(),
+                &FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(digest.hex(), PAYLOAD_DIGEST);
+
+        let mut tampered_manifest = fixture("openpgp/SHA256SUMS");
+        tampered_manifest[0] = b'0';
+        let tampered = fetcher(tampered_manifest, fixture("openpgp/SHA256SUMS.asc"));
+        assert!(
+            tampered
+                .fetch_manifest_digest(
+                    &source,
+                    "payload.txt",
+                    &openpgp_trust(),
+                    &FetchOptions::default()
+                )
+                .await
+                .is_err()
+        );
+
+        assert!(
+            signed
+                .fetch_manifest_digest(
+                    &source,
+                    "payload.txt",
+                    &ManifestTrust::new(),
+                    &FetchOptions::default()
+                )
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_manifest_rejects_key_outside_requirement() {
+        let fetcher = fetcher(
+            fixture("openpgp/SHA256SUMS"),
+            fixture("openpgp/SHA256SUMS.asc"),
+        );
+        let source = manifest_source("2DD047E987179833A326928FE865DFBD45F9DB2D");
+
+        let error = fetcher
+            .fetch_manifest_digest(
+                &source,
+                "payload.txt",
+                &openpgp_trust(),
+                &FetchOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not from an allowed key"));
+    }
+
+    #[tokio::test]
+    async fn test_sigstore_manifest_signature_is_bound_to_issuer() {
+        let identity =
+            "https://github.com/example/tool/.github/workflows/release.yml@refs/tags/v1.0.0";
+        let issuer = "https://token.actions.githubusercontent.com";
+        let trust = ManifestTrust::new().with_sigstore(
+            SigstoreVerifier::from_trust_root_file(
+                Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("tests/fixtures/signature/sigstore/trusted_root.json"),
+            )
+            .unwrap(),
+        );
+        let manifest = fixture("sigstore/artifact.txt");
+        let bundle = fixture("sigstore/artifact.txt.sigstore.json");
+
+        let (verified, metadata) = trust
+            .verify(&manifest, &bundle, SignatureScheme::Sigstore)
+            .unwrap();
+        assert_eq!(verified.key_id, sigstore_key_id(issuer, identity));
+        assert_eq!(
+            metadata
+                .get(SIGSTORE_ISSUER_METADATA_KEY)
+                .map(String::as_str),
+            Some(issuer)
+        );
+
+        let fetcher = fetcher(manifest, bundle);
+        let source = |issuer: &str| {
+            ChecksumManifestSource::new(
+                ValidUrl::parse(MANIFEST_URL).unwrap(),
+                DigestAlgorithm::Sha256,
+            )
+            .with_signature(
+                ValidUrl::parse(SIGNATURE_URL).unwrap(),
+                SignatureRequirement::new(
+                    SignatureScheme::Sigstore,
+                    [sigstore_key_id(issuer, identity)],
+                )
+                .unwrap(),
+            )
+        };
+        let error = fetcher
+            .fetch_manifest_digest(
+                &source("https://accounts.example.com"),
+                "payload.txt",
+                &trust,
+                &FetchOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not from an allowed key"));
+    }
+
+    #[tokio::test]
+    async fn test_minisign_signed_manifest() {
+        use base64::{Engine as _, engine::general_purpose::STANDARD};
+        use ed25519_dalek::Signer as _;
+
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[5u8; 32]);
+        let key_id = [8, 7, 6, 5, 4, 3, 2, 1];
+        let manifest = fixture("openpgp/SHA256SUMS");
+
+        let mut public_key = b"Ed".to_vec();
+        public_key.extend_from_slice(&key_id);
+        public_key.extend_from_slice(signing_key.verifying_key().as_bytes());
+        let key = MinisignKey::parse(STANDARD.encode(public_key)).unwrap();
+
+        let mut signature = b"Ed".to_vec();
+        signature.extend_from_slice(&key_id);
+        signature.extend_from_slice(&signing_key.sign(&manifest).to_bytes());
+        let signature = format!(
+            "untrusted comment: signify\n{}\n",
+            STANDARD.encode(signature)
+        );
+
+        let fetcher = fetcher(manifest, signature.into_bytes());
+        let source = ChecksumManifestSource::new(
+            ValidUrl::parse(MANIFEST_URL).unwrap(),
+            DigestAlgorithm::Sha256,
+        )
+        .with_signature(
+            ValidUrl::parse(SIGNATURE_URL).unwrap(),
+            SignatureRequirement::new(SignatureScheme::Minisign, [key.key_id()]).unwrap(),
+        );
+
+        let digest = fetcher
+            .fetch_manifest_digest(
+                &source,
+                "payload.txt",
+                &ManifestTrust::new().with_signify_key(key.clone()),
+                &FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(digest.hex(), PAYLOAD_DIGEST);
+
+        // A minisign key wants the trusted comment this file lacks.
+        assert!(
+            fetcher
+                .fetch_manifest_digest(
+                    &source,
+                    "payload.txt",
+                    &ManifestTrust::new().with_minisign_key(key),
+                    &FetchOptions::default(),
+                )
+                .await
+                .is_err()
+        );
+    }
+}
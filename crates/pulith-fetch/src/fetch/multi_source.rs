@@ -3,51 +3,110 @@
 //! This module provides the ability to download from multiple sources
 //! with different strategies for source selection and fallback.
 
+use futures_util::future::join_all;
 use futures_util::stream::{FuturesUnordered, StreamExt};
 use pulith_resource::{RequestedResource, ResolvedResource};
 use pulith_source::{PlannedSources, ResolvedSourceCandidate, SelectionStrategy, SourceSpec};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::config::{DownloadSource, MultiSourceOptions, SourceSelectionStrategy};
 use crate::error::{Error, Result};
-use crate::fetch::fetcher::{FetchReceipt, FetchSource, Fetcher};
+use crate::fetch::fetcher::{FetchReceipt, Fetcher, replace_destination_file};
+use crate::fetch::git::{GitFetcher, place};
+use crate::fetch::health::{
+    LossReason, LostSource, MirrorHealth, SourceProbe, SourceSelection, rank_candidates,
+};
+use crate::fetch::oci::{OciFetcher, registry_url};
+use crate::fetch::s3::S3Fetcher;
 use crate::net::http::HttpClient;
+use crate::net::sigv4::S3Credentials;
+
+/// Probe timeout when `MultiSourceOptions::per_source_timeout` is unset.
+const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Multi-source fetcher implementation.
 pub struct MultiSourceFetcher<C: HttpClient> {
     fetcher: Arc<Fetcher<C>>,
+    health: Arc<MirrorHealth>,
+    region: Option<String>,
+    s3_credentials: Option<S3Credentials>,
 }
 
 impl<C: HttpClient + 'static> MultiSourceFetcher<C> {
     /// Create a new multi-source fetcher.
     pub fn new(fetcher: Arc<Fetcher<C>>) -> Self {
-        Self { fetcher }
+        Self {
+            fetcher,
+            health: Arc::new(MirrorHealth::in_memory()),
+            region: None,
+            s3_credentials: None,
+        }
+    }
+
+    /// Use `health` to rank mirrors and record transfer outcomes.
+    #[must_use]
+    pub fn with_health(mut self, health: MirrorHealth) -> Self {
+        self.health = Arc::new(health);
+        self
+    }
+
+    /// Prefer sources in `region` for geographic selection.
+    #[must_use]
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// Sign requests to S3 sources with `credentials`; without them S3
+    /// objects are requested unsigned.
+    #[must_use]
+    pub fn with_s3_credentials(mut self, credentials: S3Credentials) -> Self {
+        self.s3_credentials = Some(credentials);
+        self
+    }
+
+    /// Mirror health statistics gathered so far.
+    pub fn health(&self) -> &MirrorHealth {
+        &self.health
     }
 
     /// Fetch from multiple sources using the specified strategy.
+    ///
+    /// Every source is fetched with `fetch_options`, except that each
+    /// source's own checksum replaces `fetch_options.checksum`.
     pub async fn fetch_multi_source_with_receipt(
         &self,
         sources: Vec<DownloadSource>,
         destination: &Path,
         options: MultiSourceOptions,
+        fetch_options: &crate::FetchOptions,
     ) -> Result<FetchReceipt> {
         if sources.is_empty() {
             return Err(Error::InvalidState("No sources provided".into()));
         }
+        if options.verify_consistency {
+            return self
+                .fetch_consistent(sources, destination, fetch_options)
+                .await;
+        }
 
         match options.strategy {
             SourceSelectionStrategy::Priority => {
-                self.fetch_priority(sources, destination, options).await
+                self.fetch_priority(sources, destination, fetch_options)
+                    .await
             }
             SourceSelectionStrategy::RaceAll => {
-                self.fetch_race(sources, destination, options).await
+                self.fetch_race(sources, destination, fetch_options).await
             }
             SourceSelectionStrategy::FastestFirst => {
-                self.fetch_fastest(sources, destination, options).await
+                self.fetch_fastest(sources, destination, options, fetch_options)
+                    .await
             }
             SourceSelectionStrategy::Geographic => {
-                self.fetch_geographic(sources, destination, options).await
+                self.fetch_geographic(sources, destination, options, fetch_options)
+                    .await
             }
         }
     }
@@ -57,13 +116,10 @@ impl<C: HttpClient + 'static> MultiSourceFetcher<C> {
         &self,
         mut sources: Vec<DownloadSource>,
         destination: &Path,
-        _options: MultiSourceOptions,
+        fetch_options: &crate::FetchOptions,
     ) -> Result<FetchReceipt> {
         for source in sources.drain(..) {
-            match self
-                .try_source(&source, destination, &crate::FetchOptions::default())
-                .await
-            {
+            match self.try_source(&source, destination, fetch_options).await {
                 Ok(path) => return Ok(path),
                 Err(_) => continue,
             }
@@ -71,57 +127,263 @@ impl<C: HttpClient + 'static> MultiSourceFetcher<C> {
         Err(Error::Network("All sources failed".to_string()))
     }
 
-    /// Try all sources in parallel and use the first successful one.
+    /// Download from all sources at once and keep the first verified result.
     async fn fetch_race(
         &self,
         sources: Vec<DownloadSource>,
         destination: &Path,
-        _options: MultiSourceOptions,
+        fetch_options: &crate::FetchOptions,
     ) -> Result<FetchReceipt> {
-        let mut futures = FuturesUnordered::new();
-
-        for source in sources {
-            let fetcher = self.fetcher.clone();
-            let dest = destination.to_path_buf();
-            let future = async move {
-                fetcher
-                    .fetch_with_receipt(&source.url, &dest, crate::FetchOptions::default())
-                    .await
-            };
-            futures.push(Box::pin(future));
+        let arena = self.race_arena()?;
+        let labels = sources.iter().map(|source| source.url.clone()).collect();
+        let futures = sources
+            .into_iter()
+            .enumerate()
+            .map(|(index, source)| {
+                let staged = arena.path().join(index.to_string());
+                async move {
+                    let result = self.try_source(&source, &staged, fetch_options).await;
+                    (index, staged, result)
+                }
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        self.finish_race(arena, labels, futures, destination).await
+    }
+
+    /// Download every source and accept the content only if they all agree.
+    ///
+    /// Sources that fail to download are skipped, but at least two (or the
+    /// only one given) must succeed. The digest and size served by most
+    /// sources is the reference, and the first source that differs from it
+    /// is reported; otherwise the highest priority download is placed.
+    async fn fetch_consistent(
+        &self,
+        mut sources: Vec<DownloadSource>,
+        destination: &Path,
+        fetch_options: &crate::FetchOptions,
+    ) -> Result<FetchReceipt> {
+        sources.sort_by_key(|source| source.priority);
+        let arena = self.race_arena()?;
+        let results = join_all(sources.iter().enumerate().map(|(index, source)| {
+            let staged = arena.path().join(index.to_string());
+            async move {
+                let result = self.try_source(source, &staged, fetch_options).await;
+                (staged, result)
+            }
+        }))
+        .await;
+
+        let mut fetched = Vec::new();
+        let mut last_error = None;
+        for (source, (staged, result)) in sources.iter().zip(results) {
+            // Without a digest, mirrors could only be compared by size.
+            let result = result.and_then(|receipt| match &receipt.sha256_hex {
+                Some(_) => Ok(receipt),
+                None => Err(Error::VerificationFailed(format!(
+                    "{} returned no digest to compare",
+                    source.url
+                ))),
+            });
+            match result {
+                Ok(receipt) => fetched.push((source, staged, receipt)),
+                Err(error) => {
+                    tracing::warn!(url = %source.url, %error, "mirror unavailable for consistency check");
+                    last_error = Some(error);
+                }
+            }
+        }
+        if fetched.len() < sources.len().min(2) {
+            return Err(last_error.unwrap_or_else(|| {
+                Error::Network("Not enough sources to verify consistency".to_string())
+            }));
         }
 
-        while let Some(result) = futures.next().await {
-            if let Ok(path) = result {
-                return Ok(path);
+        let fingerprint = |receipt: &FetchReceipt| {
+            (
+                receipt.sha256_hex.clone().unwrap_or_default(),
+                receipt.bytes_downloaded,
+            )
+        };
+        let mut reference = fingerprint(&fetched[0].2);
+        let mut best = 0;
+        for (_, _, receipt) in &fetched {
+            let candidate = fingerprint(receipt);
+            let count = fetched
+                .iter()
+                .filter(|(_, _, other)| fingerprint(other) == candidate)
+                .count();
+            if count > best {
+                best = count;
+                reference = candidate;
             }
         }
+        // Only a strict majority can tell which mirror is the odd one out.
+        if best * 2 <= fetched.len() {
+            return Err(Error::NoMirrorMajority {
+                mirrors: fetched.len(),
+            });
+        }
+        if let Some((source, _, receipt)) = fetched
+            .iter()
+            .find(|(_, _, receipt)| fingerprint(receipt) != reference)
+        {
+            let (actual, actual_bytes) = fingerprint(receipt);
+            return Err(Error::InconsistentMirror {
+                mirror: source.url.clone(),
+                expected: reference.0,
+                actual,
+                expected_bytes: reference.1,
+                actual_bytes,
+            });
+        }
 
-        Err(Error::Network("All sources failed".to_string()))
+        let (_, staged, mut receipt) = fetched.swap_remove(0);
+        replace_destination_file(&staged, destination)?;
+        drop(arena);
+        receipt.destination = destination.to_path_buf();
+        Ok(receipt)
     }
 
-    /// Try the fastest responding source first.
+    /// Probe all sources and try them from the fastest healthy one.
     async fn fetch_fastest(
         &self,
         sources: Vec<DownloadSource>,
         destination: &Path,
-        _options: MultiSourceOptions,
+        options: MultiSourceOptions,
+        fetch_options: &crate::FetchOptions,
     ) -> Result<FetchReceipt> {
-        // For now, just use priority order
-        // In a real implementation, we would measure response times
-        self.fetch_priority(sources, destination, _options).await
+        self.fetch_ranked(
+            "fastest-first",
+            sources,
+            None,
+            destination,
+            &options,
+            fetch_options,
+        )
+        .await
     }
 
-    /// Try geographically closest source first.
+    /// Probe all sources and try those in the configured region first.
     async fn fetch_geographic(
         &self,
         sources: Vec<DownloadSource>,
         destination: &Path,
-        _options: MultiSourceOptions,
+        options: MultiSourceOptions,
+        fetch_options: &crate::FetchOptions,
+    ) -> Result<FetchReceipt> {
+        let region = self.region.as_deref();
+        self.fetch_ranked(
+            "geographic",
+            sources,
+            region,
+            destination,
+            &options,
+            fetch_options,
+        )
+        .await
+    }
+
+    /// Rank sources by probes and health, then try them in that order.
+    ///
+    /// Every attempt updates the mirror health, which is saved before
+    /// returning; the ranking is attached to the receipt.
+    async fn fetch_ranked(
+        &self,
+        strategy: &str,
+        mut sources: Vec<DownloadSource>,
+        region: Option<&str>,
+        destination: &Path,
+        options: &MultiSourceOptions,
+        fetch_options: &crate::FetchOptions,
     ) -> Result<FetchReceipt> {
-        // For now, just use priority order
-        // In a real implementation, we would use geographic information
-        self.fetch_priority(sources, destination, _options).await
+        sources.sort_by_key(|source| source.priority);
+        let timeout = options.per_source_timeout.unwrap_or(DEFAULT_PROBE_TIMEOUT);
+        let probes = join_all(sources.iter().map(|source| {
+            let in_region = region.is_some() && source.region.as_deref() == region;
+            self.probe(&source.url, in_region, timeout)
+        }))
+        .await;
+        let order = rank_candidates(&probes);
+        let ranked = order
+            .iter()
+            .map(|&index| probes[index].clone())
+            .collect::<Vec<_>>();
+        tracing::debug!(strategy, ?ranked, "ranked download sources");
+
+        let mut last_error = None;
+        for index in order {
+            let source = &sources[index];
+            let started = Instant::now();
+            match self.try_source(source, destination, fetch_options).await {
+                Ok(mut receipt) => {
+                    self.health.record_success(
+                        &source.url,
+                        receipt.bytes_downloaded,
+                        started.elapsed(),
+                    );
+                    self.save_health();
+                    receipt.selection = Some(SourceSelection {
+                        strategy: strategy.to_string(),
+                        chosen: source.url.clone(),
+                        probes: ranked,
+                        losers: Vec::new(),
+                    });
+                    return Ok(receipt);
+                }
+                Err(error) => {
+                    tracing::debug!(url = %source.url, %error, "source failed");
+                    self.health.record_failure(&source.url);
+                    last_error = Some(error);
+                }
+            }
+        }
+
+        self.save_health();
+        Err(last_error.unwrap_or_else(|| Error::Network("All sources failed".to_string())))
+    }
+
+    /// Time a HEAD request to `url`, recording the outcome in the mirror health.
+    async fn probe(&self, url: &str, in_region: bool, timeout: Duration) -> SourceProbe {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let history = self.health.host(url).unwrap_or_default();
+        let demoted = history.recently_failed(now, self.health.failure_cooldown());
+
+        let started = Instant::now();
+        let (latency_ms, error) = match tokio::time::timeout(timeout, self.fetcher.head(url)).await
+        {
+            Ok(Ok(_)) => {
+                let elapsed = started.elapsed();
+                self.health.record_probe(url, elapsed);
+                (Some(elapsed.as_millis() as u64), None)
+            }
+            Ok(Err(error)) => {
+                self.health.record_failure(url);
+                (None, Some(error.to_string()))
+            }
+            Err(_) => {
+                self.health.record_failure(url);
+                (None, Some(format!("probe timed out after {timeout:?}")))
+            }
+        };
+
+        SourceProbe {
+            url: url.to_string(),
+            latency_ms,
+            error,
+            throughput_bps: history.throughput_bps.map(|bps| bps as u64),
+            demoted,
+            in_region,
+        }
+    }
+
+    fn save_health(&self) {
+        if let Err(error) = self.health.save() {
+            tracing::warn!(%error, "failed to save mirror health");
+        }
     }
 
     /// Try to fetch from a single source.
@@ -232,37 +494,103 @@ impl<C: HttpClient + 'static> MultiSourceFetcher<C> {
         destination: &Path,
         options: &crate::FetchOptions,
     ) -> Result<FetchReceipt> {
-        let mut futures = FuturesUnordered::new();
-
-        for candidate in candidates.iter().cloned() {
-            let fetcher = self.fetcher.clone();
-            let dest = destination.to_path_buf();
-            let options = options.clone();
-            futures.push(Box::pin(async move {
-                match candidate {
-                    ResolvedSourceCandidate::Url(url) => {
-                        fetcher
-                            .fetch_with_receipt(url.as_url().as_ref(), &dest, options)
-                            .await
-                    }
-                    ResolvedSourceCandidate::LocalPath(path) => copy_local_candidate(&path, &dest),
-                    ResolvedSourceCandidate::Git { .. } => Err(Error::InvalidState(
-                        "git candidates are not executable by pulith-fetch yet".to_string(),
-                    )),
+        let arena = self.race_arena()?;
+        let labels = candidates.iter().map(candidate_label).collect();
+        let futures = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| {
+                let staged = arena.path().join(index.to_string());
+                async move {
+                    let result = self.try_candidate(candidate, &staged, options).await;
+                    (index, staged, result)
                 }
-            }));
-        }
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        self.finish_race(arena, labels, futures, destination).await
+    }
+
+    /// Create the directory raced downloads stage into, removed when dropped.
+    fn race_arena(&self) -> Result<tempfile::TempDir> {
+        let staging_root = self.fetcher.workspace_root().join("staging");
+        std::fs::create_dir_all(&staging_root).map_err(|source| pulith_fs::Error::Write {
+            path: staging_root.clone(),
+            source,
+        })?;
+        Ok(tempfile::Builder::new()
+            .prefix(".pulith-race.")
+            .tempdir_in(&staging_root)
+            .map_err(|source| pulith_fs::Error::Write {
+                path: staging_root,
+                source,
+            })?)
+    }
 
+    /// Wait for the first contender to succeed and place its staged result.
+    ///
+    /// Contenders still running are cancelled by dropping their futures, and
+    /// the arena with every partial download is removed afterwards.
+    async fn finish_race<F>(
+        &self,
+        arena: tempfile::TempDir,
+        labels: Vec<String>,
+        mut futures: FuturesUnordered<F>,
+        destination: &Path,
+    ) -> Result<FetchReceipt>
+    where
+        F: Future<Output = (usize, PathBuf, Result<FetchReceipt>)>,
+    {
+        let mut outcomes: Vec<Option<LossReason>> = vec![None; labels.len()];
+        let mut winner = None;
         let mut last_error = None;
-        while let Some(result) = futures.next().await {
+        while let Some((index, staged, result)) = futures.next().await {
             match result {
-                Ok(path) => return Ok(path),
-                Err(error) => last_error = Some(error),
+                Ok(receipt) => {
+                    winner = Some((index, staged, receipt));
+                    break;
+                }
+                Err(error) => {
+                    tracing::debug!(source = %labels[index], %error, "race contender failed");
+                    outcomes[index] = Some(LossReason::Failed {
+                        error: error.to_string(),
+                    });
+                    last_error = Some(error);
+                }
             }
         }
+        drop(futures);
 
-        Err(last_error
-            .unwrap_or_else(|| Error::Network("All planned candidates failed".to_string())))
+        let Some((index, staged, mut receipt)) = winner else {
+            return Err(
+                last_error.unwrap_or_else(|| Error::Network("All sources failed".to_string()))
+            );
+        };
+        let is_dir = std::fs::symlink_metadata(&staged).is_ok_and(|meta| meta.is_dir());
+        if is_dir {
+            place(&staged, true, destination)?;
+        } else {
+            replace_destination_file(&staged, destination)?;
+        }
+        drop(arena);
+
+        receipt.destination = destination.to_path_buf();
+        receipt.selection = Some(SourceSelection {
+            strategy: "race".to_string(),
+            chosen: labels[index].clone(),
+            probes: Vec::new(),
+            losers: labels
+                .into_iter()
+                .zip(outcomes)
+                .enumerate()
+                .filter(|(position, _)| *position != index)
+                .map(|(_, (source, reason))| LostSource {
+                    source,
+                    reason: reason.unwrap_or(LossReason::Cancelled),
+                })
+                .collect(),
+        });
+        Ok(receipt)
     }
 
     async fn try_candidate(
@@ -277,81 +605,48 @@ impl<C: HttpClient + 'static> MultiSourceFetcher<C> {
                     .fetch_with_receipt(url.as_url().as_ref(), destination, options.clone())
                     .await
             }
-            ResolvedSourceCandidate::LocalPath(path) => copy_local_candidate(path, destination),
-            ResolvedSourceCandidate::Git { .. } => Err(Error::InvalidState(
-                "git candidates are not executable by pulith-fetch yet".to_string(),
-            )),
+            ResolvedSourceCandidate::LocalPath(path) => {
+                self.fetcher
+                    .fetch_local_with_receipt(path, destination, options.clone())
+                    .await
+            }
+            ResolvedSourceCandidate::Git { url, rev, subpath } => {
+                GitFetcher::new(self.fetcher.workspace_root())
+                    .fetch_with_receipt(
+                        url.as_url().as_ref(),
+                        rev.as_deref(),
+                        subpath.as_deref(),
+                        destination,
+                        options.clone(),
+                    )
+                    .await
+            }
+            ResolvedSourceCandidate::Oci(source) => {
+                OciFetcher::new(Arc::clone(&self.fetcher))
+                    .fetch_with_receipt(source, destination, options.clone())
+                    .await
+            }
+            ResolvedSourceCandidate::S3(source) => {
+                S3Fetcher::new(Arc::clone(&self.fetcher), self.s3_credentials.clone())
+                    .fetch_with_receipt(source, destination, options.clone())
+                    .await
+            }
         }
     }
 }
 
-fn copy_local_candidate(source: &Path, destination: &Path) -> Result<FetchReceipt> {
-    if source.is_dir() {
-        return Err(Error::InvalidState(
-            "local directory candidates are not executable by pulith-fetch".to_string(),
-        ));
-    }
-
-    let dest_dir = destination.parent().unwrap_or_else(|| Path::new("."));
-    std::fs::create_dir_all(dest_dir).map_err(|source| {
-        Error::Fs(pulith_fs::Error::Write {
-            path: dest_dir.to_path_buf(),
-            source,
-        })
-    })?;
-
-    let file_name = destination
-        .file_name()
-        .unwrap_or_else(|| std::ffi::OsStr::new("download"));
-    let staging_dir = tempfile::Builder::new()
-        .prefix(".pulith-local-copy.")
-        .tempdir_in(dest_dir)
-        .map_err(|error| Error::Network(error.to_string()))?;
-    let staging_path = staging_dir.path().join(file_name);
-
-    std::fs::copy(source, &staging_path).map_err(|source| {
-        Error::Fs(pulith_fs::Error::Write {
-            path: staging_path.clone(),
-            source,
-        })
-    })?;
-
-    replace_destination_file(&staging_path, destination)?;
-
-    let size = std::fs::metadata(destination)
-        .map_err(|error| Error::Network(error.to_string()))?
-        .len();
-    Ok(FetchReceipt {
-        source: FetchSource::LocalPath(source.to_path_buf()),
-        destination: destination.to_path_buf(),
-        bytes_downloaded: size,
-        total_bytes: Some(size),
-        sha256_hex: None,
-    })
-}
-
-fn replace_destination_file(staged_path: &Path, destination: &Path) -> Result<()> {
-    if let Ok(metadata) = std::fs::symlink_metadata(destination) {
-        if metadata.file_type().is_dir() {
-            return Err(Error::DestinationIsDirectory);
+/// Human-readable name of a candidate for receipts.
+pub(crate) fn candidate_label(candidate: &ResolvedSourceCandidate) -> String {
+    match candidate {
+        ResolvedSourceCandidate::Url(url) => url.as_url().to_string(),
+        ResolvedSourceCandidate::LocalPath(path) => path.display().to_string(),
+        ResolvedSourceCandidate::Git { url, .. } => url.as_url().to_string(),
+        ResolvedSourceCandidate::Oci(source) => {
+            registry_url(source, "manifests", source.reference.as_str())
+                .unwrap_or_else(|_| format!("{}:{}", source.repository, source.reference))
         }
-
-        std::fs::remove_file(destination).map_err(|source| {
-            Error::Fs(pulith_fs::Error::Write {
-                path: destination.to_path_buf(),
-                source,
-            })
-        })?;
+        ResolvedSourceCandidate::S3(source) => format!("s3://{}/{}", source.bucket, source.key),
     }
-
-    std::fs::rename(staged_path, destination).map_err(|source| {
-        Error::Fs(pulith_fs::Error::Write {
-            path: destination.to_path_buf(),
-            source,
-        })
-    })?;
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -427,6 +722,498 @@ mod tests {
         }
     }
 
+    // Mirrors that answer HEAD after a per-host delay, or fail entirely
+    struct MirrorClient {
+        head_delays: Vec<(&'static str, u64)>,
+        broken: Vec<&'static str>,
+    }
+
+    impl MirrorClient {
+        fn is_broken(&self, url: &str) -> bool {
+            self.broken.iter().any(|host| url.contains(host))
+        }
+    }
+
+    impl HttpClient for MirrorClient {
+        type Error = MockError;
+
+        async fn stream(
+            &self,
+            url: &str,
+            _headers: &[(String, String)],
+        ) -> std::result::Result<
+            BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            if self.is_broken(url) {
+                return Err(MockError(format!("{url} unavailable")));
+            }
+            let body = Bytes::from(url.to_string());
+            Ok(Box::pin(futures_util::stream::once(
+                async move { Ok(body) },
+            )))
+        }
+
+        async fn head(&self, url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            if self.is_broken(url) {
+                return Err(MockError(format!("{url} unavailable")));
+            }
+            let delay = self
+                .head_delays
+                .iter()
+                .find(|(host, _)| url.contains(host))
+                .map_or(0, |(_, delay)| *delay);
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            Ok(None)
+        }
+    }
+
+    fn ranked_options(strategy: SourceSelectionStrategy) -> MultiSourceOptions {
+        MultiSourceOptions {
+            sources: Vec::new(),
+            strategy,
+            verify_consistency: false,
+            per_source_timeout: Some(std::time::Duration::from_secs(2)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fastest_first_ranks_probes_and_persists_health() {
+        let temp = tempfile::tempdir().unwrap();
+        let health_path = temp.path().join("mirror-health.json");
+        let client = MirrorClient {
+            head_delays: vec![("slow.example.com", 150)],
+            broken: vec!["down.example.com"],
+        };
+        let fetcher = Arc::new(Fetcher::new(client, temp.path().join("workspace")));
+        let multi_fetcher =
+            MultiSourceFetcher::new(fetcher).with_health(MirrorHealth::load(&health_path));
+
+        let sources = vec![
+            DownloadSource::new("http://down.example.com/tool"),
+            DownloadSource::new("http://slow.example.com/tool"),
+            DownloadSource::new("http://fast.example.com/tool").priority(5),
+        ];
+        let destination = temp.path().join("tool");
+        let receipt = multi_fetcher
+            .fetch_multi_source_with_receipt(
+                sources,
+                &destination,
+                ranked_options(SourceSelectionStrategy::FastestFirst),
+                &crate::FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&destination).unwrap(),
+            "http://fast.example.com/tool"
+        );
+        let selection = receipt.selection.unwrap();
+        assert_eq!(selection.strategy, "fastest-first");
+        assert_eq!(selection.chosen, "http://fast.example.com/tool");
+        let tried = selection
+            .probes
+            .iter()
+            .map(|probe| probe.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tried,
+            [
+                "http://fast.example.com/tool",
+                "http://slow.example.com/tool",
+                "http://down.example.com/tool"
+            ]
+        );
+        assert!(selection.probes[2].error.is_some());
+
+        let saved = MirrorHealth::load(&health_path);
+        assert_eq!(saved.host("http://fast.example.com/").unwrap().successes, 1);
+        assert_eq!(saved.host("http://down.example.com/").unwrap().failures, 1);
+    }
+
+    #[tokio::test]
+    async fn test_geographic_prefers_region_and_demotes_recent_failures() {
+        let temp = tempfile::tempdir().unwrap();
+        let client = MirrorClient {
+            head_delays: vec![("eu.example.com", 100)],
+            broken: Vec::new(),
+        };
+        let fetcher = Arc::new(Fetcher::new(client, temp.path().join("workspace")));
+        let health = MirrorHealth::in_memory();
+        health.record_failure("http://eu-backup.example.com/tool");
+        let multi_fetcher = MultiSourceFetcher::new(fetcher)
+            .with_health(health)
+            .with_region("eu");
+
+        let sources = vec![
+            DownloadSource::new("http://us.example.com/tool"),
+            DownloadSource::new("http://eu-backup.example.com/tool").region("eu"),
+            DownloadSource::new("http://eu.example.com/tool").region("eu"),
+        ];
+        let receipt = multi_fetcher
+            .fetch_multi_source_with_receipt(
+                sources,
+                &temp.path().join("tool"),
+                ranked_options(SourceSelectionStrategy::Geographic),
+                &crate::FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let selection = receipt.selection.unwrap();
+        assert_eq!(selection.strategy, "geographic");
+        assert_eq!(selection.chosen, "http://eu.example.com/tool");
+        assert_eq!(selection.probes[1].url, "http://us.example.com/tool");
+        assert!(!selection.probes[1].in_region);
+        assert!(selection.probes[2].demoted);
+        assert!(selection.probes[2].in_region);
+    }
+
+    // Contenders that finish after a delay, stall after one chunk, or fail
+    struct RaceClient;
+
+    impl HttpClient for RaceClient {
+        type Error = MockError;
+
+        async fn stream(
+            &self,
+            url: &str,
+            _headers: &[(String, String)],
+        ) -> std::result::Result<
+            BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            let body = Bytes::from(url.to_string());
+            if url.contains("broken") {
+                Err(MockError(format!("{url} unavailable")))
+            } else if url.contains("stalled") {
+                Ok(Box::pin(
+                    futures_util::stream::once(async move { Ok(body) })
+                        .chain(futures_util::stream::pending()),
+                ))
+            } else {
+                Ok(Box::pin(futures_util::stream::once(async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                    Ok(body)
+                })))
+            }
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_race_places_winner_and_cancels_losers() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Arc::new(Fetcher::new(RaceClient, temp.path().join("workspace")));
+        let multi_fetcher = MultiSourceFetcher::new(fetcher);
+        let destination = temp.path().join("out/tool");
+        std::fs::create_dir_all(destination.parent().unwrap()).unwrap();
+        std::fs::write(&destination, "previous").unwrap();
+
+        let planned = SourceSpec::new(
+            SourceSet::new(
+                ["stalled", "broken", "mirror"]
+                    .into_iter()
+                    .map(|host| {
+                        SourceDefinition::Remote(RemoteSource::HttpAsset(HttpAssetSource {
+                            url: ValidUrl::parse(format!("http://{host}.example.com/tool"))
+                                .unwrap(),
+                            file_name: None,
+                        }))
+                    })
+                    .collect(),
+            )
+            .unwrap(),
+        )
+        .plan(SelectionStrategy::Race);
+        let options = crate::FetchOptions::default().retry_policy(crate::RetryPolicy {
+            max_retries: 0,
+            base_backoff: std::time::Duration::from_millis(1),
+            ..crate::RetryPolicy::default()
+        });
+        let receipt = multi_fetcher
+            .fetch_planned_sources_with_receipt(&planned, &destination, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&destination).unwrap(),
+            "http://mirror.example.com/tool"
+        );
+        assert_eq!(receipt.destination, destination);
+        let selection = receipt.selection.unwrap();
+        assert_eq!(selection.strategy, "race");
+        assert_eq!(selection.chosen, "http://mirror.example.com/tool");
+        assert_eq!(selection.losers.len(), 2);
+        assert_eq!(
+            selection.losers[0].source,
+            "http://stalled.example.com/tool"
+        );
+        assert_eq!(selection.losers[0].reason, LossReason::Cancelled);
+        assert!(matches!(
+            &selection.losers[1].reason,
+            LossReason::Failed { error } if error.contains("max retries")
+        ));
+
+        let leftovers = std::fs::read_dir(temp.path().join("workspace/staging"))
+            .unwrap()
+            .count();
+        assert_eq!(leftovers, 0);
+        assert_eq!(
+            std::fs::read_dir(temp.path().join("out")).unwrap().count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_race_reports_last_error_when_all_fail() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Arc::new(Fetcher::new(RaceClient, temp.path().join("workspace")));
+        let destination = temp.path().join("tool");
+
+        let result = MultiSourceFetcher::new(fetcher)
+            .fetch_multi_source_with_receipt(
+                vec![
+                    DownloadSource::new("http://broken-a.example.com/tool"),
+                    DownloadSource::new("http://broken-b.example.com/tool"),
+                ],
+                &destination,
+                ranked_options(SourceSelectionStrategy::RaceAll),
+                &crate::FetchOptions::default(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(Error::MaxRetriesExceeded { .. })));
+        assert!(!destination.exists());
+    }
+
+    // Mirrors serving the same artifact, except tampered or unreachable ones
+    struct ConsistencyClient;
+
+    impl HttpClient for ConsistencyClient {
+        type Error = MockError;
+
+        async fn stream(
+            &self,
+            url: &str,
+            _headers: &[(String, String)],
+        ) -> std::result::Result<
+            BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            if url.contains("broken") {
+                return Err(MockError(format!("{url} unavailable")));
+            }
+            let body = if url.contains("tampered") {
+                Bytes::from_static(b"artifact with a backdoor")
+            } else {
+                Bytes::from_static(b"artifact")
+            };
+            Ok(Box::pin(futures_util::stream::once(
+                async move { Ok(body) },
+            )))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+    }
+
+    fn consistency_options() -> MultiSourceOptions {
+        MultiSourceOptions {
+            verify_consistency: true,
+            ..ranked_options(SourceSelectionStrategy::RaceAll)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_consistency_names_divergent_mirror() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Arc::new(Fetcher::new(
+            ConsistencyClient,
+            temp.path().join("workspace"),
+        ));
+        let destination = temp.path().join("tool");
+
+        let result = MultiSourceFetcher::new(fetcher)
+            .fetch_multi_source_with_receipt(
+                vec![
+                    DownloadSource::new("http://tampered.example.com/tool"),
+                    DownloadSource::new("http://a.example.com/tool").priority(1),
+                    DownloadSource::new("http://b.example.com/tool").priority(2),
+                ],
+                &destination,
+                consistency_options(),
+                &crate::FetchOptions::default(),
+            )
+            .await;
+
+        match result {
+            Err(Error::InconsistentMirror {
+                mirror,
+                expected_bytes,
+                actual_bytes,
+                ..
+            }) => {
+                assert_eq!(mirror, "http://tampered.example.com/tool");
+                assert_eq!(expected_bytes, 8);
+                assert_eq!(actual_bytes, 24);
+            }
+            other => panic!("expected InconsistentMirror, got {other:?}"),
+        }
+        assert!(!destination.exists());
+        let leftovers = std::fs::read_dir(temp.path().join("workspace/staging"))
+            .unwrap()
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[tokio::test]
+    async fn test_verify_consistency_without_majority_blames_no_mirror() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Arc::new(Fetcher::new(
+            ConsistencyClient,
+            temp.path().join("workspace"),
+        ));
+        let destination = temp.path().join("tool");
+
+        let result = MultiSourceFetcher::new(fetcher)
+            .fetch_multi_source_with_receipt(
+                vec![
+                    DownloadSource::new("http://a.example.com/tool"),
+                    DownloadSource::new("http://tampered.example.com/tool"),
+                ],
+                &destination,
+                consistency_options(),
+                &crate::FetchOptions::default(),
+            )
+            .await;
+
+        assert!(
+            matches!(result, Err(Error::NoMirrorMajority { mirrors: 2 })),
+            "{result:?}"
+        );
+        assert!(!destination.exists());
+    }
+
+    #[tokio::test]
+    async fn test_verify_consistency_places_agreed_content() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Arc::new(Fetcher::new(
+            ConsistencyClient,
+            temp.path().join("workspace"),
+        ));
+        let destination = temp.path().join("tool");
+        let multi_fetcher = MultiSourceFetcher::new(fetcher);
+
+        let receipt = multi_fetcher
+            .fetch_multi_source_with_receipt(
+                vec![
+                    DownloadSource::new("http://a.example.com/tool"),
+                    DownloadSource::new("http://b.example.com/tool"),
+                ],
+                &destination,
+                consistency_options(),
+                &crate::FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&destination).unwrap(), b"artifact");
+        assert_eq!(receipt.destination, destination);
+        assert_eq!(
+            receipt.source,
+            crate::FetchSource::Url("http://a.example.com/tool".into())
+        );
+
+        let single = multi_fetcher
+            .fetch_multi_source_with_receipt(
+                vec![
+                    DownloadSource::new("http://broken.example.com/tool"),
+                    DownloadSource::new("http://a.example.com/tool"),
+                ],
+                &destination,
+                consistency_options(),
+                &crate::FetchOptions::default(),
+            )
+            .await;
+        assert!(matches!(single, Err(Error::MaxRetriesExceeded { .. })));
+    }
+
+    // Records the headers sent with every GET
+    #[derive(Default)]
+    struct HeaderClient {
+        seen: std::sync::Mutex<Vec<Vec<(String, String)>>>,
+    }
+
+    impl HttpClient for HeaderClient {
+        type Error = MockError;
+
+        async fn stream(
+            &self,
+            _url: &str,
+            headers: &[(String, String)],
+        ) -> std::result::Result<
+            BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            self.seen.lock().unwrap().push(headers.to_vec());
+            Ok(Box::pin(futures_util::stream::once(async {
+                Ok(Bytes::from_static(b"artifact"))
+            })))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_strategy_uses_the_callers_fetch_options() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetcher = Arc::new(Fetcher::new(
+            HeaderClient::default(),
+            temp.path().join("workspace"),
+        ));
+        let multi = MultiSourceFetcher::new(Arc::clone(&fetcher));
+        let fetch_options = crate::FetchOptions::default().header("X-Mirror-Token", "secret");
+        let strategies = [
+            ranked_options(SourceSelectionStrategy::Priority),
+            ranked_options(SourceSelectionStrategy::RaceAll),
+            ranked_options(SourceSelectionStrategy::FastestFirst),
+            ranked_options(SourceSelectionStrategy::Geographic),
+            consistency_options(),
+        ];
+
+        for options in strategies {
+            multi
+                .fetch_multi_source_with_receipt(
+                    vec![
+                        DownloadSource::new("http://a.example.com/tool"),
+                        DownloadSource::new("http://b.example.com/tool"),
+                    ],
+                    &temp.path().join("tool"),
+                    options,
+                    &fetch_options,
+                )
+                .await
+                .unwrap();
+        }
+
+        let seen = fetcher.client.seen.lock().unwrap();
+        assert!(!seen.is_empty());
+        for headers in seen.iter() {
+            assert!(
+                headers
+                    .iter()
+                    .any(|(name, value)| name == "X-Mirror-Token" && value == "secret")
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_multi_source_fetcher_new() {
         // Create a mock HTTP client
@@ -452,7 +1239,12 @@ mod tests {
         };
 
         let result = multi_fetcher
-            .fetch_multi_source_with_receipt(sources, destination, options)
+            .fetch_multi_source_with_receipt(
+                sources,
+                destination,
+                options,
+                &crate::FetchOptions::default(),
+            )
             .await;
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -480,7 +1272,12 @@ mod tests {
         };
 
         let result = multi_fetcher
-            .fetch_multi_source_with_receipt(sources, destination, options)
+            .fetch_multi_source_with_receipt(
+                sources,
+                destination,
+                options,
+                &crate::FetchOptions::default(),
+            )
             .await;
         // The test will fail because we're using a real fetcher with mock client
         // but that's expected - we're just testing the structure
@@ -506,7 +1303,12 @@ mod tests {
         };
 
         let result = multi_fetcher
-            .fetch_multi_source_with_receipt(sources, destination, options)
+            .fetch_multi_source_with_receipt(
+                sources,
+                destination,
+                options,
+                &crate::FetchOptions::default(),
+            )
             .await;
         // The test will fail because we're using a real fetcher with mock client
         // but that's expected - we're just testing the structure
@@ -532,7 +1334,12 @@ mod tests {
         };
 
         let result = multi_fetcher
-            .fetch_multi_source_with_receipt(sources, destination, options)
+            .fetch_multi_source_with_receipt(
+                sources,
+                destination,
+                options,
+                &crate::FetchOptions::default(),
+            )
             .await;
         // The test will fail because we're using a real fetcher with mock client
         // but that's expected - we're just testing the structure
@@ -558,7 +1365,12 @@ mod tests {
         };
 
         let result = multi_fetcher
-            .fetch_multi_source_with_receipt(sources, destination, options)
+            .fetch_multi_source_with_receipt(
+                sources,
+                destination,
+                options,
+                &crate::FetchOptions::default(),
+            )
             .await;
         // The test will fail because we're using a real fetcher with mock client
         // but that's expected - we're just testing the structure
@@ -0,0 +1,686 @@
+//! OCI distribution registry artifacts.
+//!
+//! An [`OciSource`] is resolved by reading its manifest from the registry's
+//! `/v2/` API, following an image index to the manifest for the target
+//! platform, and selecting one layer. The layer blob is then downloaded
+//! through the regular [`Fetcher`] with the layer digest and size as the
+//! verification requirement, so the result is an ordinary [`FetchReceipt`].
+
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+use pulith_platform::arch::{Arch, TargetTriple};
+use pulith_resource::ValidUrl;
+use pulith_source::{OciReference, OciSource};
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
+
+use crate::config::FetchOptions;
+use crate::error::{Error, Result};
+use crate::fetch::fetcher::{FetchReceipt, Fetcher};
+use crate::net::credentials::REDACTED;
+use crate::net::http::{HttpClient, read_body};
+
+/// OCI image manifest media type.
+pub const OCI_MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+/// OCI image index media type.
+pub const OCI_INDEX_MEDIA_TYPE: &str = "application/vnd.oci.image.index.v1+json";
+
+const DOCKER_MANIFEST_MEDIA_TYPE: &str = "application/vnd.docker.distribution.manifest.v2+json";
+const DOCKER_MANIFEST_LIST_MEDIA_TYPE: &str =
+    "application/vnd.docker.distribution.manifest.list.v2+json";
+
+/// Supplies the `Authorization` header for requests to one repository.
+pub trait OciAuth: Send + Sync {
+    fn authorization<C: HttpClient>(
+        &self,
+        client: &C,
+        registry: &ValidUrl,
+        repository: &str,
+    ) -> impl Future<Output = Result<Option<String>>> + Send;
+}
+
+/// Anonymous registry access.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnonymousOciAuth;
+
+impl OciAuth for AnonymousOciAuth {
+    async fn authorization<C: HttpClient>(
+        &self,
+        _client: &C,
+        _registry: &ValidUrl,
+        _repository: &str,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// A pre-issued bearer token.
+#[derive(Clone)]
+pub struct OciBearerToken(pub String);
+
+impl fmt::Debug for OciBearerToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OciBearerToken").field(&REDACTED).finish()
+    }
+}
+
+impl OciAuth for OciBearerToken {
+    async fn authorization<C: HttpClient>(
+        &self,
+        _client: &C,
+        _registry: &ValidUrl,
+        _repository: &str,
+    ) -> Result<Option<String>> {
+        Ok(Some(format!("Bearer {}", self.0)))
+    }
+}
+
+/// Registry token service issuing pull tokens per repository.
+///
+/// Requests `<realm>?service=<service>&scope=repository:<repository>:pull`
+/// and sends the returned token as a bearer token.
+#[derive(Clone)]
+pub struct OciTokenService {
+    realm: String,
+    service: Option<String>,
+    basic_auth: Option<String>,
+}
+
+impl fmt::Debug for OciTokenService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OciTokenService")
+            .field("realm", &self.realm)
+            .field("service", &self.service)
+            .field("basic_auth", &self.basic_auth.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
+
+impl OciTokenService {
+    pub fn new(realm: impl Into<String>) -> Self {
+        Self {
+            realm: realm.into(),
+            service: None,
+            basic_auth: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_service(mut self, service: impl Into<String>) -> Self {
+        self.service = Some(service.into());
+        self
+    }
+
+    /// Authenticate to the token service with a base64 `user:password` pair.
+    #[must_use]
+    pub fn with_basic_auth(mut self, encoded: impl Into<String>) -> Self {
+        self.basic_auth = Some(encoded.into());
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    access_token: Option<String>,
+}
+
+impl OciAuth for OciTokenService {
+    async fn authorization<C: HttpClient>(
+        &self,
+        client: &C,
+        _registry: &ValidUrl,
+        repository: &str,
+    ) -> Result<Option<String>> {
+        let mut url = ValidUrl::parse(&self.realm)
+            .map_err(|e| Error::InvalidUrl(format!("{}: {e}", self.realm)))?
+            .as_url()
+            .clone();
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(service) = &self.service {
+                query.append_pair("service", service);
+            }
+            query.append_pair("scope", &format!("repository:{repository}:pull"));
+        }
+        let headers = self
+            .basic_auth
+            .iter()
+            .map(|encoded| ("Authorization".to_string(), format!("Basic {encoded}")))
+            .collect::<Vec<_>>();
+
+        let body = read_body(client, url.as_str(), &headers).await?;
+        let response: TokenResponse = serde_json::from_slice(&body)
+            .map_err(|e| Error::InvalidState(format!("Invalid OCI token response: {e}")))?;
+        response
+            .token
+            .or(response.access_token)
+            .map(|token| Some(format!("Bearer {token}")))
+            .ok_or_else(|| Error::InvalidState("Invalid OCI token response: no token".to_string()))
+    }
+}
+
+/// Content descriptor from a manifest or index.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OciDescriptor {
+    #[serde(default)]
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(default)]
+    pub platform: Option<OciPlatform>,
+    #[serde(default)]
+    pub annotations: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OciPlatform {
+    pub os: String,
+    pub architecture: String,
+}
+
+/// Image manifest or image index, as returned by the registry.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestDocument {
+    #[serde(default)]
+    media_type: Option<String>,
+    #[serde(default)]
+    layers: Vec<OciDescriptor>,
+    #[serde(default)]
+    manifests: Vec<OciDescriptor>,
+}
+
+impl ManifestDocument {
+    fn is_index(&self) -> bool {
+        matches!(
+            self.media_type.as_deref(),
+            Some(OCI_INDEX_MEDIA_TYPE | DOCKER_MANIFEST_LIST_MEDIA_TYPE)
+        ) || (self.layers.is_empty() && !self.manifests.is_empty())
+    }
+}
+
+/// Fetches artifacts from OCI distribution registries.
+pub struct OciFetcher<C: HttpClient, A = AnonymousOciAuth> {
+    fetcher: Arc<Fetcher<C>>,
+    auth: A,
+    platform: OciPlatform,
+}
+
+impl<C: HttpClient> OciFetcher<C> {
+    /// Create an OCI fetcher with anonymous access for the host platform.
+    pub fn new(fetcher: Arc<Fetcher<C>>) -> Self {
+        Self {
+            fetcher,
+            auth: AnonymousOciAuth,
+            platform: oci_platform(&TargetTriple::host()),
+        }
+    }
+}
+
+impl<C: HttpClient, A: OciAuth> OciFetcher<C, A> {
+    /// Authenticate registry requests with `auth`.
+    pub fn with_auth<B: OciAuth>(self, auth: B) -> OciFetcher<C, B> {
+        OciFetcher {
+            fetcher: self.fetcher,
+            auth,
+            platform: self.platform,
+        }
+    }
+
+    /// Select image index entries for `target` instead of the host.
+    #[must_use]
+    pub fn with_target(mut self, target: &TargetTriple) -> Self {
+        self.platform = oci_platform(target);
+        self
+    }
+
+    /// Resolve the manifest of `source` and return the layer to download.
+    pub async fn resolve_layer(&self, source: &OciSource) -> Result<OciDescriptor> {
+        let headers = self.auth_headers(source).await?;
+        self.select_layer(source, &headers).await
+    }
+
+    async fn select_layer(
+        &self,
+        source: &OciSource,
+        headers: &[(String, String)],
+    ) -> Result<OciDescriptor> {
+        let mut manifest = self
+            .read_manifest(source, source.reference.as_str(), headers)
+            .await?;
+
+        if manifest.is_index() {
+            let entry = manifest
+                .manifests
+                .iter()
+                .find(|entry| entry.platform.as_ref() == Some(&self.platform))
+                .or_else(|| {
+                    manifest
+                        .manifests
+                        .iter()
+                        .find(|entry| entry.platform.is_none())
+                })
+                .ok_or_else(|| {
+                    Error::InvalidState(format!(
+                        "OCI index {}:{} has no manifest for {}/{}",
+                        source.repository,
+                        source.reference,
+                        self.platform.os,
+                        self.platform.architecture
+                    ))
+                })?
+                .digest
+                .clone();
+            manifest = self.read_manifest(source, &entry, headers).await?;
+        }
+
+        let mut layers = manifest.layers.into_iter().filter(|layer| {
+            source
+                .media_type
+                .as_ref()
+                .is_none_or(|media_type| &layer.media_type == media_type)
+        });
+        let layer = layers.next().ok_or_else(|| {
+            Error::InvalidState(format!(
+                "OCI manifest {}:{} has no layer{}",
+                source.repository,
+                source.reference,
+                source
+                    .media_type
+                    .as_ref()
+                    .map(|media_type| format!(" of type {media_type}"))
+                    .unwrap_or_default()
+            ))
+        })?;
+        if layers.next().is_some() {
+            return Err(Error::InvalidState(format!(
+                "OCI manifest {}:{} has several matching layers; set a media type",
+                source.repository, source.reference
+            )));
+        }
+        Ok(layer)
+    }
+
+    /// Download the selected layer of `source` to `destination`.
+    ///
+    /// The layer digest and size override any checksum or expected size in
+    /// `options`.
+    #[tracing::instrument(skip(self, source, options), fields(repository = %source.repository, reference = %source.reference))]
+    pub async fn fetch_with_receipt(
+        &self,
+        source: &OciSource,
+        destination: &Path,
+        options: FetchOptions,
+    ) -> Result<FetchReceipt> {
+        let headers = self.auth_headers(source).await?;
+        let layer = self.select_layer(source, &headers).await?;
+        let checksum = sha256_digest(&layer.digest)?;
+        let blob_url = registry_url(source, "blobs", &layer.digest)?;
+
+        let mut options = options
+            .checksum(Some(checksum))
+            .expected_bytes(Some(layer.size));
+        for (key, value) in headers {
+            options = options.header(key, value);
+        }
+        self.fetcher
+            .fetch_with_receipt(&blob_url, destination, options)
+            .await
+    }
+
+    async fn auth_headers(&self, source: &OciSource) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .auth
+            .authorization(&self.fetcher.client, &source.registry, &source.repository)
+            .await?
+            .map(|value| vec![("Authorization".to_string(), value)])
+            .unwrap_or_default())
+    }
+
+    async fn read_manifest(
+        &self,
+        source: &OciSource,
+        reference: &str,
+        auth: &[(String, String)],
+    ) -> Result<ManifestDocument> {
+        let url = registry_url(source, "manifests", reference)?;
+        let mut headers = vec![(
+            "Accept".to_string(),
+            [
+                OCI_MANIFEST_MEDIA_TYPE,
+                OCI_INDEX_MEDIA_TYPE,
+                DOCKER_MANIFEST_MEDIA_TYPE,
+                DOCKER_MANIFEST_LIST_MEDIA_TYPE,
+            ]
+            .join(", "),
+        )];
+        headers.extend_from_slice(auth);
+
+        let body = read_body(&self.fetcher.client, &url, &headers).await?;
+        if matches!(OciReference::parse(reference), Ok(OciReference::Digest(_))) {
+            let actual = format!("sha256:{}", hex::encode(Sha256::digest(&body)));
+            if actual != reference {
+                return Err(Error::ChecksumMismatch {
+                    expected: reference.to_string(),
+                    actual,
+                });
+            }
+        }
+        serde_json::from_slice(&body).map_err(|e| {
+            Error::InvalidState(format!(
+                "Invalid OCI manifest {}:{}: {e}",
+                source.repository, reference
+            ))
+        })
+    }
+}
+
+/// Map a target triple to OCI platform names.
+fn oci_platform(target: &TargetTriple) -> OciPlatform {
+    let architecture = match target.arch {
+        Arch::X86 => "386",
+        Arch::X86_64 => "amd64",
+        Arch::ARM => "arm",
+        Arch::ARM64 => "arm64",
+        Arch::Unknown => "unknown",
+    };
+    OciPlatform {
+        os: target.os_to_str().to_string(),
+        architecture: architecture.to_string(),
+    }
+}
+
+/// URL of `/v2/<repository>/<kind>/<reference>` below the registry URL,
+/// keeping any path prefix the registry is served under.
+pub(crate) fn registry_url(source: &OciSource, kind: &str, reference: &str) -> Result<String> {
+    let mut url = source.registry.as_url().clone();
+    if url.cannot_be_a_base() {
+        return Err(Error::InvalidUrl(url.to_string()));
+    }
+    let path = format!(
+        "{}/v2/{}/{kind}/{reference}",
+        url.path().trim_end_matches('/'),
+        source.repository
+    );
+    url.set_path(&path);
+    url.set_query(None);
+    Ok(url.to_string())
+}
+
+fn sha256_digest(digest: &str) -> Result<[u8; 32]> {
+    let invalid = || Error::InvalidState(format!("Unsupported OCI layer digest: {digest}"));
+    let hex = digest.strip_prefix("sha256:").ok_or_else(invalid)?;
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(hex, &mut bytes).map_err(|_| invalid())?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::http::BoxStream;
+    use bytes::Bytes;
+    use pulith_platform::os::OS;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct MockError(String);
+
+    impl std::fmt::Display for MockError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::error::Error for MockError {}
+
+    type Requests = Mutex<Vec<(String, Vec<(String, String)>)>>;
+
+    /// Serves fixed bodies by URL and records every request.
+    #[derive(Default)]
+    struct MockRegistry {
+        bodies: HashMap<String, Vec<u8>>,
+        requests: Requests,
+    }
+
+    impl MockRegistry {
+        fn serve(mut self, url: &str, body: impl Into<Vec<u8>>) -> Self {
+            self.bodies.insert(url.to_string(), body.into());
+            self
+        }
+    }
+
+    impl HttpClient for MockRegistry {
+        type Error = MockError;
+
+        async fn stream(
+            &self,
+            url: &str,
+            headers: &[(String, String)],
+        ) -> std::result::Result<
+            BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((url.to_string(), headers.to_vec()));
+            let body = self
+                .bodies
+                .get(url)
+                .cloned()
+                .ok_or_else(|| MockError(format!("404 {url}")))?;
+            Ok(Box::pin(futures_util::stream::iter(vec![Ok(Bytes::from(
+                body,
+            ))])))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+    }
+
+    const REGISTRY: &str = "https://registry.example.com";
+
+    fn digest(data: &[u8]) -> String {
+        format!("sha256:{}", hex::encode(Sha256::digest(data)))
+    }
+
+    fn manifest(layers: &[(&str, &[u8])]) -> String {
+        let layers = layers
+            .iter()
+            .map(|(media_type, data)| {
+                serde_json::json!({
+                    "mediaType": media_type,
+                    "digest": digest(data),
+                    "size": data.len(),
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({
+            "schemaVersion": 2,
+            "mediaType": OCI_MANIFEST_MEDIA_TYPE,
+            "config": {"mediaType": "application/vnd.oci.empty.v1+json", "digest": digest(b"{}"), "size": 2},
+            "layers": layers,
+        })
+        .to_string()
+    }
+
+    fn source(reference: &str) -> OciSource {
+        OciSource::new(
+            ValidUrl::parse(REGISTRY).unwrap(),
+            "team/toolchain",
+            OciReference::parse(reference).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn url(kind: &str, reference: &str) -> String {
+        format!("{REGISTRY}/v2/team/toolchain/{kind}/{reference}")
+    }
+
+    #[test]
+    fn test_registry_url_keeps_path_prefix() {
+        for registry in [
+            "https://mirror.example/registry",
+            "https://mirror.example/registry/",
+        ] {
+            let source = OciSource::new(
+                ValidUrl::parse(registry).unwrap(),
+                "team/toolchain",
+                OciReference::parse("latest").unwrap(),
+            )
+            .unwrap();
+            assert_eq!(
+                registry_url(&source, "manifests", "latest").unwrap(),
+                "https://mirror.example/registry/v2/team/toolchain/manifests/latest"
+            );
+        }
+        assert_eq!(
+            registry_url(&source("latest"), "blobs", "sha256:00").unwrap(),
+            url("blobs", "sha256:00")
+        );
+    }
+
+    fn linux_amd64() -> TargetTriple {
+        TargetTriple {
+            arch: Arch::X86_64,
+            vendor: "unknown".to_string(),
+            os: OS::Linux,
+            env: Some("gnu".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_layer_by_media_type_with_bearer_token() {
+        let temp = tempfile::tempdir().unwrap();
+        let archive = b"toolchain archive".as_slice();
+        let manifest = manifest(&[
+            ("application/vnd.example.readme", b"docs"),
+            ("application/vnd.example.toolchain.tar+gzip", archive),
+        ]);
+        let client = MockRegistry::default()
+            .serve(&url("manifests", "1.0"), manifest)
+            .serve(&url("blobs", &digest(archive)), archive);
+        let fetcher = Arc::new(Fetcher::new(client, temp.path().join("workspace")));
+        let oci = OciFetcher::new(Arc::clone(&fetcher)).with_auth(OciBearerToken("secret".into()));
+
+        let destination = temp.path().join("toolchain.tar.gz");
+        let receipt = oci
+            .fetch_with_receipt(
+                &source("1.0").with_media_type("application/vnd.example.toolchain.tar+gzip"),
+                &destination,
+                FetchOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&destination).unwrap(), archive);
+        assert_eq!(receipt.sha256_hex, Some(digest(archive)[7..].to_string()));
+        let requests = fetcher.client.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        for (_, headers) in &requests {
+            assert!(headers.contains(&("Authorization".to_string(), "Bearer secret".to_string())));
+        }
+        assert!(
+            requests[0]
+                .1
+                .iter()
+                .any(|(key, value)| key == "Accept" && value.contains(OCI_INDEX_MEDIA_TYPE))
+        );
+
+        let ambiguous = oci
+            .fetch_with_receipt(&source("1.0"), &destination, FetchOptions::default())
+            .await
+            .unwrap_err();
+        assert!(ambiguous.to_string().contains("several matching layers"));
+    }
+
+    #[tokio::test]
+    async fn test_index_selects_platform_and_digest_is_verified() {
+        let temp = tempfile::tempdir().unwrap();
+        let linux = manifest(&[("application/octet-stream", b"linux build")]);
+        let darwin = manifest(&[("application/octet-stream", b"darwin build")]);
+        let index = serde_json::json!({
+            "schemaVersion": 2,
+            "mediaType": OCI_INDEX_MEDIA_TYPE,
+            "manifests": [
+                {"mediaType": OCI_MANIFEST_MEDIA_TYPE, "digest": digest(darwin.as_bytes()), "size": darwin.len(),
+                 "platform": {"os": "darwin", "architecture": "arm64"}},
+                {"mediaType": OCI_MANIFEST_MEDIA_TYPE, "digest": digest(linux.as_bytes()), "size": linux.len(),
+                 "platform": {"os": "linux", "architecture": "amd64"}},
+            ],
+        })
+        .to_string();
+        let client = MockRegistry::default()
+            .serve(&url("manifests", "latest"), index)
+            .serve(&url("manifests", &digest(linux.as_bytes())), linux.clone())
+            .serve(&url("manifests", &digest(darwin.as_bytes())), "tampered")
+            .serve(
+                &url("blobs", &digest(b"linux build")),
+                b"linux build".as_slice(),
+            );
+        let fetcher = Arc::new(Fetcher::new(client, temp.path().join("workspace")));
+        let oci = OciFetcher::new(fetcher).with_target(&linux_amd64());
+
+        let destination = temp.path().join("tool");
+        oci.fetch_with_receipt(&source("latest"), &destination, FetchOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&destination).unwrap(), b"linux build");
+
+        let darwin_digest = digest(darwin.as_bytes());
+        let tampered = oci
+            .resolve_layer(&source(&darwin_digest))
+            .await
+            .unwrap_err();
+        assert!(matches!(tampered, Error::ChecksumMismatch { .. }));
+
+        let mut arm = linux_amd64();
+        arm.arch = Arch::ARM;
+        let missing = oci
+            .with_target(&arm)
+            .resolve_layer(&source("latest"))
+            .await
+            .unwrap_err();
+        assert!(missing.to_string().contains("linux/arm"));
+    }
+
+    #[tokio::test]
+    async fn test_token_service_requests_pull_scope() {
+        let client = MockRegistry::default().serve(
+            "https://auth.example.com/token?service=registry.example.com&scope=repository%3Ateam%2Ftoolchain%3Apull",
+            r#"{"token": "issued"}"#,
+        );
+        let auth = OciTokenService::new("https://auth.example.com/token")
+            .with_service("registry.example.com")
+            .with_basic_auth("dXNlcjpwYXNz");
+
+        let header = auth
+            .authorization(
+                &client,
+                &ValidUrl::parse(REGISTRY).unwrap(),
+                "team/toolchain",
+            )
+            .await
+            .unwrap();
+        assert_eq!(header.as_deref(), Some("Bearer issued"));
+        assert!(!format!("{auth:?}").contains("dXNlcjpwYXNz"));
+        assert!(!format!("{:?}", OciBearerToken("secret".into())).contains("secret"));
+        assert_eq!(
+            client.requests.lock().unwrap()[0].1,
+            [(
+                "Authorization".to_string(),
+                "Basic dXNlcjpwYXNz".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Objects in S3-compatible storage.
+//!
+//! An [`S3Source`] is downloaded through the regular [`Fetcher`] pipeline
+//! with every request signed by [`SigV4Client`], so retries, verification
+//! and placement behave exactly as for plain HTTP downloads.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use pulith_source::S3Source;
+
+use crate::config::FetchOptions;
+use crate::error::{Error, Result};
+use crate::fetch::fetcher::{FetchReceipt, Fetcher};
+use crate::net::http::HttpClient;
+use crate::net::sigv4::{S3Credentials, SigV4Client, SigV4Signer};
+
+/// Fetches objects from S3-compatible buckets.
+pub struct S3Fetcher<C: HttpClient> {
+    fetcher: Arc<Fetcher<C>>,
+    credentials: Option<S3Credentials>,
+}
+
+impl<C: HttpClient> S3Fetcher<C> {
+    /// Create an S3 fetcher signing requests with `credentials`, for
+    /// example [`S3Credentials::from_env`].
+    ///
+    /// Without credentials, requests are sent unsigned, which only works
+    /// for publicly readable buckets.
+    pub fn new(fetcher: Arc<Fetcher<C>>, credentials: Option<S3Credentials>) -> Self {
+        Self {
+            fetcher,
+            credentials,
+        }
+    }
+
+    /// Download the object named by `source` to `destination`.
+    #[tracing::instrument(skip(self, source, options), fields(bucket = %source.bucket, key = %source.key))]
+    pub async fn fetch_with_receipt(
+        &self,
+        source: &S3Source,
+        destination: &Path,
+        options: FetchOptions,
+    ) -> Result<FetchReceipt> {
+        let url = source
+            .object_url()
+            .map_err(|e| Error::InvalidUrl(e.to_string()))?;
+        let url = url.as_url().as_str();
+
+        match &self.credentials {
+            Some(credentials) => {
+                let signer = SigV4Signer::new(credentials.clone(), &source.region);
+                Fetcher::new(
+                    SigV4Client::new(&self.fetcher.client, signer),
+                    self.fetcher.workspace_root(),
+                )
+                .fetch_with_receipt(url, destination, options)
+                .await
+            }
+            None => {
+                self.fetcher
+                    .fetch_with_receipt(url, destination, options)
+                    .await
+            }
+        }
+    }
+}
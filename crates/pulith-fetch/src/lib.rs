@@ -14,26 +14,40 @@ pub mod perf;
 pub mod progress;
 pub mod rate;
 pub mod segment;
+pub mod source;
 
 pub use error::{Error, Result};
 
-pub use cache::{Cache, CacheControl, CacheEntry, CacheError, CacheStats, HttpCache};
+pub use cache::{Cache, CacheConfig, CacheControl, CacheEntry, CacheError, CacheStats, HttpCache};
 pub use codec::{
-    ChecksumConfig, MultiVerifier, SignatureVerifier, StreamTransform, StreamVerifier,
-    TransformError, verify_checksum, verify_signature,
+    ChecksumConfig, ChecksumManifest, Ed25519Verifier, MinisignSignature, MultiVerifier,
+    OpenPgpVerifier, RsaVerifier, SignatureVerifier, SigstoreTrustRoot, SigstoreVerifier,
+    StreamTransform, StreamVerifier, TransformError, verify_checksum, verify_signature,
 };
 pub use config::{
-    DownloadSource, FetchOptions, FetchPhase, MultiSourceOptions, RetryDelayFuture,
-    RetryDelayProvider, RetryPolicy, SourceSelectionStrategy, SourceType,
+    CancellationToken, DownloadSource, FetchOptions, FetchPhase, MultiSourceOptions,
+    RetryDelayFuture, RetryDelayProvider, RetryPolicy, SourceSelectionStrategy, SourceType,
 };
 pub use fetch::{
-    BatchDownloadJob, BatchFetcher, BatchOptions, ConditionalFetcher, ConditionalOptions,
-    DownloadCheckpoint, FetchReceipt, FetchSource, Fetcher, MultiSourceFetcher, RemoteMetadata,
-    ResumableFetcher, SegmentedFetcher, SegmentedOptions,
+    AnonymousOciAuth, BatchDownloadJob, BatchFetcher, BatchOptions, BatchResult, CachingFetcher,
+    CoalescingFetcher, ConditionalFetcher, ConditionalOptions, DownloadCheckpoint, FetchAttempt,
+    FetchReceipt, FetchSource, Fetcher, GitFetcher, HostHealth, LossReason, LostSource,
+    ManifestDigest, ManifestTrust, MirrorHealth, MultiSourceFetcher, OciAuth, OciBearerToken,
+    OciDescriptor, OciFetcher, OciPlatform, OciTokenService, RemoteMetadata, ResumableFetcher,
+    S3Fetcher, SegmentedFetcher, SegmentedOptions, Sha256State, SourceProbe, SourceSelection,
+};
+pub use net::{
+    BoxStream, Credential, CredentialChain, CredentialHelper, CredentialProvider,
+    EnvTokenCredentials, HttpClient, HttpResponse, Method, NetrcCredentials, ProxySettings,
+    ReqwestClient, ReqwestClientBuilder, ReqwestClientError, ResponseHead, S3Credentials,
+    SigV4Client, SigV4ClientError, SigV4Signer, install_crypto_provider, redact_url,
 };
-pub use net::{BoxStream, HttpClient, ReqwestClient};
 pub use progress::{
     ExtendedProgress, PerformanceMetrics, PhaseTimings, Progress, ProgressReporter,
 };
-pub use rate::{AsyncThrottledStream, ThrottledStream, TokenBucket, retry_delay};
+pub use rate::{AsyncThrottledStream, ThrottledStream, TokenBucket, jittered_delay, retry_delay};
 pub use segment::{Segment, calculate_segments, is_redirect};
+pub use source::{
+    GithubAsset, GithubRelease, GithubReleaseAdapter, IndexedVersion, JsonVersionIndex,
+    VersionIndex,
+};
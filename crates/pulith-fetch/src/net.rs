@@ -1,3 +1,16 @@
+pub mod credentials;
 pub mod http;
+#[cfg(feature = "reqwest")]
+pub mod reqwest_config;
+pub mod response;
+pub mod sigv4;
 
-pub use http::{BoxStream, HttpClient, ReqwestClient};
+pub use credentials::{
+    Credential, CredentialChain, CredentialHelper, CredentialProvider, EnvTokenCredentials,
+    NetrcCredentials, redact_url,
+};
+pub use http::{BoxStream, HttpClient, ReqwestClient, ReqwestClientError};
+#[cfg(feature = "reqwest")]
+pub use reqwest_config::{ProxySettings, ReqwestClientBuilder, install_crypto_provider};
+pub use response::{HttpResponse, Method, ResponseHead};
+pub use sigv4::{S3Credentials, SigV4Client, SigV4ClientError, SigV4Signer};
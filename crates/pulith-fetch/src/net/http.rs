@@ -4,7 +4,10 @@ use std::pin::Pin;
 use bytes::Bytes;
 use futures_util::{Stream, StreamExt};
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::fetch::conditional::RemoteMetadata;
+use crate::net::credentials::redact_url;
+use crate::net::response::{HttpResponse, Method, ResponseHead};
 
 /// A boxed stream type for HTTP response bodies.
 ///
@@ -70,13 +73,169 @@ pub trait HttpClient: Send + Sync {
         &self,
         url: &str,
     ) -> impl Future<Output = std::result::Result<Option<u64>, Self::Error>> + Send;
+
+    /// Send a request and return the response head with its unread body.
+    ///
+    /// Unlike [`HttpClient::stream`], non-2xx responses are returned rather
+    /// than treated as errors, so callers can act on the status and headers.
+    /// The default is built from `stream` and `head`: every response reports
+    /// status 200 from the requested URL, and HEAD responses only carry
+    /// `content-length`. Clients that can see the real response should
+    /// override it.
+    fn send(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> impl Future<Output = std::result::Result<HttpResponse<Self::Error>, Self::Error>> + Send
+    {
+        async move {
+            let mut head = ResponseHead::new(200, url);
+            let body: BoxStream<'static, std::result::Result<Bytes, Self::Error>> = match method {
+                Method::Get => self.stream(url, headers).await?,
+                Method::Head => {
+                    if let Some(length) = self.head(url).await? {
+                        head = head.with_header("content-length", length.to_string());
+                    }
+                    Box::pin(futures_util::stream::empty())
+                }
+            };
+            Ok(HttpResponse { head, body })
+        }
+    }
+
+    /// Query cache validators and directives without downloading the body.
+    fn head_metadata(
+        &self,
+        url: &str,
+    ) -> impl Future<Output = std::result::Result<RemoteMetadata, Self::Error>> + Send {
+        async move { Ok(self.send(Method::Head, url, &[]).await?.head.metadata()) }
+    }
+}
+
+impl<C: HttpClient> HttpClient for &C {
+    type Error = C::Error;
+
+    async fn stream(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> std::result::Result<BoxStream<'static, std::result::Result<Bytes, Self::Error>>, Self::Error>
+    {
+        (**self).stream(url, headers).await
+    }
+
+    async fn head(&self, url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+        (**self).head(url).await
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> std::result::Result<HttpResponse<Self::Error>, Self::Error> {
+        (**self).send(method, url, headers).await
+    }
+
+    async fn head_metadata(&self, url: &str) -> std::result::Result<RemoteMetadata, Self::Error> {
+        (**self).head_metadata(url).await
+    }
+}
+
+/// Fail with [`Error::Http`] unless `head` reports a 2xx status.
+pub(crate) fn ensure_success(head: &ResponseHead) -> Result<()> {
+    if head.is_success() {
+        Ok(())
+    } else {
+        Err(Error::Http {
+            status: head.status,
+            message: format!("from {}", head.url),
+            retry_after: head.retry_after(),
+        })
+    }
+}
+
+/// Download a small response body, such as a manifest or API listing, into memory.
+pub(crate) async fn read_body<C: HttpClient>(
+    client: &C,
+    url: &str,
+    headers: &[(String, String)],
+) -> Result<Vec<u8>> {
+    read_body_limited(client, url, headers, usize::MAX).await
+}
+
+/// Like [`read_body`], but fail once the body grows past `limit` bytes.
+pub(crate) async fn read_body_limited<C: HttpClient>(
+    client: &C,
+    url: &str,
+    headers: &[(String, String)],
+    limit: usize,
+) -> Result<Vec<u8>> {
+    let response = client
+        .send(Method::Get, url, headers)
+        .await
+        .map_err(|e| Error::Network(e.to_string()))?;
+    ensure_success(&response.head)?;
+
+    let mut stream = response.body;
+    let mut data = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| Error::Network(e.to_string()))?;
+        if data.len() + chunk.len() > limit {
+            return Err(Error::InvalidState(format!(
+                "response body from {} exceeds {limit} bytes",
+                redact_url(url)
+            )));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
 }
 
 #[cfg(feature = "reqwest")]
 mod reqwest_impl {
     use super::*;
+    use crate::net::reqwest_config::{PinMismatch, ReqwestClientBuilder};
     use reqwest;
 
+    /// Error returned by [`ReqwestClient`].
+    #[derive(Debug, thiserror::Error)]
+    pub enum ReqwestClientError {
+        #[error(transparent)]
+        Request(#[from] reqwest::Error),
+
+        #[error("certificate presented by {host} does not match a pinned public key")]
+        PinMismatch { host: String },
+    }
+
+    impl ReqwestClientError {
+        /// Report a handshake rejected by a certificate pin as
+        /// [`ReqwestClientError::PinMismatch`].
+        fn classify(error: reqwest::Error) -> Self {
+            let mut current: Option<&(dyn std::error::Error + 'static)> = Some(&error);
+            while let Some(layer) = current {
+                if let Some(rustls::Error::InvalidCertificate(rustls::CertificateError::Other(
+                    other,
+                ))) = layer.downcast_ref::<rustls::Error>()
+                    && let Some(mismatch) = other.0.downcast_ref::<PinMismatch>()
+                {
+                    return Self::PinMismatch {
+                        host: mismatch.host.clone(),
+                    };
+                }
+                // `io::Error::source` skips the error it wraps.
+                current = match layer.downcast_ref::<std::io::Error>() {
+                    Some(io) => io
+                        .get_ref()
+                        .map(|inner| inner as &(dyn std::error::Error + 'static)),
+                    None => layer.source(),
+                };
+            }
+            Self::Request(error)
+        }
+    }
+
     /// Production HTTP client implementation using reqwest.
     pub struct ReqwestClient {
         client: reqwest::Client,
@@ -84,14 +243,25 @@ mod reqwest_impl {
 
     impl ReqwestClient {
         /// Create a new ReqwestClient with default configuration.
+        ///
+        /// Proxies are taken from the standard environment variables.
         pub fn new() -> Result<Self> {
-            let client = reqwest::Client::new();
-            Ok(Self { client })
+            ReqwestClientBuilder::new().build()
+        }
+
+        /// Configure proxies, trust roots, client identity, pins and timeouts.
+        pub fn builder() -> ReqwestClientBuilder {
+            ReqwestClientBuilder::new()
+        }
+
+        /// Wrap a configured client.
+        pub(crate) fn from_client(client: reqwest::Client) -> Self {
+            Self { client }
         }
     }
 
     impl HttpClient for ReqwestClient {
-        type Error = reqwest::Error;
+        type Error = ReqwestClientError;
 
         async fn stream(
             &self,
@@ -101,33 +271,53 @@ mod reqwest_impl {
             BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
             Self::Error,
         > {
-            let mut request = self.client.get(url);
+            Ok(self.send(Method::Get, url, headers).await?.body)
+        }
+
+        async fn head(&self, url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(self
+                .send(Method::Head, url, &[])
+                .await?
+                .head
+                .content_length())
+        }
 
+        async fn send(
+            &self,
+            method: Method,
+            url: &str,
+            headers: &[(String, String)],
+        ) -> std::result::Result<HttpResponse<Self::Error>, Self::Error> {
+            let mut request = match method {
+                Method::Get => self.client.get(url),
+                Method::Head => self.client.head(url),
+            };
             for (key, value) in headers {
                 request = request.header(key, value);
             }
+            let response = request.send().await.map_err(ReqwestClientError::classify)?;
 
-            let response = request.send().await?;
-            let stream = response.bytes_stream().map(|result| result);
+            let mut head = ResponseHead::new(response.status().as_u16(), response.url().as_str());
+            for (name, value) in response.headers() {
+                if let Ok(value) = value.to_str() {
+                    head = head.with_header(name.as_str(), value);
+                }
+            }
 
-            Ok(Box::pin(stream))
-        }
-
-        async fn head(&self, url: &str) -> std::result::Result<Option<u64>, Self::Error> {
-            let response = self.client.head(url).send().await?;
-            let content_length = response
-                .headers()
-                .get(reqwest::header::CONTENT_LENGTH)
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.parse::<u64>().ok());
-
-            Ok(content_length)
+            Ok(HttpResponse {
+                head,
+                body: Box::pin(
+                    response
+                        .bytes_stream()
+                        .map(|chunk| chunk.map_err(ReqwestClientError::from)),
+                ),
+            })
         }
     }
 }
 
 #[cfg(feature = "reqwest")]
-pub use reqwest_impl::ReqwestClient;
+pub use reqwest_impl::{ReqwestClient, ReqwestClientError};
 
 #[cfg(test)]
 mod tests {
@@ -263,6 +453,89 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_default_send_builds_on_stream_and_head() {
+        let client = MockHttpClient::with_content_length(2048);
+
+        let get = client
+            .send(Method::Get, "http://example.com", &[])
+            .await
+            .unwrap();
+        assert_eq!(get.head.status, 200);
+        assert_eq!(get.head.url, "http://example.com");
+        let body = get.body.collect::<Vec<_>>().await;
+        assert_eq!(body.len(), 1);
+
+        let head = client
+            .send(Method::Head, "http://example.com", &[])
+            .await
+            .unwrap();
+        assert_eq!(head.head.content_length(), Some(2048));
+        assert_eq!(
+            client
+                .head_metadata("http://example.com")
+                .await
+                .unwrap()
+                .content_length,
+            Some(2048)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_body_rejects_error_status() {
+        struct NotFound;
+
+        impl HttpClient for NotFound {
+            type Error = MockError;
+
+            async fn stream(
+                &self,
+                _url: &str,
+                _headers: &[(String, String)],
+            ) -> std::result::Result<
+                BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+                Self::Error,
+            > {
+                Ok(Box::pin(stream::empty()))
+            }
+
+            async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+                Ok(None)
+            }
+
+            async fn send(
+                &self,
+                _method: Method,
+                url: &str,
+                _headers: &[(String, String)],
+            ) -> std::result::Result<HttpResponse<Self::Error>, Self::Error> {
+                Ok(HttpResponse {
+                    head: ResponseHead::new(404, url),
+                    body: Box::pin(stream::empty()),
+                })
+            }
+        }
+
+        let error = read_body(&NotFound, "http://example.com/index.json", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::Http { status: 404, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_read_body_limited_rejects_oversized_body() {
+        let client = MockHttpClient::new();
+
+        let body = read_body_limited(&client, "http://example.com/data", &[], 9)
+            .await
+            .unwrap();
+        assert_eq!(body, b"test data");
+        let error = read_body_limited(&client, "http://example.com/data", &[], 8)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("exceeds 8 bytes"), "{error}");
+    }
+
     #[test]
     fn test_box_stream_type_alias() {
         // Test that BoxStream is a valid type
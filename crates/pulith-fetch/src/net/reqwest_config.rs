@@ -0,0 +1,493 @@
+//! Network configuration for [`ReqwestClient`]: proxies, extra trust roots,
+//! client certificates, certificate pinning and timeouts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Once};
+use std::time::Duration;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::WebPkiSupportedAlgorithms;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+use rustls::{CertificateError, DigitallySignedStruct, OtherError, SignatureScheme};
+
+use crate::error::{Error, Result};
+use crate::net::credentials::REDACTED;
+use crate::net::http::ReqwestClient;
+
+/// SHA-256 digests of acceptable `SubjectPublicKeyInfo`s, by lowercase host.
+type Pins = HashMap<String, Vec<[u8; 32]>>;
+
+/// Install ring as the process-wide rustls crypto provider, unless another
+/// provider is installed already.
+///
+/// reqwest is built without a provider of its own, so `reqwest::Client::new()`
+/// panics until one is installed. [`ReqwestClientBuilder::build`] calls this;
+/// callers that build reqwest or rustls clients themselves can call it first.
+pub fn install_crypto_provider() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        // An error means another provider won the race, which is fine.
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    });
+}
+
+/// Where [`ReqwestClient`] finds its proxy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ProxySettings {
+    /// `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY`, in upper or
+    /// lower case.
+    #[default]
+    Environment,
+    /// Connect to every host directly.
+    Direct,
+    /// Send all requests through `url`, except to hosts matched by
+    /// `no_proxy`, which uses the `NO_PROXY` syntax.
+    Explicit {
+        url: String,
+        no_proxy: Option<String>,
+    },
+}
+
+/// Client certificate presented for mutual TLS.
+#[derive(Clone, PartialEq, Eq)]
+enum ClientIdentity {
+    /// PEM certificate chain and PKCS#8 private key.
+    Pem {
+        certificate: PathBuf,
+        key: PathBuf,
+    },
+    Pkcs12 {
+        archive: PathBuf,
+        password: String,
+    },
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pem { certificate, key } => f
+                .debug_struct("Pem")
+                .field("certificate", certificate)
+                .field("key", key)
+                .finish(),
+            Self::Pkcs12 { archive, .. } => f
+                .debug_struct("Pkcs12")
+                .field("archive", archive)
+                .field("password", &REDACTED)
+                .finish(),
+        }
+    }
+}
+
+/// Builder for [`ReqwestClient`].
+///
+/// Certificate and key files are read by [`ReqwestClientBuilder::build`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use pulith_fetch::ReqwestClient;
+/// use std::time::Duration;
+///
+/// let client = ReqwestClient::builder()
+///     .with_proxy("http://proxy.corp.example:3128")
+///     .with_no_proxy("localhost,.corp.example")
+///     .with_root_certificates("/etc/corp/root-ca.pem")
+///     .with_client_identity_pem("/etc/corp/client.pem", "/etc/corp/client.key")
+///     .with_connect_timeout(Duration::from_secs(10))
+///     .build()?;
+/// # Ok::<(), pulith_fetch::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestClientBuilder {
+    proxy: ProxySettings,
+    root_certificates: Vec<PathBuf>,
+    identity: Option<ClientIdentity>,
+    pins: Vec<(String, String)>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+}
+
+impl ReqwestClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_proxy_settings(mut self, proxy: ProxySettings) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Send all requests through the proxy at `url`.
+    ///
+    /// Proxy environment variables are ignored from then on.
+    #[must_use]
+    pub fn with_proxy(mut self, url: impl Into<String>) -> Self {
+        let no_proxy = match self.proxy {
+            ProxySettings::Explicit { no_proxy, .. } => no_proxy,
+            _ => None,
+        };
+        self.proxy = ProxySettings::Explicit {
+            url: url.into(),
+            no_proxy,
+        };
+        self
+    }
+
+    /// Hosts that bypass an explicit proxy, in the `NO_PROXY` syntax
+    /// (comma-separated hosts, `.domain` suffixes, IPs and CIDR ranges).
+    #[must_use]
+    pub fn with_no_proxy(mut self, hosts: impl Into<String>) -> Self {
+        if let ProxySettings::Explicit { no_proxy, .. } = &mut self.proxy {
+            *no_proxy = Some(hosts.into());
+        }
+        self
+    }
+
+    /// Ignore proxy settings, including the environment.
+    #[must_use]
+    pub fn without_proxy(mut self) -> Self {
+        self.proxy = ProxySettings::Direct;
+        self
+    }
+
+    /// Trust the certificates in a PEM bundle in addition to the system
+    /// roots.
+    #[must_use]
+    pub fn with_root_certificates(mut self, pem_bundle: impl Into<PathBuf>) -> Self {
+        self.root_certificates.push(pem_bundle.into());
+        self
+    }
+
+    /// Present a client certificate from a PEM chain and a PKCS#8 PEM key.
+    #[must_use]
+    pub fn with_client_identity_pem(
+        mut self,
+        certificate: impl Into<PathBuf>,
+        key: impl Into<PathBuf>,
+    ) -> Self {
+        self.identity = Some(ClientIdentity::Pem {
+            certificate: certificate.into(),
+            key: key.into(),
+        });
+        self
+    }
+
+    /// Present a client certificate from a PKCS#12 archive.
+    #[must_use]
+    pub fn with_client_identity_pkcs12(
+        mut self,
+        archive: impl Into<PathBuf>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.identity = Some(ClientIdentity::Pkcs12 {
+            archive: archive.into(),
+            password: password.into(),
+        });
+        self
+    }
+
+    /// Only accept `host` when its certificate carries a public key whose
+    /// DER `SubjectPublicKeyInfo` hashes to `spki_sha256_hex`.
+    ///
+    /// Several pins for one host are alternatives. Pins are checked during
+    /// the TLS handshake of every connection, redirects included, so nothing
+    /// is sent to a host whose key does not match.
+    #[must_use]
+    pub fn with_pinned_key(
+        mut self,
+        host: impl Into<String>,
+        spki_sha256_hex: impl Into<String>,
+    ) -> Self {
+        self.pins.push((host.into(), spki_sha256_hex.into()));
+        self
+    }
+
+    /// Limit the time to establish a connection, including the TLS handshake.
+    #[must_use]
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Fail when a response stays silent for `timeout`; each received chunk
+    /// restarts it.
+    #[must_use]
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> Result<ReqwestClient> {
+        install_crypto_provider();
+        let mut builder = reqwest::Client::builder();
+
+        match &self.proxy {
+            // reqwest reads the environment unless a proxy is configured.
+            ProxySettings::Environment => {}
+            ProxySettings::Direct => builder = builder.no_proxy(),
+            ProxySettings::Explicit { url, no_proxy } => {
+                let proxy = reqwest::Proxy::all(url)
+                    .map_err(|e| invalid("proxy", e))?
+                    .no_proxy(no_proxy.as_deref().and_then(reqwest::NoProxy::from_string));
+                builder = builder.proxy(proxy);
+            }
+        }
+
+        let mut pins = Pins::new();
+        for (host, digest) in &self.pins {
+            let digest = hex::decode(digest.trim())
+                .ok()
+                .and_then(|digest| <[u8; 32]>::try_from(digest).ok())
+                .ok_or_else(|| {
+                    Error::InvalidState(format!(
+                        "Invalid certificate pin for {host}: expected 64 hex digits"
+                    ))
+                })?;
+            pins.entry(host.to_ascii_lowercase())
+                .or_default()
+                .push(digest);
+        }
+        builder = builder.tls_backend_preconfigured(self.tls_config(pins)?);
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+
+        let client = builder.build().map_err(|e| invalid("HTTP client", e))?;
+        Ok(ReqwestClient::from_client(client))
+    }
+
+    /// rustls configuration trusting the platform roots plus the extra
+    /// bundles, enforcing `pins` while verifying the server certificate.
+    fn tls_config(&self, pins: Pins) -> Result<rustls::ClientConfig> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let mut extra_roots = Vec::new();
+        for path in &self.root_certificates {
+            for certificate in CertificateDer::pem_slice_iter(&read(path)?) {
+                extra_roots.push(certificate.map_err(|e| invalid_tls("root certificates", e))?);
+            }
+        }
+        let verifier = PinningVerifier {
+            // A host without system roots can still reach servers under the
+            // extra roots; anything else fails in the handshake.
+            roots: rustls_platform_verifier::Verifier::new_with_extra_roots(
+                extra_roots,
+                Arc::clone(&provider),
+            ),
+            pins,
+            algorithms: provider.signature_verification_algorithms,
+        };
+
+        let config = rustls::ClientConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| invalid_tls("TLS", e))?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier));
+        match &self.identity {
+            None => Ok(config.with_no_client_auth()),
+            Some(identity) => {
+                let (chain, key) = identity.load()?;
+                config
+                    .with_client_auth_cert(chain, key)
+                    .map_err(|e| invalid_tls("client identity", e))
+            }
+        }
+    }
+}
+
+impl ClientIdentity {
+    fn load(&self) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+        match self {
+            Self::Pem { certificate, key } => {
+                let chain = CertificateDer::pem_slice_iter(&read(certificate)?)
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|e| invalid_tls("client identity", e))?;
+                let key = PrivateKeyDer::from_pem_slice(&read(key)?)
+                    .map_err(|e| invalid_tls("client identity", e))?;
+                Ok((chain, key))
+            }
+            Self::Pkcs12 { archive, password } => {
+                let store = p12_keystore::KeyStore::from_pkcs12(&read(archive)?, password)
+                    .map_err(|e| invalid_tls("client identity", e))?;
+                let (_, entry) = store.private_key_chain().ok_or_else(|| {
+                    Error::InvalidState(
+                        "Invalid client identity configuration: no private key in archive"
+                            .to_string(),
+                    )
+                })?;
+                let chain = entry
+                    .chain()
+                    .iter()
+                    .map(|certificate| CertificateDer::from(certificate.as_der().to_vec()))
+                    .collect();
+                let key = PrivatePkcs8KeyDer::from(entry.key().to_vec()).into();
+                Ok((chain, key))
+            }
+        }
+    }
+}
+
+/// Handshake rejection for a certificate whose key is not pinned for its host.
+#[derive(Debug, thiserror::Error)]
+#[error("certificate presented by {host} does not match a pinned public key")]
+pub(crate) struct PinMismatch {
+    pub(crate) host: String,
+}
+
+/// Verifies the server chain against the trust roots, then the host's pins.
+#[derive(Debug)]
+struct PinningVerifier {
+    roots: std::result::Result<rustls_platform_verifier::Verifier, rustls::Error>,
+    pins: Pins,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let verified = self
+            .roots
+            .as_ref()
+            .map_err(Clone::clone)?
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+
+        let host = match server_name {
+            ServerName::DnsName(name) => name.as_ref().to_ascii_lowercase(),
+            ServerName::IpAddress(address) => std::net::IpAddr::from(*address).to_string(),
+            _ => return Ok(verified),
+        };
+        let Some(pins) = self.pins.get(&host) else {
+            return Ok(verified);
+        };
+        if spki_sha256(end_entity).is_some_and(|digest| pins.contains(&digest)) {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::InvalidCertificate(CertificateError::Other(
+                OtherError(Arc::new(PinMismatch { host })),
+            )))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, certificate, signature, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, certificate, signature, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+/// SHA-256 of the DER `SubjectPublicKeyInfo` in a DER certificate.
+fn spki_sha256(certificate: &[u8]) -> Option<[u8; 32]> {
+    use sha2::{Digest, Sha256};
+    use x509_cert::der::{Decode, Encode};
+
+    let certificate = x509_cert::Certificate::from_der(certificate).ok()?;
+    let spki = certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .ok()?;
+    Some(Sha256::digest(spki).into())
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|source| {
+        Error::Fs(pulith_fs::Error::Read {
+            path: path.to_path_buf(),
+            source,
+        })
+    })
+}
+
+fn invalid(what: &str, error: reqwest::Error) -> Error {
+    Error::InvalidState(format!("Invalid {what} configuration: {error}"))
+}
+
+fn invalid_tls(what: &str, error: impl std::fmt::Display) -> Error {
+    Error::InvalidState(format!("Invalid {what} configuration: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy_settings() {
+        let builder = ReqwestClientBuilder::new()
+            .with_no_proxy("ignored")
+            .with_proxy("http://proxy:3128")
+            .with_no_proxy("localhost");
+        assert_eq!(
+            builder.proxy,
+            ProxySettings::Explicit {
+                url: "http://proxy:3128".to_string(),
+                no_proxy: Some("localhost".to_string()),
+            }
+        );
+        assert_eq!(builder.without_proxy().proxy, ProxySettings::Direct);
+        assert_eq!(
+            ReqwestClientBuilder::new().proxy,
+            ProxySettings::Environment
+        );
+    }
+
+    #[test]
+    fn test_build_rejects_bad_configuration() {
+        let error = ReqwestClientBuilder::new()
+            .with_pinned_key("example.com", "abcd")
+            .build()
+            .err()
+            .unwrap();
+        assert!(
+            error
+                .to_string()
+                .contains("certificate pin for example.com")
+        );
+
+        let error = ReqwestClientBuilder::new()
+            .with_root_certificates("/nonexistent/root-ca.pem")
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(error, Error::Fs(_)));
+    }
+
+    #[test]
+    fn test_debug_redacts_pkcs12_password() {
+        let builder =
+            ReqwestClientBuilder::new().with_client_identity_pkcs12("/etc/client.p12", "hunter2");
+        let debug = format!("{builder:?}");
+        assert!(debug.contains("client.p12"));
+        assert!(!debug.contains("hunter2"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Transport-level request and response facts.
+//!
+//! [`ResponseHead`] carries what a server actually said about a response —
+//! status, headers and the URL after redirects — so fetch logic can act on
+//! validators, range support and retry hints instead of guessing.
+
+use std::time::{Duration, SystemTime};
+
+use bytes::Bytes;
+
+use crate::fetch::conditional::RemoteMetadata;
+use crate::net::http::BoxStream;
+
+/// HTTP request method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Head,
+}
+
+/// Status, headers and final URL of an HTTP response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseHead {
+    pub status: u16,
+    /// Header names are lowercase; repeated headers keep every value in order.
+    pub headers: Vec<(String, String)>,
+    /// URL that produced the response, after following redirects.
+    pub url: String,
+}
+
+impl ResponseHead {
+    pub fn new(status: u16, url: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            url: url.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_header(mut self, name: impl AsRef<str>, value: impl Into<String>) -> Self {
+        self.headers
+            .push((name.as_ref().to_ascii_lowercase(), value.into()));
+        self
+    }
+
+    /// First value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the status is 2xx.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn content_length(&self) -> Option<u64> {
+        self.header("content-length")?.trim().parse().ok()
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.header("etag")
+    }
+
+    pub fn last_modified(&self) -> Option<&str> {
+        self.header("last-modified")
+    }
+
+    /// Whether the server advertises byte range support.
+    pub fn accepts_ranges(&self) -> bool {
+        self.header("accept-ranges")
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("bytes"))
+    }
+
+    /// Byte range of a partial response as `(first, last, complete_length)`.
+    pub fn content_range(&self) -> Option<(u64, u64, Option<u64>)> {
+        let value = self.header("content-range")?.trim();
+        let (range, complete) = value.strip_prefix("bytes ")?.split_once('/')?;
+        let (first, last) = range.split_once('-')?;
+        let complete = match complete.trim() {
+            "*" => None,
+            length => Some(length.parse().ok()?),
+        };
+        Some((
+            first.trim().parse().ok()?,
+            last.trim().parse().ok()?,
+            complete,
+        ))
+    }
+
+    /// Delay requested by `Retry-After`, as seconds or an HTTP date.
+    pub fn retry_after(&self) -> Option<Duration> {
+        let value = self.header("retry-after")?.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+        let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+        let at = SystemTime::from(at);
+        Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+    }
+
+    /// File name suggested by `Content-Disposition`, preferring `filename*`.
+    ///
+    /// Directory components are stripped so the name is safe to join onto a
+    /// destination directory.
+    pub fn content_disposition_filename(&self) -> Option<String> {
+        let value = self.header("content-disposition")?;
+        let mut plain = None;
+        let mut extended = None;
+        for parameter in value.split(';').skip(1) {
+            let Some((key, raw)) = parameter.split_once('=') else {
+                continue;
+            };
+            let raw = raw.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "filename*" => {
+                    // RFC 5987: charset'language'percent-encoded
+                    extended = raw.splitn(3, '\'').nth(2).and_then(percent_decode);
+                }
+                "filename" => plain = Some(raw.trim_matches('"').to_string()),
+                _ => {}
+            }
+        }
+        let name = extended.or(plain)?;
+        let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+        (!name.is_empty() && name != "." && name != "..").then(|| name.to_string())
+    }
+
+    /// Cache validators and directives from this response.
+    pub fn metadata(&self) -> RemoteMetadata {
+        RemoteMetadata {
+            etag: self.etag().map(str::to_string),
+            last_modified: self.last_modified().map(str::to_string),
+            content_length: self.content_length(),
+            cache_control: self.header("cache-control").map(str::to_string),
+        }
+    }
+}
+
+/// An HTTP response whose body has not been read yet.
+pub struct HttpResponse<E> {
+    pub head: ResponseHead,
+    pub body: BoxStream<'static, std::result::Result<Bytes, E>>,
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = value.get(index + 1..index + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_head_headers() {
+        let head = ResponseHead::new(206, "https://cdn.example.com/tool.tar.gz")
+            .with_header("Content-Length", "42")
+            .with_header("ETag", "\"v1\"")
+            .with_header("Accept-Ranges", "bytes")
+            .with_header("Cache-Control", "max-age=60");
+
+        assert!(head.is_success());
+        assert!(!ResponseHead::new(404, "https://example.com").is_success());
+        assert_eq!(head.header("etag"), Some("\"v1\""));
+        assert_eq!(head.content_length(), Some(42));
+        assert!(head.accepts_ranges());
+        assert_eq!(head.metadata().cache_control.as_deref(), Some("max-age=60"));
+        assert_eq!(head.metadata().etag.as_deref(), Some("\"v1\""));
+    }
+
+    #[test]
+    fn test_content_range() {
+        let head = |value: &str| ResponseHead::new(206, "u").with_header("Content-Range", value);
+        assert_eq!(
+            head("bytes 100-199/1000").content_range(),
+            Some((100, 199, Some(1000)))
+        );
+        assert_eq!(head("bytes 0-9/*").content_range(), Some((0, 9, None)));
+        assert_eq!(head("bytes */1000").content_range(), None);
+        assert_eq!(ResponseHead::new(200, "u").content_range(), None);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let head = |value: &str| ResponseHead::new(503, "u").with_header("Retry-After", value);
+        assert_eq!(head("120").retry_after(), Some(Duration::from_secs(120)));
+        assert_eq!(
+            head("Sun, 06 Nov 1994 08:49:37 GMT").retry_after(),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(head("soon").retry_after(), None);
+    }
+
+    #[test]
+    fn test_content_disposition_filename() {
+        let head = |value: &str| {
+            ResponseHead::new(200, "u")
+                .with_header("Content-Disposition", value)
+                .content_disposition_filename()
+        };
+        assert_eq!(
+            head("attachment; filename=\"tool-1.0.tar.gz\"").as_deref(),
+            Some("tool-1.0.tar.gz")
+        );
+        assert_eq!(
+            head("attachment; filename=\"fallback.zip\"; filename*=UTF-8''t%C3%B6%C3%B6l.zip")
+                .as_deref(),
+            Some("tööl.zip")
+        );
+        assert_eq!(
+            head("attachment; filename=\"../../etc/passwd\"").as_deref(),
+            Some("passwd")
+        );
+        assert_eq!(head("inline"), None);
+    }
+}
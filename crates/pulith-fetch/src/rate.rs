@@ -4,6 +4,6 @@ pub mod backoff;
 pub mod bandwidth;
 pub mod throttled;
 
-pub use backoff::retry_delay;
+pub use backoff::{jittered_delay, retry_delay};
 pub use bandwidth::{AdaptiveConfig, RateMetrics, TokenBucket};
 pub use throttled::{AsyncThrottledStream, ThrottledStream};
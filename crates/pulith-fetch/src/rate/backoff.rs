@@ -35,6 +35,32 @@ pub fn retry_delay(retry_count: u32, base: Duration) -> Duration {
     base.saturating_mul(multiplier)
 }
 
+/// Add a random extra delay of up to `max_jitter` to `delay`.
+///
+/// Jitter spreads out retries from many clients that failed at the same
+/// moment. A zero `max_jitter` returns `delay` unchanged.
+pub fn jittered_delay(delay: Duration, max_jitter: Duration) -> Duration {
+    if max_jitter.is_zero() {
+        return delay;
+    }
+    let span = max_jitter.as_nanos().min(u64::MAX as u128) as u64;
+    delay.saturating_add(Duration::from_nanos(random_u64() % (span + 1)))
+}
+
+/// Per-call random value from the std hasher's randomly seeded keys.
+fn random_u64() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,6 +91,16 @@ mod tests {
         assert_eq!(retry_delay(2, base), Duration::from_secs(4));
     }
 
+    #[test]
+    fn test_jittered_delay_bounds() {
+        let delay = Duration::from_millis(100);
+        assert_eq!(jittered_delay(delay, Duration::ZERO), delay);
+        for _ in 0..32 {
+            let jittered = jittered_delay(delay, Duration::from_millis(50));
+            assert!(jittered >= delay && jittered <= Duration::from_millis(150));
+        }
+    }
+
     #[test]
     fn test_retry_delay_zero_base() {
         let base = Duration::from_millis(0);
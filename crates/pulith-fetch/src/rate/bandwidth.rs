@@ -19,6 +19,17 @@ pub struct TokenBucket {
     adaptive_config: Arc<AdaptiveConfig>,
     metrics: Arc<RateMetrics>,
     congestion_state: AtomicU8, // 0: normal, 1: congestion, 2: recovery
+    adaptive: bool,
+}
+
+impl std::fmt::Debug for TokenBucket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenBucket")
+            .field("capacity", &self.capacity)
+            .field("refill_rate", &self.current_rate())
+            .field("adaptive", &self.adaptive)
+            .finish()
+    }
 }
 
 /// Configuration for adaptive rate limiting
@@ -186,6 +197,7 @@ impl TokenBucket {
             adaptive_config: Arc::new(AdaptiveConfig::default()),
             metrics: Arc::new(RateMetrics::default()),
             congestion_state: AtomicU8::new(CongestionState::Normal as u8),
+            adaptive: false,
         }
     }
 
@@ -206,18 +218,29 @@ impl TokenBucket {
             adaptive_config: Arc::new(config),
             metrics: Arc::new(RateMetrics::default()),
             congestion_state: AtomicU8::new(CongestionState::Normal as u8),
+            adaptive: true,
         }
     }
 
     /// Acquire the specified number of tokens, waiting if necessary.
     ///
-    /// This method will block until enough tokens are available.
+    /// This method will block until enough tokens are available. Requests
+    /// larger than the bucket capacity are acquired one full bucket at a time.
     ///
     /// # Arguments
     ///
     /// * `bytes` - Number of tokens (bytes) to acquire
     pub async fn acquire(&self, bytes: usize) {
-        let tokens_needed = bytes as u64;
+        let mut remaining = bytes as u64;
+        let piece_size = self.capacity.max(1);
+        while remaining > piece_size {
+            self.acquire_tokens(piece_size).await;
+            remaining -= piece_size;
+        }
+        self.acquire_tokens(remaining).await;
+    }
+
+    async fn acquire_tokens(&self, tokens_needed: u64) {
         let start_time = Instant::now();
 
         loop {
@@ -321,6 +344,17 @@ impl TokenBucket {
         self.refill_rate.load(Ordering::Relaxed)
     }
 
+    /// Get the maximum number of tokens the bucket can hold.
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Whether the bucket was created with [`TokenBucket::new_adaptive`] and
+    /// should have its rate adjusted with [`TokenBucket::check_and_adjust_rate`].
+    pub fn is_adaptive(&self) -> bool {
+        self.adaptive
+    }
+
     /// Check network conditions and adjust the refill rate accordingly.
     pub fn check_and_adjust_rate(&self) {
         let config = &self.adaptive_config;
@@ -439,6 +473,21 @@ mod tests {
         assert!(elapsed <= Duration::from_millis(550));
     }
 
+    #[tokio::test]
+    async fn test_token_bucket_acquires_more_than_capacity() {
+        let bucket = TokenBucket::new(100, 1000);
+
+        let start = Instant::now();
+        tokio::time::timeout(Duration::from_secs(2), bucket.acquire(250))
+            .await
+            .expect("oversized acquisition must not wait forever");
+
+        // 100 bytes up front, the remaining 150 bytes at 1000 bytes/second.
+        assert!(start.elapsed() >= Duration::from_millis(140));
+        assert!(!TokenBucket::new(100, 1000).is_adaptive());
+        assert!(TokenBucket::new_adaptive(100, 1000, AdaptiveConfig::default()).is_adaptive());
+    }
+
     #[tokio::test]
     async fn test_token_bucket_refill() {
         let bucket = TokenBucket::new(100, 100);
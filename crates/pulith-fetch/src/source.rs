@@ -0,0 +1,12 @@
+//! Source adapters that need network access to expand.
+//!
+//! `pulith-source` keeps adapters synchronous and transport-free; adapters
+//! here load their upstream listings through an [`crate::HttpClient`] first
+//! and then expand definitions from that snapshot. Version indexes resolve
+//! requested versions and channel aliases before any source is planned.
+
+pub mod github;
+pub mod index;
+
+pub use github::{GithubAsset, GithubRelease, GithubReleaseAdapter};
+pub use index::{IndexedVersion, JsonVersionIndex, VersionIndex};
@@ -0,0 +1,573 @@
+//! GitHub Releases source adapter.
+//!
+//! Expands an `owner/repo` and an asset-name template such as
+//! `tool-{version}-{os}-{arch}.tar.gz` into the download URL of the matching
+//! release asset.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use pulith_platform::arch::TargetTriple;
+use pulith_resource::{ResolvedResource, ValidUrl, VersionSelector};
+use pulith_source::{
+    HttpAssetSource, RemoteSource, SourceAdapter, SourceDefinition, SourceError, SourceSet,
+};
+use pulith_version::{VersionKind, select_preferred};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::net::credentials::REDACTED;
+use crate::net::http::{HttpClient, read_body};
+
+const DEFAULT_API_BASE: &str = "https://api.github.com";
+const PAGE_SIZE: usize = 100;
+
+/// A release as returned by the GitHub releases API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GithubRelease {
+    pub tag_name: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub assets: Vec<GithubAsset>,
+}
+
+/// A downloadable file attached to a release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GithubAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// Digest published by GitHub, e.g. `sha256:<hex>`.
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+/// Source adapter for GitHub release assets.
+///
+/// Load the release listing with [`GithubReleaseAdapter::load_releases`]
+/// before expanding. Placeholders in the asset template are `{version}`,
+/// `{tag}`, `{os}`, `{arch}` and `{triple}`; the platform values come from the
+/// configured [`TargetTriple`] and can be overridden with
+/// [`GithubReleaseAdapter::with_placeholder`] for projects that name
+/// platforms differently (e.g. `amd64`).
+#[derive(Clone)]
+pub struct GithubReleaseAdapter {
+    owner: String,
+    repo: String,
+    asset_template: String,
+    selector: VersionSelector,
+    target: TargetTriple,
+    tag_prefix: String,
+    include_prereleases: bool,
+    placeholders: BTreeMap<String, String>,
+    api_base: String,
+    token: Option<String>,
+    releases: Vec<GithubRelease>,
+}
+
+impl fmt::Debug for GithubReleaseAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GithubReleaseAdapter")
+            .field("owner", &self.owner)
+            .field("repo", &self.repo)
+            .field("asset_template", &self.asset_template)
+            .field("selector", &self.selector)
+            .field("target", &self.target)
+            .field("tag_prefix", &self.tag_prefix)
+            .field("include_prereleases", &self.include_prereleases)
+            .field("placeholders", &self.placeholders)
+            .field("api_base", &self.api_base)
+            .field("token", &self.token.as_ref().map(|_| REDACTED))
+            .field("releases", &self.releases.len())
+            .finish()
+    }
+}
+
+impl GithubReleaseAdapter {
+    /// Create an adapter for the host platform that selects the latest release.
+    pub fn new(
+        owner: impl Into<String>,
+        repo: impl Into<String>,
+        asset_template: impl Into<String>,
+    ) -> Self {
+        Self {
+            owner: owner.into(),
+            repo: repo.into(),
+            asset_template: asset_template.into(),
+            selector: VersionSelector::Unspecified,
+            target: TargetTriple::host(),
+            tag_prefix: "v".to_string(),
+            include_prereleases: false,
+            placeholders: BTreeMap::new(),
+            api_base: DEFAULT_API_BASE.to_string(),
+            token: None,
+            releases: Vec::new(),
+        }
+    }
+
+    /// Select releases with the given version selector.
+    #[must_use]
+    pub fn with_selector(mut self, selector: VersionSelector) -> Self {
+        self.selector = selector;
+        self
+    }
+
+    /// Expand assets for the given target instead of the host.
+    #[must_use]
+    pub fn with_target(mut self, target: TargetTriple) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Strip this prefix from tags to obtain versions (default `v`).
+    #[must_use]
+    pub fn with_tag_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.tag_prefix = prefix.into();
+        self
+    }
+
+    /// Consider releases marked as pre-release on GitHub.
+    #[must_use]
+    pub fn with_prereleases(mut self, include: bool) -> Self {
+        self.include_prereleases = include;
+        self
+    }
+
+    /// Override or add a template placeholder value.
+    #[must_use]
+    pub fn with_placeholder(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.placeholders.insert(name.into(), value.into());
+        self
+    }
+
+    /// Use a different API endpoint, e.g. GitHub Enterprise or a test server.
+    #[must_use]
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Authenticate API requests with a token.
+    #[must_use]
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Use an already known release listing instead of loading one.
+    #[must_use]
+    pub fn with_releases(mut self, releases: Vec<GithubRelease>) -> Self {
+        self.releases = releases;
+        self
+    }
+
+    /// Get the loaded releases.
+    pub fn releases(&self) -> &[GithubRelease] {
+        &self.releases
+    }
+
+    /// Fetch the full release listing, following pages until a short page.
+    pub async fn load_releases<C: HttpClient>(&mut self, client: &C) -> Result<&[GithubRelease]> {
+        let mut headers = vec![
+            (
+                "Accept".to_string(),
+                "application/vnd.github+json".to_string(),
+            ),
+            ("User-Agent".to_string(), "pulith-fetch".to_string()),
+        ];
+        if let Some(token) = &self.token {
+            headers.push(("Authorization".to_string(), format!("Bearer {token}")));
+        }
+
+        let mut releases = Vec::new();
+        for page in 1.. {
+            let url = format!(
+                "{}/repos/{}/{}/releases?per_page={PAGE_SIZE}&page={page}",
+                self.api_base, self.owner, self.repo
+            );
+            let body = read_body(client, &url, &headers).await?;
+            let batch: Vec<GithubRelease> = serde_json::from_slice(&body).map_err(|e| {
+                Error::InvalidState(format!("Invalid GitHub release listing from {url}: {e}"))
+            })?;
+            let done = batch.len() < PAGE_SIZE;
+            releases.extend(batch);
+            if done {
+                break;
+            }
+        }
+
+        self.releases = releases;
+        Ok(&self.releases)
+    }
+
+    /// Version of a release: its tag without the configured prefix.
+    pub fn release_version<'a>(&self, release: &'a GithubRelease) -> &'a str {
+        release
+            .tag_name
+            .strip_prefix(self.tag_prefix.as_str())
+            .unwrap_or(&release.tag_name)
+    }
+
+    /// Pick the release preferred by the configured selector.
+    ///
+    /// Drafts and tags that do not parse as versions are ignored, as are
+    /// pre-releases unless enabled or pinned exactly.
+    pub fn select_release(&self) -> Result<Option<&GithubRelease>> {
+        let policy = self
+            .selector
+            .selection_policy()
+            .map_err(|e| Error::InvalidState(format!("Invalid version selector: {e}")))?;
+        let pinned = matches!(self.selector, VersionSelector::Exact(_));
+
+        let candidates = self
+            .releases
+            .iter()
+            .filter(|release| !release.draft)
+            .filter(|release| pinned || self.include_prereleases || !release.prerelease)
+            .filter_map(|release| {
+                VersionKind::parse(self.release_version(release))
+                    .ok()
+                    .map(|version| (release, version))
+            })
+            .collect::<Vec<_>>();
+        let versions = candidates
+            .iter()
+            .map(|(_, version)| version.clone())
+            .collect::<Vec<_>>();
+
+        Ok(select_preferred(&versions, &policy).and_then(|selected| {
+            candidates
+                .iter()
+                .find(|(_, version)| version == selected)
+                .map(|(release, _)| *release)
+        }))
+    }
+
+    /// Render the asset name for a release.
+    pub fn asset_name(&self, release: &GithubRelease) -> String {
+        let mut values = BTreeMap::from([
+            ("version", self.release_version(release).to_string()),
+            ("tag", release.tag_name.clone()),
+            ("os", self.target.os_to_str().to_string()),
+            ("arch", self.target.arch_to_str().to_string()),
+            ("triple", self.target.to_string()),
+        ]);
+        for (name, value) in &self.placeholders {
+            values.insert(name.as_str(), value.clone());
+        }
+
+        values
+            .iter()
+            .fold(self.asset_template.clone(), |name, (placeholder, value)| {
+                name.replace(&format!("{{{placeholder}}}"), value)
+            })
+    }
+
+    /// Expand a release into its matching asset download.
+    pub fn release_sources(&self, release: &GithubRelease) -> pulith_source::Result<SourceSet> {
+        let name = self.asset_name(release);
+        let asset = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| SourceError::AssetNotFound {
+                release: release.tag_name.clone(),
+                asset: name.clone(),
+            })?;
+        let url = ValidUrl::parse(&asset.browser_download_url).map_err(|_| {
+            SourceError::AssetNotFound {
+                release: release.tag_name.clone(),
+                asset: name.clone(),
+            }
+        })?;
+
+        SourceSet::new(vec![SourceDefinition::Remote(RemoteSource::HttpAsset(
+            HttpAssetSource {
+                url,
+                file_name: Some(name),
+            },
+        ))])
+    }
+}
+
+impl SourceAdapter for GithubReleaseAdapter {
+    /// Expand to the asset of the release whose version equals the resolved
+    /// version of `resource`; the incoming definition is replaced.
+    fn expand(
+        &self,
+        resource: &ResolvedResource,
+        _definition: &SourceDefinition,
+    ) -> pulith_source::Result<SourceSet> {
+        let version = resource.version().as_str();
+        let release = self
+            .releases
+            .iter()
+            .filter(|release| !release.draft)
+            .find(|release| release.tag_name == version || self.release_version(release) == version)
+            .ok_or_else(|| SourceError::ReleaseNotFound(version.to_string()))?;
+
+        self.release_sources(release)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::http::BoxStream;
+    use bytes::Bytes;
+    use pulith_platform::arch::Arch;
+    use pulith_platform::os::OS;
+    use pulith_resource::{
+        RequestedResource, ResolvedVersion, ResourceId, ResourceLocator, ResourceSpec,
+    };
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct MockError(String);
+
+    impl std::fmt::Display for MockError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::error::Error for MockError {}
+
+    type Headers = Vec<(String, String)>;
+
+    struct MockHttpClient {
+        pages: Vec<String>,
+        requests: Mutex<Vec<(String, Headers)>>,
+    }
+
+    impl HttpClient for MockHttpClient {
+        type Error = MockError;
+
+        async fn stream(
+            &self,
+            url: &str,
+            headers: &[(String, String)],
+        ) -> std::result::Result<
+            BoxStream<'static, std::result::Result<Bytes, Self::Error>>,
+            Self::Error,
+        > {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push((url.to_string(), headers.to_vec()));
+            let page = self
+                .pages
+                .get(requests.len() - 1)
+                .cloned()
+                .ok_or_else(|| MockError(format!("unexpected request {url}")))?;
+            Ok(Box::pin(futures_util::stream::iter(vec![Ok(Bytes::from(
+                page,
+            ))])))
+        }
+
+        async fn head(&self, _url: &str) -> std::result::Result<Option<u64>, Self::Error> {
+            Ok(None)
+        }
+    }
+
+    fn release(tag: &str, prerelease: bool) -> GithubRelease {
+        GithubRelease {
+            tag_name: tag.to_string(),
+            name: None,
+            draft: false,
+            prerelease,
+            assets: ["linux-x86_64", "darwin-aarch64"]
+                .iter()
+                .map(|platform| {
+                    let name = format!("tool-{}-{platform}.tar.gz", tag.trim_start_matches('v'));
+                    GithubAsset {
+                        browser_download_url: format!(
+                            "https://github.com/example/tool/releases/download/{tag}/{name}"
+                        ),
+                        name,
+                        size: None,
+                        digest: None,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn linux_x86_64() -> TargetTriple {
+        TargetTriple {
+            arch: Arch::X86_64,
+            vendor: "unknown".to_string(),
+            os: OS::Linux,
+            env: None,
+        }
+    }
+
+    fn adapter() -> GithubReleaseAdapter {
+        GithubReleaseAdapter::new("example", "tool", "tool-{version}-{os}-{arch}.tar.gz")
+            .with_target(linux_x86_64())
+            .with_releases(vec![
+                release("v2.0.0-rc.1", true),
+                release("v1.2.0", false),
+                release("v1.10.0", false),
+                release("nightly", true),
+            ])
+    }
+
+    fn selected_tag(adapter: &GithubReleaseAdapter) -> Option<String> {
+        adapter
+            .select_release()
+            .unwrap()
+            .map(|release| release.tag_name.clone())
+    }
+
+    #[test]
+    fn test_select_release_honours_selector_and_prereleases() {
+        assert_eq!(selected_tag(&adapter()), Some("v1.10.0".to_string()));
+        assert_eq!(
+            selected_tag(&adapter().with_selector(VersionSelector::requirement("~1.2").unwrap())),
+            Some("v1.2.0".to_string())
+        );
+        assert_eq!(
+            selected_tag(&adapter().with_prereleases(true)),
+            Some("v2.0.0-rc.1".to_string())
+        );
+        assert_eq!(
+            selected_tag(&adapter().with_selector(VersionSelector::exact("2.0.0-rc.1").unwrap())),
+            Some("v2.0.0-rc.1".to_string())
+        );
+        assert_eq!(
+            selected_tag(&adapter().with_selector(VersionSelector::requirement(">=3").unwrap())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_release_sources_render_template_for_target() {
+        let adapter = adapter();
+        let release = adapter.select_release().unwrap().unwrap();
+        let sources = adapter.release_sources(release).unwrap();
+
+        assert_eq!(
+            sources.entries(),
+            [SourceDefinition::Remote(RemoteSource::HttpAsset(
+                HttpAssetSource {
+                    url: ValidUrl::parse(
+                        "https://github.com/example/tool/releases/download/v1.10.0/tool-1.10.0-linux-x86_64.tar.gz"
+                    )
+                    .unwrap(),
+                    file_name: Some("tool-1.10.0-linux-x86_64.tar.gz".to_string()),
+                }
+            ))]
+        );
+
+        let renamed = adapter.clone().with_placeholder("arch", "amd64");
+        assert_eq!(
+            renamed.release_sources(release),
+            Err(SourceError::AssetNotFound {
+                release: "v1.10.0".to_string(),
+                asset: "tool-1.10.0-linux-amd64.tar.gz".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_source_adapter_expands_resolved_version() {
+        let adapter = adapter().with_target(TargetTriple {
+            arch: Arch::ARM64,
+            vendor: "apple".to_string(),
+            os: OS::MacOS,
+            env: None,
+        });
+        let spec = ResourceSpec::new(
+            ResourceId::parse("github.com/example/tool").unwrap(),
+            ResourceLocator::Url(ValidUrl::parse("https://github.com/example/tool").unwrap()),
+        );
+        let definition = SourceDefinition::Remote(RemoteSource::HttpAsset(HttpAssetSource {
+            url: ValidUrl::parse("https://github.com/example/tool").unwrap(),
+            file_name: None,
+        }));
+        let resolve = |version: &str| {
+            RequestedResource::new(spec.clone()).resolve(
+                ResolvedVersion::new(version).unwrap(),
+                pulith_resource::ResolvedLocator::Url(
+                    ValidUrl::parse("https://github.com/example/tool").unwrap(),
+                ),
+                None,
+            )
+        };
+
+        let expanded = adapter.expand(&resolve("1.2.0"), &definition).unwrap();
+        assert_eq!(
+            expanded.entries()[0].resolved_candidates()[0],
+            pulith_source::ResolvedSourceCandidate::Url(
+                ValidUrl::parse(
+                    "https://github.com/example/tool/releases/download/v1.2.0/tool-1.2.0-darwin-aarch64.tar.gz"
+                )
+                .unwrap()
+            )
+        );
+        assert_eq!(
+            adapter.expand(&resolve("9.9.9"), &definition),
+            Err(SourceError::ReleaseNotFound("9.9.9".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_releases_follows_pages_and_sends_token() {
+        let full_page = serde_json::to_string(
+            &(0..PAGE_SIZE)
+                .map(|i| release(&format!("v0.0.{i}"), false))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let last_page = serde_json::to_string(&vec![release("v1.0.0", false)]).unwrap();
+        let client = MockHttpClient {
+            pages: vec![full_page, last_page],
+            requests: Mutex::new(Vec::new()),
+        };
+
+        let mut adapter = GithubReleaseAdapter::new("example", "tool", "tool-{version}")
+            .with_api_base("http://127.0.0.1:9/")
+            .with_token("secret");
+        assert!(!format!("{adapter:?}").contains("secret"));
+        let releases = adapter.load_releases(&client).await.unwrap();
+        assert_eq!(releases.len(), PAGE_SIZE + 1);
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(
+            requests
+                .iter()
+                .map(|(url, _)| url.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "http://127.0.0.1:9/repos/example/tool/releases?per_page=100&page=1",
+                "http://127.0.0.1:9/repos/example/tool/releases?per_page=100&page=2",
+            ]
+        );
+        assert!(
+            requests[0]
+                .1
+                .contains(&("Authorization".to_string(), "Bearer secret".to_string()))
+        );
+        assert_eq!(
+            adapter.select_release().unwrap().unwrap().tag_name,
+            "v1.0.0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_releases_rejects_invalid_listing() {
+        let client = MockHttpClient {
+            pages: vec![r#"{"message":"Not Found"}"#.to_string()],
+            requests: Mutex::new(Vec::new()),
+        };
+
+        let mut adapter = GithubReleaseAdapter::new("example", "missing", "tool");
+        let error = adapter.load_releases(&client).await.unwrap_err();
+        assert!(error.to_string().contains("Invalid GitHub release listing"));
+    }
+}
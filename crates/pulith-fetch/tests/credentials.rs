@@ -0,0 +1,125 @@
+//! Per-host credentials through `Fetcher` against an in-process HTTP stub.
+
+#![cfg(feature = "reqwest")]
+
+use std::sync::{Arc, Mutex};
+
+use pulith_fetch::{
+    CredentialChain, EnvTokenCredentials, Error, FetchOptions, FetchSource, Fetcher,
+    NetrcCredentials, ReqwestClient,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Serve `artifact` only to requests authorized as `ci:hunter2`; record the
+/// `Authorization` header of every request.
+async fn serve() -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let log = log.clone();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    if read == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                let authorization = request.lines().find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("authorization")
+                        .then(|| value.trim().to_string())
+                });
+                let authorized = authorization.as_deref() == Some("Basic Y2k6aHVudGVyMg==");
+                log.lock().unwrap().push(authorization);
+
+                let (status, body) = if authorized {
+                    ("200 OK", "artifact")
+                } else {
+                    ("401 Unauthorized", "denied")
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len()
+                );
+                socket.write_all(head.as_bytes()).await.unwrap();
+                socket.write_all(body.as_bytes()).await.unwrap();
+            });
+        }
+    });
+    (format!("http://{address}"), seen)
+}
+
+#[tokio::test]
+async fn fetcher_authorizes_requests_per_host() {
+    let (server, seen) = serve().await;
+    let temp = tempfile::tempdir().unwrap();
+    let fetcher = Fetcher::new(ReqwestClient::new().unwrap(), temp.path().join("workspace"));
+    let destination = temp.path().join("artifact");
+    let url = format!("{server}/artifact");
+
+    let error = fetcher
+        .fetch_with_receipt(&url, &destination, FetchOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Http { status: 401, .. }));
+
+    let credentials = CredentialChain::new()
+        .with_provider(EnvTokenCredentials::new().with_token("other.example", "CARGO_PKG_NAME"))
+        .with_provider(NetrcCredentials::parse(
+            "machine 127.0.0.1 login ci password hunter2\ndefault login nobody password x",
+        ));
+    let options = FetchOptions::default().credentials(Arc::new(credentials));
+    let receipt = fetcher
+        .fetch_with_receipt(&url, &destination, options.clone())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&destination).unwrap(), b"artifact");
+    assert!(!format!("{options:?}").contains("hunter2"));
+    assert!(!serde_json::to_string(&receipt).unwrap().contains("Y2k6"));
+
+    // An explicit header wins over the provider.
+    let error = fetcher
+        .fetch_with_receipt(
+            &url,
+            &destination,
+            options.header("Authorization", "Bearer caller"),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Http { status: 401, .. }));
+
+    // Each fetch sends a HEAD and a GET with the same credential.
+    let basic = Some("Basic Y2k6aHVudGVyMg==".to_string());
+    let caller = Some("Bearer caller".to_string());
+    assert_eq!(
+        seen.lock().unwrap().as_slice(),
+        [None, None, basic.clone(), basic, caller.clone(), caller]
+    );
+}
+
+#[tokio::test]
+async fn receipts_do_not_record_url_credentials() {
+    let (server, _) = serve().await;
+    let temp = tempfile::tempdir().unwrap();
+    let fetcher = Fetcher::new(ReqwestClient::new().unwrap(), temp.path().join("workspace"));
+    let destination = temp.path().join("artifact");
+    let url = server.replace("http://", "http://ci:hunter2@") + "/artifact";
+
+    let receipt = fetcher
+        .fetch_with_receipt(&url, &destination, FetchOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        receipt.source,
+        FetchSource::Url(format!("{server}/artifact"))
+    );
+}
@@ -0,0 +1,87 @@
+//! Git candidates planned by `pulith-source` against a local `file://` repository.
+
+#![cfg(feature = "reqwest")]
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use pulith_fetch::{FetchOptions, FetchSource, Fetcher, MultiSourceFetcher, ReqwestClient};
+use pulith_resource::ValidUrl;
+use pulith_source::{
+    GitSource, RemoteSource, SelectionStrategy, SourceDefinition, SourceSet, SourceSpec,
+};
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "user.name=pulith",
+            "-c",
+            "user.email=pulith@example.com",
+        ])
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[tokio::test]
+async fn planned_git_candidate_materializes_pinned_subpath() {
+    let temp = tempfile::tempdir().unwrap();
+    let upstream = temp.path().join("upstream");
+    std::fs::create_dir_all(upstream.join("share/completions")).unwrap();
+    git(&upstream, &["init", "-q", "-b", "main"]);
+    std::fs::write(
+        upstream.join("share/completions/tool.bash"),
+        "complete -F _tool tool\n",
+    )
+    .unwrap();
+    git(&upstream, &["add", "."]);
+    git(&upstream, &["commit", "-q", "-m", "release"]);
+    git(&upstream, &["tag", "v1.2.0"]);
+    let commit = git(&upstream, &["rev-parse", "HEAD"]);
+    std::fs::write(
+        upstream.join("share/completions/tool.bash"),
+        "# unreleased\n",
+    )
+    .unwrap();
+    git(&upstream, &["commit", "-q", "-am", "wip"]);
+
+    let url = format!("file://{}", upstream.display());
+    let planned = SourceSpec::new(
+        SourceSet::new(vec![SourceDefinition::Remote(RemoteSource::Git(
+            GitSource::new(ValidUrl::parse(&url).unwrap(), Some("v1.2.0".to_string()))
+                .unwrap()
+                .with_subpath("share/completions"),
+        ))])
+        .unwrap(),
+    )
+    .plan(SelectionStrategy::OrderedFallback);
+
+    let fetcher = Arc::new(Fetcher::new(
+        ReqwestClient::new().unwrap(),
+        temp.path().join("workspace"),
+    ));
+    let destination = temp.path().join("install/completions");
+    let receipt = MultiSourceFetcher::new(fetcher)
+        .fetch_planned_sources_with_receipt(&planned, &destination, &FetchOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(destination.join("tool.bash")).unwrap(),
+        "complete -F _tool tool\n"
+    );
+    assert_eq!(
+        receipt.source,
+        FetchSource::Git {
+            url,
+            rev: Some("v1.2.0".to_string()),
+            commit,
+        }
+    );
+}
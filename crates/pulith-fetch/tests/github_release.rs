@@ -0,0 +1,102 @@
+//! GitHub release adapter against a local stub of the releases API.
+
+#![cfg(feature = "reqwest")]
+
+use pulith_fetch::{GithubReleaseAdapter, ReqwestClient};
+use pulith_platform::arch::{Arch, TargetTriple};
+use pulith_platform::os::OS;
+use pulith_resource::VersionSelector;
+use pulith_source::ResolvedSourceCandidate;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const RELEASES: &str = r#"[
+  {
+    "tag_name": "v0.9.0",
+    "draft": false,
+    "prerelease": false,
+    "assets": [
+      {"name": "tool_0.9.0_windows_amd64.zip", "browser_download_url": "https://github.com/example/tool/releases/download/v0.9.0/tool_0.9.0_windows_amd64.zip", "size": 10}
+    ]
+  },
+  {
+    "tag_name": "v1.0.0",
+    "draft": false,
+    "prerelease": false,
+    "assets": [
+      {"name": "tool_1.0.0_windows_amd64.zip", "browser_download_url": "https://github.com/example/tool/releases/download/v1.0.0/tool_1.0.0_windows_amd64.zip", "size": 12, "digest": "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"},
+      {"name": "tool_1.0.0_linux_amd64.tar.gz", "browser_download_url": "https://github.com/example/tool/releases/download/v1.0.0/tool_1.0.0_linux_amd64.tar.gz", "size": 11}
+    ]
+  }
+]"#;
+
+/// Serve `body` to every request and report the request lines received.
+async fn serve(body: &'static str) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = socket.read(&mut buffer).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8(request)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    });
+    (format!("http://{address}"), handle)
+}
+
+#[tokio::test]
+async fn github_adapter_expands_assets_from_stub_api() {
+    let (api_base, server) = serve(RELEASES).await;
+    let mut adapter =
+        GithubReleaseAdapter::new("example", "tool", "tool_{version}_{os}_{arch}.zip")
+            .with_api_base(api_base)
+            .with_selector(VersionSelector::requirement("^1").unwrap())
+            .with_target(TargetTriple {
+                arch: Arch::X86_64,
+                vendor: "pc".to_string(),
+                os: OS::Windows,
+                env: Some("msvc".to_string()),
+            })
+            .with_placeholder("arch", "amd64");
+
+    let client = ReqwestClient::new().unwrap();
+    assert_eq!(adapter.load_releases(&client).await.unwrap().len(), 2);
+    let request = server.await.unwrap();
+    assert_eq!(
+        request[0],
+        "GET /repos/example/tool/releases?per_page=100&page=1 HTTP/1.1"
+    );
+
+    let release = adapter.select_release().unwrap().unwrap();
+    assert_eq!(release.tag_name, "v1.0.0");
+    assert_eq!(
+        release.assets[0].digest.as_deref(),
+        Some("sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+    );
+
+    let sources = adapter.release_sources(release).unwrap();
+    assert_eq!(
+        sources.entries()[0].resolved_candidates(),
+        [ResolvedSourceCandidate::Url(
+            "https://github.com/example/tool/releases/download/v1.0.0/tool_1.0.0_windows_amd64.zip"
+                .parse()
+                .unwrap()
+        )]
+    );
+}
@@ -0,0 +1,117 @@
+//! `ReqwestClient` response facts against an in-process HTTP stub.
+
+#![cfg(feature = "reqwest")]
+
+use futures_util::StreamExt;
+use pulith_fetch::{Error, FetchOptions, Fetcher, HttpClient, Method, ReqwestClient};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Serve a redirect, a named download and a 404.
+async fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    if read == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+                let (status, headers, body) = match path {
+                    "/latest" => ("302 Found", "location: /tool-1.2.0\r\n", ""),
+                    "/tool-1.2.0" => (
+                        "200 OK",
+                        "etag: \"1.2.0\"\r\naccept-ranges: bytes\r\ncontent-disposition: attachment; filename=\"tool-1.2.0.tar.gz\"\r\n",
+                        "tool bytes",
+                    ),
+                    _ => ("404 Not Found", "", "no such file"),
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\n{headers}content-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len()
+                );
+                socket.write_all(head.as_bytes()).await.unwrap();
+                if !request.starts_with("HEAD ") {
+                    socket.write_all(body.as_bytes()).await.unwrap();
+                }
+            });
+        }
+    });
+    format!("http://{address}")
+}
+
+#[tokio::test]
+async fn reqwest_client_reports_status_headers_and_final_url() {
+    let server = serve().await;
+    let client = ReqwestClient::new().unwrap();
+
+    let response = client
+        .send(Method::Get, &format!("{server}/latest"), &[])
+        .await
+        .unwrap();
+    assert_eq!(response.head.status, 200);
+    assert_eq!(response.head.url, format!("{server}/tool-1.2.0"));
+    assert_eq!(response.head.etag(), Some("\"1.2.0\""));
+    assert!(response.head.accepts_ranges());
+    assert_eq!(
+        response.head.content_disposition_filename().as_deref(),
+        Some("tool-1.2.0.tar.gz")
+    );
+    let body = response
+        .body
+        .map(|chunk| chunk.unwrap().to_vec())
+        .concat()
+        .await;
+    assert_eq!(body, b"tool bytes");
+
+    let head = client
+        .send(Method::Head, &format!("{server}/tool-1.2.0"), &[])
+        .await
+        .unwrap();
+    assert_eq!(head.head.content_length(), Some(10));
+
+    let missing = client
+        .send(Method::Get, &format!("{server}/missing"), &[])
+        .await
+        .unwrap();
+    assert_eq!(missing.head.status, 404);
+}
+
+#[tokio::test]
+async fn fetcher_does_not_place_error_pages() {
+    let server = serve().await;
+    let temp = tempfile::tempdir().unwrap();
+    let fetcher = Fetcher::new(ReqwestClient::new().unwrap(), temp.path().join("workspace"));
+    let destination = temp.path().join("tool.tar.gz");
+
+    let error = fetcher
+        .fetch_with_receipt(
+            &format!("{server}/missing"),
+            &destination,
+            FetchOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Http { status: 404, .. }));
+    assert!(!destination.exists());
+
+    fetcher
+        .fetch_with_receipt(
+            &format!("{server}/latest"),
+            &destination,
+            FetchOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&destination).unwrap(), b"tool bytes");
+}
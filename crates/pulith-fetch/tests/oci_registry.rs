@@ -0,0 +1,133 @@
+//! OCI artifact pulls against an in-process registry stub.
+
+#![cfg(feature = "reqwest")]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pulith_fetch::{FetchOptions, FetchSource, Fetcher, OciBearerToken, OciFetcher, ReqwestClient};
+use pulith_resource::ValidUrl;
+use pulith_source::{OciReference, OciSource};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const TOKEN: &str = "Bearer registry-token";
+const MEDIA_TYPE: &str = "application/vnd.example.toolchain.tar+gzip";
+
+fn digest(data: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(data)))
+}
+
+/// Serve `routes` by request path, answering 401 without the expected token.
+async fn serve(routes: HashMap<String, Vec<u8>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let routes = Arc::new(routes);
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let routes = Arc::clone(&routes);
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    if read == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let authorized = request
+                    .lines()
+                    .any(|line| line.eq_ignore_ascii_case(&format!("authorization: {TOKEN}")));
+
+                let (status, body) = match routes.get(path) {
+                    Some(_) if !authorized => ("401 Unauthorized", b"{}".to_vec()),
+                    Some(body) => ("200 OK", body.clone()),
+                    None => ("404 Not Found", b"{}".to_vec()),
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len()
+                );
+                socket.write_all(head.as_bytes()).await.unwrap();
+                socket.write_all(&body).await.unwrap();
+            });
+        }
+    });
+    format!("http://{address}")
+}
+
+#[tokio::test]
+async fn oci_fetcher_pulls_layer_blob_from_registry_stub() {
+    let blob = b"clang toolchain bytes".to_vec();
+    let manifest = serde_json::json!({
+        "schemaVersion": 2,
+        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+        "config": {"mediaType": "application/vnd.oci.empty.v1+json", "digest": digest(b"{}"), "size": 2},
+        "layers": [
+            {"mediaType": "text/plain", "digest": digest(b"notes"), "size": 5},
+            {"mediaType": MEDIA_TYPE, "digest": digest(&blob), "size": blob.len(),
+             "annotations": {"org.opencontainers.image.title": "clang.tar.gz"}}
+        ]
+    })
+    .to_string();
+    let manifest_digest = digest(manifest.as_bytes());
+    let registry = serve(HashMap::from([
+        (
+            "/v2/toolchains/clang/manifests/18.1.0".to_string(),
+            manifest.clone().into_bytes(),
+        ),
+        (
+            format!("/v2/toolchains/clang/manifests/{manifest_digest}"),
+            manifest.into_bytes(),
+        ),
+        (
+            format!("/v2/toolchains/clang/blobs/{}", digest(&blob)),
+            blob.clone(),
+        ),
+    ]))
+    .await;
+
+    let temp = tempfile::tempdir().unwrap();
+    let fetcher = Arc::new(Fetcher::new(
+        ReqwestClient::new().unwrap(),
+        temp.path().join("workspace"),
+    ));
+    let source = |reference: &str| {
+        OciSource::new(
+            ValidUrl::parse(&registry).unwrap(),
+            "toolchains/clang",
+            OciReference::parse(reference).unwrap(),
+        )
+        .unwrap()
+        .with_media_type(MEDIA_TYPE)
+    };
+    let destination = temp.path().join("clang.tar.gz");
+
+    let anonymous = OciFetcher::new(Arc::clone(&fetcher))
+        .fetch_with_receipt(&source("18.1.0"), &destination, FetchOptions::default())
+        .await;
+    assert!(anonymous.is_err());
+
+    let oci = OciFetcher::new(fetcher).with_auth(OciBearerToken("registry-token".to_string()));
+    for reference in ["18.1.0", manifest_digest.as_str()] {
+        let receipt = oci
+            .fetch_with_receipt(&source(reference), &destination, FetchOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&destination).unwrap(), blob);
+        assert_eq!(receipt.bytes_downloaded, blob.len() as u64);
+        assert_eq!(
+            receipt.source,
+            FetchSource::Url(format!(
+                "{registry}/v2/toolchains/clang/blobs/{}",
+                digest(&blob)
+            ))
+        );
+        assert_eq!(receipt.sha256_hex.as_deref(), Some(&digest(&blob)[7..]));
+    }
+}
@@ -0,0 +1,153 @@
+//! S3 objects through `S3Fetcher` against a MinIO-like stub that checks
+//! SigV4 signatures with fixed keys.
+
+#![cfg(feature = "reqwest")]
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use pulith_fetch::{
+    Error, FetchOptions, FetchSource, Fetcher, Method, ReqwestClient, S3Credentials, S3Fetcher,
+    SigV4Signer,
+};
+use pulith_resource::ValidUrl;
+use pulith_source::S3Source;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const ACCESS_KEY: &str = "minioadmin";
+const SECRET_KEY: &str = "minio-secret-key";
+const OBJECT: &[u8] = b"internal tool binary";
+
+/// Serve `/tools/linux/tool 1.0.tar.gz` to correctly signed requests and
+/// record `METHOD path` of every request that verified.
+async fn serve() -> (ValidUrl, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let verified = Arc::new(Mutex::new(Vec::new()));
+    let log = verified.clone();
+    tokio::spawn(async move {
+        let signer = SigV4Signer::new(S3Credentials::new(ACCESS_KEY, SECRET_KEY), "us-east-1");
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let (log, signer) = (log.clone(), signer.clone());
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    if read == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                let mut lines = request.lines();
+                let mut request_line = lines.next().unwrap().split_whitespace();
+                let (method, path) = (request_line.next().unwrap(), request_line.next().unwrap());
+                let headers: Vec<(String, String)> = lines
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+                    .collect();
+                let header = |name: &str| {
+                    headers
+                        .iter()
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| value.clone())
+                };
+
+                let signed = header("x-amz-date").and_then(|date| {
+                    let at = NaiveDateTime::parse_from_str(&date, "%Y%m%dT%H%M%SZ").ok()?;
+                    let ranged: Vec<_> = headers
+                        .iter()
+                        .filter(|(name, _)| name == "range")
+                        .cloned()
+                        .collect();
+                    let expected = signer
+                        .sign(
+                            if method == "HEAD" {
+                                Method::Head
+                            } else {
+                                Method::Get
+                            },
+                            &format!("http://{address}{path}"),
+                            &ranged,
+                            Utc.from_utc_datetime(&at),
+                        )
+                        .ok()?;
+                    let expected = expected.iter().find(|(name, _)| name == "authorization")?;
+                    (header("authorization").as_ref() == Some(&expected.1)).then_some(())
+                });
+
+                let (status, body): (&str, &[u8]) = match (signed, path) {
+                    (None, _) => (
+                        "403 Forbidden",
+                        b"<Error><Code>SignatureDoesNotMatch</Code></Error>",
+                    ),
+                    (Some(()), "/tools/linux/tool%201.0.tar.gz") => ("200 OK", OBJECT),
+                    (Some(()), _) => ("404 Not Found", b"<Error><Code>NoSuchKey</Code></Error>"),
+                };
+                if signed.is_some() {
+                    log.lock().unwrap().push(format!("{method} {path}"));
+                }
+                let head = format!(
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len()
+                );
+                socket.write_all(head.as_bytes()).await.unwrap();
+                if method != "HEAD" {
+                    socket.write_all(body).await.unwrap();
+                }
+            });
+        }
+    });
+    (
+        ValidUrl::parse(format!("http://{address}")).unwrap(),
+        verified,
+    )
+}
+
+#[tokio::test]
+async fn s3_fetcher_signs_requests_with_fixed_keys() {
+    let (endpoint, verified) = serve().await;
+    let temp = tempfile::tempdir().unwrap();
+    let fetcher = Arc::new(Fetcher::new(
+        ReqwestClient::new().unwrap(),
+        temp.path().join("workspace"),
+    ));
+    let source = S3Source::new(endpoint, "tools", "linux/tool 1.0.tar.gz", "us-east-1").unwrap();
+    let destination = temp.path().join("tool.tar.gz");
+
+    let receipt = S3Fetcher::new(
+        fetcher.clone(),
+        Some(S3Credentials::new(ACCESS_KEY, SECRET_KEY)),
+    )
+    .fetch_with_receipt(&source, &destination, FetchOptions::default())
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(&destination).unwrap(), OBJECT);
+    assert_eq!(
+        receipt.source,
+        FetchSource::Url(source.object_url().unwrap().as_url().to_string())
+    );
+    assert_eq!(
+        verified.lock().unwrap().as_slice(),
+        [
+            "HEAD /tools/linux/tool%201.0.tar.gz",
+            "GET /tools/linux/tool%201.0.tar.gz",
+        ]
+    );
+
+    let error = S3Fetcher::new(
+        fetcher,
+        Some(S3Credentials::new(ACCESS_KEY, "wrong-secret")),
+    )
+    .fetch_with_receipt(
+        &source,
+        &temp.path().join("denied.tar.gz"),
+        FetchOptions::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(error, Error::Http { status: 403, .. }), "{error}");
+}
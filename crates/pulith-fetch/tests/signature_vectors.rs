@@ -0,0 +1,543 @@
+//! Signature verification test vectors for pulith-fetch.
+//!
+//! Fixtures under `tests/fixtures/signature` were produced with OpenSSL over
+//! `payload.txt` and cover the encoded `SignatureFormat`s and the supported
+//! `PublicKeyFormat`s for Ed25519 and RSA. The minisign fixtures are the
+//! upstream minisign test vectors, and the `openpgp` fixtures were produced
+//! with GnuPG. The `sigstore` fixtures are a self-contained Fulcio-style CA,
+//! Rekor-style log key and message signature bundle generated with the
+//! Python `cryptography` package, so they verify offline.
+
+use std::path::Path;
+
+use pulith_fetch::codec::signature::{
+    OpenPgpSignature, OpenPgpVerifier, PublicKey, PublicKeyFormat, Signature, SignatureAlgorithm,
+    SignatureConfig, SignatureFormat, SignatureManager, SigstoreTrustRoot, SigstoreVerifier,
+};
+use pulith_fetch::{FetchReceipt, FetchSource, verify_signature};
+use pulith_resource::{
+    DigestAlgorithm, SIGSTORE_IDENTITY_METADATA_KEY, SIGSTORE_ISSUER_METADATA_KEY, SignatureScheme,
+    TrustAnchor, TrustDecision, TrustMode, TrustPolicy, ValidDigest, VerificationRequirement,
+    VerifiedSignature, sigstore_key_id,
+};
+
+const ALL_FORMATS: [(SignatureFormat, &str); 5] = [
+    (SignatureFormat::Raw, "sig"),
+    (SignatureFormat::Base64, "sig.b64"),
+    (SignatureFormat::Hex, "sig.hex"),
+    (SignatureFormat::Pem, "sig.pem"),
+    (SignatureFormat::Der, "sig"),
+];
+
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(Path::new("tests/fixtures/signature").join(name)).unwrap()
+}
+
+fn sha256(data: &[u8]) -> ValidDigest {
+    use sha2::Digest as _;
+    ValidDigest::from_bytes(DigestAlgorithm::Sha256, sha2::Sha256::digest(data).to_vec()).unwrap()
+}
+
+fn config(
+    algorithm: SignatureAlgorithm,
+    key_format: PublicKeyFormat,
+    key_file: &str,
+    signature_format: SignatureFormat,
+    signature_file: &str,
+) -> SignatureConfig {
+    SignatureConfig::new(
+        PublicKey::new(algorithm, key_format, fixture(key_file)),
+        Signature::new(algorithm, signature_format, fixture(signature_file)),
+    )
+}
+
+#[test]
+fn ed25519_vectors_verify_in_every_signature_format() {
+    let payload = fixture("payload.txt");
+
+    for (key_format, key_file) in [
+        (PublicKeyFormat::Pem, "ed25519.pub.pem"),
+        (PublicKeyFormat::Der, "ed25519.pub.der"),
+        (PublicKeyFormat::Raw, "ed25519.pub.raw"),
+    ] {
+        for (signature_format, extension) in ALL_FORMATS {
+            let config = config(
+                SignatureAlgorithm::EdDsa,
+                key_format,
+                key_file,
+                signature_format,
+                &format!("ed25519.{extension}"),
+            );
+            assert!(
+                verify_signature(&payload, &config).unwrap(),
+                "{key_format:?} key with {signature_format:?} signature"
+            );
+        }
+    }
+}
+
+#[test]
+fn rsa_pkcs1v15_vectors_verify_in_every_signature_format() {
+    let payload = fixture("payload.txt");
+
+    for (key_format, key_file) in [
+        (PublicKeyFormat::Pem, "rsa.pub.pem"),
+        (PublicKeyFormat::Pem, "rsa.pkcs1.pem"),
+        (PublicKeyFormat::Der, "rsa.pub.der"),
+    ] {
+        for (signature_format, extension) in ALL_FORMATS {
+            let config = config(
+                SignatureAlgorithm::RsaPkcs1v15,
+                key_format,
+                key_file,
+                signature_format,
+                &format!("rsa-pkcs1v15.{extension}"),
+            );
+            assert!(
+                verify_signature(&payload, &config).unwrap(),
+                "{key_file} with {signature_format:?} signature"
+            );
+        }
+    }
+}
+
+#[test]
+fn rsa_pss_vectors_verify_in_every_signature_format() {
+    let payload = fixture("payload.txt");
+
+    for (signature_format, extension) in ALL_FORMATS {
+        let config = config(
+            SignatureAlgorithm::RsaPss,
+            PublicKeyFormat::Pem,
+            "rsa.pub.pem",
+            signature_format,
+            &format!("rsa-pss.{extension}"),
+        );
+        assert!(
+            verify_signature(&payload, &config).unwrap(),
+            "{signature_format:?} signature"
+        );
+    }
+}
+
+#[test]
+fn vectors_reject_modified_payload() {
+    let mut payload = fixture("payload.txt");
+    payload[0] ^= 0x01;
+    let manager = SignatureManager::new();
+
+    for config in [
+        config(
+            SignatureAlgorithm::EdDsa,
+            PublicKeyFormat::Pem,
+            "ed25519.pub.pem",
+            SignatureFormat::Raw,
+            "ed25519.sig",
+        ),
+        config(
+            SignatureAlgorithm::RsaPkcs1v15,
+            PublicKeyFormat::Pem,
+            "rsa.pub.pem",
+            SignatureFormat::Raw,
+            "rsa-pkcs1v15.sig",
+        ),
+        config(
+            SignatureAlgorithm::RsaPss,
+            PublicKeyFormat::Pem,
+            "rsa.pub.pem",
+            SignatureFormat::Raw,
+            "rsa-pss.sig",
+        ),
+    ] {
+        assert!(!manager.verify(&payload, &config).unwrap());
+    }
+}
+
+#[test]
+fn rsa_padding_modes_are_not_interchangeable() {
+    let payload = fixture("payload.txt");
+    let config = config(
+        SignatureAlgorithm::RsaPkcs1v15,
+        PublicKeyFormat::Pem,
+        "rsa.pub.pem",
+        SignatureFormat::Raw,
+        "rsa-pss.sig",
+    );
+
+    assert!(!verify_signature(&payload, &config).unwrap());
+}
+
+#[test]
+fn minisign_vector_verifies_through_signature_manager() {
+    let payload = fixture("minisign-payload.txt");
+    let config = config(
+        SignatureAlgorithm::EdDsa,
+        PublicKeyFormat::Minisign,
+        "minisign.pub",
+        SignatureFormat::Minisign,
+        "minisign-payload.txt.minisig",
+    );
+
+    assert!(verify_signature(&payload, &config).unwrap());
+    assert!(!verify_signature(b"tampered", &config).unwrap());
+    assert_eq!(
+        config.signature.minisign().unwrap().trusted_comment(),
+        Some("timestamp:1556193335\tfile:test")
+    );
+}
+
+const ED25519_FINGERPRINT: &str = "30B65883B216EDD81528A31ADEF6B49AA2474D3E";
+const RSA_FINGERPRINT: &str = "2DD047E987179833A326928FE865DFBD45F9DB2D";
+const SUBKEY_PRIMARY_FINGERPRINT: &str = "D80E5140163D9B9221328E59189C1CB712621A0C";
+const SUBKEY_FINGERPRINT: &str = "BED6635675216D434FBF48B316479A3C53965274";
+
+fn openpgp_verifier() -> OpenPgpVerifier {
+    OpenPgpVerifier::from_keyring_dir("tests/fixtures/signature/openpgp/keyring").unwrap()
+}
+
+#[test]
+fn openpgp_keyring_dir_loads_primary_keys_and_subkeys() {
+    let verifier = openpgp_verifier();
+    let fingerprints = verifier
+        .keyring()
+        .keys()
+        .iter()
+        .map(|key| (key.fingerprint(), key.primary_fingerprint(), key.can_sign()))
+        .collect::<Vec<_>>();
+
+    // The subkey fixture's primary key is certification-only.
+    assert_eq!(
+        fingerprints,
+        [
+            (ED25519_FINGERPRINT, ED25519_FINGERPRINT, true),
+            (RSA_FINGERPRINT, RSA_FINGERPRINT, true),
+            (
+                SUBKEY_PRIMARY_FINGERPRINT,
+                SUBKEY_PRIMARY_FINGERPRINT,
+                false
+            ),
+            (SUBKEY_FINGERPRINT, SUBKEY_PRIMARY_FINGERPRINT, true),
+        ]
+        .map(|(key, primary, signing)| (key.to_string(), primary.to_string(), signing))
+    );
+}
+
+#[test]
+fn openpgp_vectors_report_signing_key_fingerprint() {
+    let verifier = openpgp_verifier();
+    let payload = fixture("payload.txt");
+
+    for (signature, fingerprint, primary) in [
+        (
+            "openpgp/payload.ed25519.asc",
+            ED25519_FINGERPRINT,
+            ED25519_FINGERPRINT,
+        ),
+        ("openpgp/payload.rsa.sig", RSA_FINGERPRINT, RSA_FINGERPRINT),
+        (
+            "openpgp/payload.subkey.asc",
+            SUBKEY_FINGERPRINT,
+            SUBKEY_PRIMARY_FINGERPRINT,
+        ),
+    ] {
+        let verification = verifier
+            .verify_detached(&payload, &fixture(signature))
+            .unwrap()
+            .unwrap_or_else(|| panic!("{signature} did not verify"));
+
+        assert_eq!(verification.fingerprint, fingerprint);
+        assert_eq!(verification.primary_fingerprint, primary);
+        assert!(verification.created_at.is_some());
+    }
+}
+
+#[test]
+fn openpgp_text_signature_ignores_line_ending_style() {
+    let verifier = openpgp_verifier();
+    let signature = fixture("openpgp/payload.ed25519-text.asc");
+    let payload = fixture("payload.txt");
+    let crlf_payload = String::from_utf8(payload.clone())
+        .unwrap()
+        .replace('\n', "\r\n");
+
+    assert!(OpenPgpSignature::parse_all(&signature).unwrap()[0].is_text());
+    assert!(
+        verifier
+            .verify_detached(&payload, &signature)
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        verifier
+            .verify_detached(crlf_payload.as_bytes(), &signature)
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[test]
+fn openpgp_rejects_tampered_content_and_unknown_keys() {
+    let verifier = openpgp_verifier();
+    let mut payload = fixture("payload.txt");
+    payload[0] ^= 0x01;
+
+    assert_eq!(
+        verifier
+            .verify_detached(&payload, &fixture("openpgp/payload.rsa.sig"))
+            .unwrap(),
+        None
+    );
+    assert!(
+        verifier
+            .verify_detached(
+                &fixture("payload.txt"),
+                &fixture("openpgp/payload.other.asc")
+            )
+            .is_err()
+    );
+}
+
+#[test]
+fn openpgp_verifier_registers_with_signature_manager() {
+    let mut manager = SignatureManager::new();
+    manager.add_verifier(Box::new(openpgp_verifier()));
+    let payload = fixture("payload.txt");
+
+    let keyring_only = SignatureConfig::new(
+        PublicKey::new(
+            SignatureAlgorithm::OpenPgp,
+            PublicKeyFormat::OpenPgp,
+            Vec::new(),
+        ),
+        Signature::new(
+            SignatureAlgorithm::OpenPgp,
+            SignatureFormat::OpenPgp,
+            fixture("openpgp/payload.ed25519.asc"),
+        ),
+    );
+    assert!(manager.verify(&payload, &keyring_only).unwrap());
+
+    let extra_key = SignatureConfig::new(
+        PublicKey::new(
+            SignatureAlgorithm::OpenPgp,
+            PublicKeyFormat::OpenPgp,
+            fixture("openpgp/other.asc"),
+        ),
+        Signature::new(
+            SignatureAlgorithm::OpenPgp,
+            SignatureFormat::OpenPgp,
+            fixture("openpgp/payload.other.asc"),
+        ),
+    );
+    assert!(manager.verify(&payload, &extra_key).unwrap());
+}
+
+#[test]
+fn openpgp_verification_is_recorded_in_fetch_receipt() {
+    let verifier = openpgp_verifier();
+    let mut receipt = FetchReceipt {
+        source: FetchSource::LocalPath("tests/fixtures/signature/payload.txt".into()),
+        destination: "tests/fixtures/signature/payload.txt".into(),
+        bytes_downloaded: 0,
+        total_bytes: None,
+        sha256_hex: None,
+        signatures: Vec::new(),
+        selection: None,
+        cache_hit: false,
+        revalidated: false,
+        coalesced: false,
+        attempts: Vec::new(),
+    };
+
+    let verification = verifier
+        .verify_receipt(&mut receipt, &fixture("openpgp/payload.subkey.asc"))
+        .unwrap();
+    assert_eq!(verification.fingerprint, SUBKEY_FINGERPRINT);
+    assert_eq!(
+        receipt.signatures,
+        [VerifiedSignature {
+            scheme: SignatureScheme::OpenPgp,
+            key_id: SUBKEY_PRIMARY_FINGERPRINT.to_string(),
+            public_key: None,
+            digest: Some(sha256(&fixture("payload.txt"))),
+        }]
+    );
+
+    receipt.destination = "tests/fixtures/signature/minisign-payload.txt".into();
+    assert!(
+        verifier
+            .verify_receipt(&mut receipt, &fixture("openpgp/payload.subkey.asc"))
+            .is_err()
+    );
+    assert_eq!(receipt.signatures.len(), 1);
+}
+
+const SIGSTORE_IDENTITY: &str =
+    "https://github.com/example/tool/.github/workflows/release.yml@refs/tags/v1.0.0";
+const SIGSTORE_ISSUER: &str = "https://token.actions.githubusercontent.com";
+
+fn sigstore_verifier() -> SigstoreVerifier {
+    SigstoreVerifier::from_trust_root_file("tests/fixtures/signature/sigstore/trusted_root.json")
+        .unwrap()
+}
+
+type BundleEdit = fn(&mut serde_json::Value);
+
+fn sigstore_bundle(edit: BundleEdit) -> Vec<u8> {
+    let mut bundle: serde_json::Value =
+        serde_json::from_slice(&fixture("sigstore/artifact.txt.sigstore.json")).unwrap();
+    edit(&mut bundle);
+    serde_json::to_vec(&bundle).unwrap()
+}
+
+#[test]
+fn sigstore_bundles_verify_and_expose_identity_facts() {
+    let verifier = sigstore_verifier();
+    let artifact = fixture("sigstore/artifact.txt");
+
+    for bundle in [
+        "sigstore/artifact.txt.sigstore.json",
+        "sigstore/artifact.txt.v0.1.sigstore.json",
+    ] {
+        let verification = verifier
+            .verify_detached(&artifact, &fixture(bundle))
+            .unwrap()
+            .unwrap();
+        assert_eq!(verification.identity, SIGSTORE_IDENTITY);
+        assert_eq!(verification.issuer, SIGSTORE_ISSUER);
+        assert_eq!(verification.log_index, 1005);
+    }
+
+    let verification = verifier
+        .verify_detached(&artifact, &fixture("sigstore/artifact.txt.sigstore.json"))
+        .unwrap()
+        .unwrap();
+    let facts = verification.metadata();
+    let policy = |key: &str, value: &str| TrustPolicy {
+        mode: TrustMode::RequireAnchorMatch,
+        anchors: vec![TrustAnchor::metadata(key, value).unwrap()],
+    };
+    assert_eq!(
+        policy(SIGSTORE_ISSUER_METADATA_KEY, SIGSTORE_ISSUER).evaluate(
+            None,
+            None,
+            &facts,
+            &VerificationRequirement::None
+        ),
+        TrustDecision::Trusted
+    );
+    assert_eq!(
+        policy(SIGSTORE_IDENTITY_METADATA_KEY, SIGSTORE_IDENTITY).evaluate(
+            None,
+            None,
+            &facts,
+            &VerificationRequirement::None
+        ),
+        TrustDecision::Trusted
+    );
+    assert_ne!(
+        policy(SIGSTORE_ISSUER_METADATA_KEY, "https://accounts.google.com").evaluate(
+            None,
+            None,
+            &facts,
+            &VerificationRequirement::None
+        ),
+        TrustDecision::Trusted
+    );
+}
+
+#[test]
+fn sigstore_rejects_modified_artifact_and_foreign_trust_root() {
+    let bundle = fixture("sigstore/artifact.txt.sigstore.json");
+    let mut artifact = fixture("sigstore/artifact.txt");
+    artifact.push(b'!');
+    assert_eq!(
+        sigstore_verifier()
+            .verify_detached(&artifact, &bundle)
+            .unwrap(),
+        None
+    );
+
+    let foreign = SigstoreVerifier::new(
+        SigstoreTrustRoot::from_file("tests/fixtures/signature/sigstore/other_trusted_root.json")
+            .unwrap(),
+    );
+    assert!(
+        foreign
+            .verify_detached(&fixture("sigstore/artifact.txt"), &bundle)
+            .is_err()
+    );
+}
+
+#[test]
+fn sigstore_rejects_tampered_transparency_log_evidence() {
+    let verifier = sigstore_verifier();
+    let artifact = fixture("sigstore/artifact.txt");
+    const ENTRY: &str = "/verificationMaterial/tlogEntries/0";
+
+    let cases: [(&str, BundleEdit); 5] = [
+        ("inclusion proof does not match", |bundle| {
+            *bundle
+                .pointer_mut(&format!("{ENTRY}/inclusionProof/hashes/0"))
+                .unwrap() = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into();
+        }),
+        ("no transparency log inclusion proof", |bundle| {
+            bundle.pointer_mut(ENTRY).unwrap()["inclusionProof"] = serde_json::Value::Null;
+        }),
+        ("signed entry timestamp", |bundle| {
+            bundle.pointer_mut(ENTRY).unwrap()["logIndex"] = "1006".into();
+        }),
+        ("signed entry timestamp", |bundle| {
+            bundle.pointer_mut(ENTRY).unwrap()["integratedTime"] = "1767230000".into();
+        }),
+        // Without a signed entry timestamp the bundle could pick its own time.
+        ("no signed entry timestamp", |bundle| {
+            let entry = bundle.pointer_mut(ENTRY).unwrap();
+            entry["inclusionPromise"] = serde_json::Value::Null;
+            entry["integratedTime"] = "1767230000".into();
+        }),
+    ];
+
+    for (expected, edit) in cases {
+        let error = verifier
+            .verify_detached(&artifact, &sigstore_bundle(edit))
+            .unwrap_err();
+        assert!(
+            error.to_string().contains(expected),
+            "expected `{expected}`, got `{error}`"
+        );
+    }
+}
+
+#[test]
+fn sigstore_verification_is_recorded_in_fetch_receipt() {
+    let mut receipt = FetchReceipt {
+        source: FetchSource::LocalPath("tests/fixtures/signature/sigstore/artifact.txt".into()),
+        destination: "tests/fixtures/signature/sigstore/artifact.txt".into(),
+        bytes_downloaded: 0,
+        total_bytes: None,
+        sha256_hex: None,
+        signatures: Vec::new(),
+        selection: None,
+        cache_hit: false,
+        revalidated: false,
+        coalesced: false,
+        attempts: Vec::new(),
+    };
+
+    sigstore_verifier()
+        .verify_receipt(
+            &mut receipt,
+            &fixture("sigstore/artifact.txt.sigstore.json"),
+        )
+        .unwrap();
+    assert_eq!(
+        receipt.signatures,
+        [VerifiedSignature {
+            scheme: SignatureScheme::Sigstore,
+            key_id: sigstore_key_id(SIGSTORE_ISSUER, SIGSTORE_IDENTITY),
+            public_key: None,
+            digest: Some(sha256(&fixture("sigstore/artifact.txt"))),
+        }]
+    );
+}
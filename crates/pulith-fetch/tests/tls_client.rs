@@ -0,0 +1,252 @@
+//! `ReqwestClientBuilder` network configuration against local TLS and proxy
+//! stubs, using the self-signed CA in `tests/fixtures/tls`.
+
+#![cfg(feature = "reqwest")]
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use pulith_fetch::{HttpClient, Method, ReqwestClient, ReqwestClientBuilder, ReqwestClientError};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig, ServerConnection, StreamOwned};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+/// Serve `hello` over TLS with the fixture server certificate, optionally
+/// requiring a client certificate issued by the fixture CA; records every
+/// request that arrives over an established connection.
+fn serve_tls(require_client_certificate: bool) -> (String, Arc<Mutex<Vec<String>>>) {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let chain = CertificateDer::pem_file_iter(fixture("server.pem"))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_file(fixture("server.key")).unwrap();
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .unwrap();
+    let builder = if require_client_certificate {
+        let mut roots = RootCertStore::empty();
+        for ca in CertificateDer::pem_file_iter(fixture("ca.pem")).unwrap() {
+            roots.add(ca.unwrap()).unwrap();
+        }
+        let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+            .build()
+            .unwrap();
+        builder.with_client_cert_verifier(verifier)
+    } else {
+        builder.with_no_client_auth()
+    };
+    let config = Arc::new(builder.with_single_cert(chain, key).unwrap());
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let (config, seen) = (config.clone(), seen.clone());
+            std::thread::spawn(move || {
+                let mut tcp = stream.unwrap();
+                let mut connection = ServerConnection::new(config).unwrap();
+                while connection.is_handshaking() {
+                    if connection.complete_io(&mut tcp).is_err() {
+                        return;
+                    }
+                }
+                let mut stream = StreamOwned::new(connection, tcp);
+                let request = read_request(&mut stream);
+                seen.lock().unwrap().push(request);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\nconnection: close\r\n\r\nhello",
+                );
+                stream.conn.send_close_notify();
+                let _ = stream.flush();
+            });
+        }
+    });
+    (format!("https://localhost:{port}/"), requests)
+}
+
+/// Plain HTTP stub answering with `body`; records each request line.
+fn serve_plain(body: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let request = read_request(&mut stream);
+            let line = request.lines().next().unwrap_or_default().to_string();
+            seen.lock().unwrap().push(line);
+            let head = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(body.as_bytes());
+        }
+    });
+    (format!("http://{address}"), requests)
+}
+
+fn read_request(stream: &mut impl Read) -> String {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => request.extend_from_slice(&buffer[..read]),
+        }
+    }
+    String::from_utf8_lossy(&request).into_owned()
+}
+
+async fn get(client: &ReqwestClient, url: &str) -> Result<Vec<u8>, ReqwestClientError> {
+    let response = client.send(Method::Get, url, &[]).await?;
+    assert_eq!(response.head.status, 200);
+    let mut body = Vec::new();
+    let mut stream = response.body;
+    while let Some(chunk) = stream.next().await {
+        body.extend_from_slice(&chunk?);
+    }
+    Ok(body)
+}
+
+fn builder() -> ReqwestClientBuilder {
+    ReqwestClient::builder()
+        .without_proxy()
+        .with_connect_timeout(Duration::from_secs(10))
+}
+
+#[tokio::test]
+async fn trusts_extra_root_certificates() {
+    let (url, _) = serve_tls(false);
+
+    let untrusted = builder().build().unwrap();
+    assert!(get(&untrusted, &url).await.is_err());
+
+    let trusted = builder()
+        .with_root_certificates(fixture("ca.pem"))
+        .build()
+        .unwrap();
+    assert_eq!(get(&trusted, &url).await.unwrap(), b"hello");
+}
+
+#[tokio::test]
+async fn presents_client_certificate_for_mutual_tls() {
+    let (url, _) = serve_tls(true);
+
+    let anonymous = builder()
+        .with_root_certificates(fixture("ca.pem"))
+        .build()
+        .unwrap();
+    assert!(get(&anonymous, &url).await.is_err());
+
+    let identified = builder()
+        .with_root_certificates(fixture("ca.pem"))
+        .with_client_identity_pem(fixture("client.pem"), fixture("client.key"))
+        .build()
+        .unwrap();
+    assert_eq!(get(&identified, &url).await.unwrap(), b"hello");
+}
+
+#[tokio::test]
+async fn enforces_pinned_public_keys() {
+    let (url, requests) = serve_tls(false);
+    let pin = std::fs::read_to_string(fixture("server.spki.sha256")).unwrap();
+
+    let pinned = builder()
+        .with_root_certificates(fixture("ca.pem"))
+        .with_pinned_key("localhost", pin.trim())
+        .build()
+        .unwrap();
+    assert_eq!(get(&pinned, &url).await.unwrap(), b"hello");
+
+    let mispinned = builder()
+        .with_root_certificates(fixture("ca.pem"))
+        .with_pinned_key("LOCALHOST", "00".repeat(32))
+        .build()
+        .unwrap();
+    let error = mispinned
+        .send(
+            Method::Get,
+            &url,
+            &[("Authorization".to_string(), "Bearer secret".to_string())],
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&error, ReqwestClientError::PinMismatch { host } if host == "localhost"),
+        "{error}"
+    );
+    // The handshake fails before the request, and its credentials, are sent.
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert!(!requests.iter().any(|request| request.contains("secret")));
+}
+
+#[tokio::test]
+async fn routes_through_explicit_proxy_except_no_proxy_hosts() {
+    let (origin, origin_requests) = serve_plain("origin");
+    let (proxy, proxy_requests) = serve_plain("proxied");
+    let url = format!("{origin}/tool.tar.gz");
+
+    let proxied = ReqwestClient::builder().with_proxy(&proxy).build().unwrap();
+    assert_eq!(get(&proxied, &url).await.unwrap(), b"proxied");
+    assert_eq!(
+        proxy_requests.lock().unwrap().as_slice(),
+        [format!("GET {url} HTTP/1.1")]
+    );
+
+    let bypassing = ReqwestClient::builder()
+        .with_proxy(&proxy)
+        .with_no_proxy("localhost,127.0.0.1")
+        .build()
+        .unwrap();
+    assert_eq!(get(&bypassing, &url).await.unwrap(), b"origin");
+    assert_eq!(proxy_requests.lock().unwrap().len(), 1);
+    assert_eq!(
+        origin_requests.lock().unwrap().as_slice(),
+        ["GET /tool.tar.gz HTTP/1.1"]
+    );
+}
+
+#[tokio::test]
+async fn read_timeout_fails_silent_servers() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let mut held = Vec::new();
+        for stream in listener.incoming() {
+            held.push(stream.unwrap());
+        }
+    });
+
+    let client = builder()
+        .with_read_timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let error = tokio::time::timeout(
+        Duration::from_secs(10),
+        get(&client, &format!("http://{address}/")),
+    )
+    .await
+    .expect("read timeout did not fire")
+    .unwrap_err();
+    assert!(
+        matches!(&error, ReqwestClientError::Request(e) if e.is_timeout()),
+        "{error}"
+    );
+}
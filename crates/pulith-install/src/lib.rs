@@ -11,7 +11,9 @@ use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use pulith_fs::{FallBack, HardlinkOrCopyOptions, Workspace, atomic_symlink, copy_dir_all};
-use pulith_resource::{Metadata, ResolvedResource};
+use pulith_resource::{
+    DigestAlgorithm, Metadata, ResolvedResource, ValidDigest, VerificationRequirement,
+};
 use pulith_serde_backend::{CodecError, JsonTextCodec, decode_slice, encode_pretty_vec};
 use pulith_shim::TargetResolver;
 use pulith_state::{
@@ -19,6 +21,7 @@ use pulith_state::{
 };
 use pulith_store::{ExtractedArtifact, StoreKey, StoredArtifact};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, InstallError>;
@@ -589,6 +592,42 @@ impl InstallInput {
 
         Ok(())
     }
+
+    /// Checks a signature requirement against what was staged: single files
+    /// are hashed in the staging area, extracted inputs are bound through the
+    /// digest recorded on the resolved artifact.
+    fn validate_signatures(
+        &self,
+        resource: &ResolvedResource,
+        staging: &StagingArea,
+    ) -> Result<()> {
+        if !matches!(
+            resource.spec().verification,
+            VerificationRequirement::Signature(_)
+        ) {
+            return Ok(());
+        }
+
+        match self {
+            Self::StagedFile { file_name, .. } | Self::StoredArtifact { file_name, .. } => {
+                let staged = staging.workspace.staging_path().join(file_name);
+                resource.validate_signatures_for(&sha256_file(&staged)?)?;
+            }
+            Self::ExtractedArtifact(_) | Self::ExtractedTree { .. } => {
+                resource.validate_signatures()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn sha256_file(path: &Path) -> Result<ValidDigest> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(ValidDigest::from_bytes(
+        DigestAlgorithm::Sha256,
+        hasher.finalize().to_vec(),
+    )?)
 }
 
 #[derive(Debug, Clone)]
@@ -808,6 +847,9 @@ impl PlannedInstall {
         let staging =
             StagingArea::new(temp.path().join("staging"), self.spec.install_root.clone())?;
         self.spec.input.stage_into(&staging)?;
+        self.spec
+            .input
+            .validate_signatures(&self.spec.resource, &staging)?;
 
         Ok(InstallFlow {
             ready: self.ready,
@@ -1708,6 +1750,111 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn install_stage_rejects_unsigned_artifact_when_signature_required() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetched_path = temp.path().join("fetched.bin");
+        std::fs::write(&fetched_path, b"payload").unwrap();
+
+        let requirement = pulith_resource::SignatureRequirement::new(
+            pulith_resource::SignatureScheme::OpenPgp,
+            ["30B65883B216EDD81528A31ADEF6B49AA2474D3E"],
+        )
+        .unwrap();
+        let resource = RequestedResource::new(
+            ResourceSpec::new(
+                ResourceId::parse("example/runtime").unwrap(),
+                ResourceLocator::Url(ValidUrl::parse("https://example.com/runtime.bin").unwrap()),
+            )
+            .verification(pulith_resource::VerificationRequirement::Signature(
+                requirement,
+            )),
+        )
+        .resolve(
+            ResolvedVersion::new("1.0.0").unwrap(),
+            ResolvedLocator::Url(ValidUrl::parse("https://example.com/runtime.bin").unwrap()),
+            None,
+        );
+
+        let state = StateReady::initialize(temp.path().join("state.json")).unwrap();
+        let ready = InstallReady::new(state);
+        let spec = InstallSpec::new(
+            resource,
+            InstallInput::from_file_path(&fetched_path).unwrap(),
+            temp.path().join("install/staged"),
+        );
+
+        assert!(matches!(
+            PlannedInstall::new(ready, spec).stage(),
+            Err(InstallError::Resource(
+                pulith_resource::ResourceError::MissingSignature { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn install_stage_binds_signature_to_staged_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let fetched_path = temp.path().join("fetched.bin");
+        std::fs::write(&fetched_path, b"payload").unwrap();
+
+        let requirement = pulith_resource::SignatureRequirement::new(
+            pulith_resource::SignatureScheme::OpenPgp,
+            ["30B65883B216EDD81528A31ADEF6B49AA2474D3E"],
+        )
+        .unwrap();
+        let stage = |signed: &[u8]| {
+            let signature = pulith_resource::VerifiedSignature {
+                scheme: pulith_resource::SignatureScheme::OpenPgp,
+                key_id: "30B65883B216EDD81528A31ADEF6B49AA2474D3E".to_string(),
+                public_key: None,
+                digest: Some(
+                    ValidDigest::from_bytes(
+                        DigestAlgorithm::Sha256,
+                        Sha256::digest(signed).to_vec(),
+                    )
+                    .unwrap(),
+                ),
+            };
+            let resource = RequestedResource::new(
+                ResourceSpec::new(
+                    ResourceId::parse("example/runtime").unwrap(),
+                    ResourceLocator::Url(
+                        ValidUrl::parse("https://example.com/runtime.bin").unwrap(),
+                    ),
+                )
+                .verification(VerificationRequirement::Signature(requirement.clone())),
+            )
+            .resolve(
+                ResolvedVersion::new("1.0.0").unwrap(),
+                ResolvedLocator::Url(ValidUrl::parse("https://example.com/runtime.bin").unwrap()),
+                Some(pulith_resource::ArtifactDescriptor {
+                    digest: None,
+                    file_name: Some("runtime.bin".to_string()),
+                    metadata: Metadata::new(),
+                    signatures: vec![signature],
+                }),
+            );
+
+            let state = StateReady::initialize(temp.path().join("state.json")).unwrap();
+            let spec = InstallSpec::new(
+                resource,
+                InstallInput::from_file_path(&fetched_path).unwrap(),
+                temp.path().join("install/staged"),
+            );
+            PlannedInstall::new(InstallReady::new(state), spec).stage()
+        };
+
+        // An allowed key signed other bytes than the ones being installed.
+        assert!(matches!(
+            stage(b"other payload"),
+            Err(InstallError::Resource(
+                pulith_resource::ResourceError::SignedDigestMismatch { .. }
+            ))
+        ));
+        assert!(stage(b"payload").is_ok());
+    }
+
     #[test]
     fn extracted_tree_input_installs_tree() {
         let temp = tempfile::tempdir().unwrap();
@@ -71,7 +71,8 @@ impl TargetTriple {
         }
     }
 
-    fn arch_to_str(&self) -> &str {
+    /// Architecture component as used in the triple, e.g. `x86_64`.
+    pub fn arch_to_str(&self) -> &str {
         match self.arch {
             Arch::X86 => "i686",
             Arch::X86_64 => "x86_64",
@@ -81,7 +82,8 @@ impl TargetTriple {
         }
     }
 
-    fn os_to_str(&self) -> &str {
+    /// Operating system component as used in the triple, e.g. `linux`.
+    pub fn os_to_str(&self) -> &str {
         match self.os {
             OS::Windows => "windows",
             OS::MacOS => "darwin",
@@ -1,8 +1,14 @@
 use crate::error::{Error, Result};
 use crate::shell::Shell;
 use std::ffi::OsStr;
+use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::process::{Command as StdCommand, Output};
+use std::process::{Command as StdCommand, Output, Stdio};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How often [`Command::output_until`] checks whether to stop the child.
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
 
 #[derive(Debug)]
 pub struct Command {
@@ -81,6 +87,76 @@ impl Command {
             .map_err(|e| command_failed(self.program.clone(), e))
     }
 
+    /// Run to completion with `input` written to stdin, capturing stdout and
+    /// stderr.
+    pub fn output_with_input(&mut self, input: &[u8]) -> Result<Output> {
+        let mut child = self
+            .inner
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| command_failed(self.program.clone(), e))?;
+        // A child that exits without reading its input closes the pipe; its
+        // exit status is what the caller wants to see.
+        if let Some(mut stdin) = child.stdin.take()
+            && let Err(e) = stdin.write_all(input)
+            && e.kind() != std::io::ErrorKind::BrokenPipe
+        {
+            return Err(command_failed(self.program.clone(), e));
+        }
+        child
+            .wait_with_output()
+            .map_err(|e| command_failed(self.program.clone(), e))
+    }
+
+    /// Run to completion capturing stdout and stderr, checking `interrupted`
+    /// while the child runs.
+    ///
+    /// Once `interrupted` returns `true` the child is killed and `None` is
+    /// returned instead of its output.
+    pub fn output_until(
+        &mut self,
+        mut interrupted: impl FnMut() -> bool,
+    ) -> Result<Option<Output>> {
+        let mut child = self
+            .inner
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| command_failed(self.program.clone(), e))?;
+        // Drain both pipes in the background so a chatty child cannot block
+        // on a full pipe while it is being polled.
+        let stdout = child.stdout.take().map(drain);
+        let stderr = child.stderr.take().map(drain);
+
+        let status = loop {
+            if let Some(status) = child
+                .try_wait()
+                .map_err(|e| command_failed(self.program.clone(), e))?
+            {
+                break status;
+            }
+            if interrupted() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+
+        let collect = |pipe: Option<JoinHandle<Vec<u8>>>| {
+            pipe.and_then(|reader| reader.join().ok())
+                .unwrap_or_default()
+        };
+        Ok(Some(Output {
+            status,
+            stdout: collect(stdout),
+            stderr: collect(stderr),
+        }))
+    }
+
     pub fn spawn(&mut self) -> Result<std::process::Child> {
         self.inner
             .spawn()
@@ -88,6 +164,14 @@ impl Command {
     }
 }
 
+fn drain(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
 fn command_failed(cmd: String, source: std::io::Error) -> Error {
     Error::CommandFailed { cmd, source }
 }
@@ -200,6 +284,34 @@ mod tests {
         assert!(result.is_ok() || result.is_err());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_command_output_with_input() {
+        let output = Command::new("cat").output_with_input(b"piped").unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"piped");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_output_until_kills_interrupted_child() {
+        let output = Command::new("echo")
+            .arg("done")
+            .output_until(|| false)
+            .unwrap()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"done\n");
+
+        let started = std::time::Instant::now();
+        let output = Command::new("sleep")
+            .arg("30")
+            .output_until(|| started.elapsed() > Duration::from_millis(50))
+            .unwrap();
+        assert!(output.is_none());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
     #[test]
     fn test_command_spawn_returns_result() {
         let mut cmd = Command::new("echo").arg("test");
@@ -43,6 +43,26 @@ pub enum ResourceError {
     EmptyTrustHost,
     #[error("trust metadata key must not be empty")]
     EmptyTrustMetadataKey,
+    #[error("invalid minisign public key: {0}")]
+    InvalidMinisignKey(String),
+    #[error("signature requirement must list at least one key")]
+    EmptySignatureKeys,
+    #[error("Sigstore key id `{0}` must be `<issuer>|<identity>`")]
+    InvalidSigstoreKeyId(String),
+    #[error("a {scheme:?} signature is required but none was verified")]
+    MissingSignature { scheme: SignatureScheme },
+    #[error("{scheme:?} signature from `{key_id}` is not from an allowed key")]
+    UnexpectedSignatureKey {
+        scheme: SignatureScheme,
+        key_id: String,
+    },
+    #[error("no allowed {scheme:?} signature covers content with digest {digest}")]
+    SignedDigestMismatch {
+        scheme: SignatureScheme,
+        digest: String,
+    },
+    #[error("a {scheme:?} signature is required but the artifact records no digest to bind it to")]
+    MissingArtifactDigest { scheme: SignatureScheme },
     #[error("resolved version is not parseable for selector matching: {0}")]
     InvalidResolvedVersion(String),
     #[error("resolved version `{version}` does not satisfy selector `{selector}`")]
@@ -306,6 +326,96 @@ pub enum VerificationRequirement {
     Digest(ValidDigest),
     AnyOf(Vec<ValidDigest>),
     AllOf(Vec<ValidDigest>),
+    Signature(SignatureRequirement),
+}
+
+/// Requires a verified detached signature from one of the listed keys.
+///
+/// Key ids are minisign key ids or OpenPGP fingerprints; whitespace is
+/// dropped and hex is upper-cased so copied fingerprints compare equal.
+/// Sigstore key ids pair the OIDC issuer with the certificate identity (see
+/// [`sigstore_key_id`]) and compare exactly, so an identity is only accepted
+/// from the issuer it is listed with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureRequirement {
+    pub scheme: SignatureScheme,
+    pub key_ids: Vec<String>,
+}
+
+impl SignatureRequirement {
+    pub fn new<I>(scheme: SignatureScheme, key_ids: I) -> Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let key_ids = key_ids
+            .into_iter()
+            .map(|key_id| normalize_key_id(scheme, key_id.as_ref()))
+            .collect::<Vec<_>>();
+        ensure_non_empty_collection(&key_ids, ResourceError::EmptySignatureKeys)?;
+        if key_ids.iter().any(String::is_empty) {
+            return Err(ResourceError::EmptyValue);
+        }
+        if scheme == SignatureScheme::Sigstore
+            && let Some(key_id) = key_ids.iter().find(|key_id| {
+                key_id
+                    .split_once('|')
+                    .is_none_or(|(issuer, identity)| issuer.is_empty() || identity.is_empty())
+            })
+        {
+            return Err(ResourceError::InvalidSigstoreKeyId(key_id.clone()));
+        }
+
+        Ok(Self { scheme, key_ids })
+    }
+
+    /// Checks that a signature from an allowed key was verified over
+    /// content with the given digest.
+    pub fn validate(&self, signatures: &[VerifiedSignature], content: &ValidDigest) -> Result<()> {
+        let mut candidates = signatures
+            .iter()
+            .filter(|signature| signature.scheme == self.scheme)
+            .peekable();
+        let Some(first) = candidates.peek().copied() else {
+            return Err(ResourceError::MissingSignature {
+                scheme: self.scheme,
+            });
+        };
+
+        let mut allowed = candidates
+            .filter(|signature| {
+                self.key_ids
+                    .contains(&normalize_key_id(self.scheme, &signature.key_id))
+            })
+            .peekable();
+        if allowed.peek().is_none() {
+            return Err(ResourceError::UnexpectedSignatureKey {
+                scheme: self.scheme,
+                key_id: first.key_id.clone(),
+            });
+        }
+
+        if allowed.any(|signature| signature.digest.as_ref() == Some(content)) {
+            Ok(())
+        } else {
+            Err(ResourceError::SignedDigestMismatch {
+                scheme: self.scheme,
+                digest: content.hex(),
+            })
+        }
+    }
+}
+
+fn normalize_key_id(scheme: SignatureScheme, value: &str) -> String {
+    if scheme == SignatureScheme::Sigstore {
+        return value.trim().to_string();
+    }
+
+    value
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(|ch| ch.to_ascii_uppercase())
+        .collect()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -320,6 +430,7 @@ pub enum TrustAnchor {
     Digest(ValidDigest),
     Host(String),
     Metadata { key: String, value: String },
+    Minisign(MinisignKey),
 }
 
 impl TrustAnchor {
@@ -336,6 +447,113 @@ impl TrustAnchor {
         ensure_non_empty(&value)?;
         Ok(Self::Metadata { key, value })
     }
+
+    /// Anchor on signatures made by this minisign or signify public key.
+    ///
+    /// Matches on the full public key, not on the key id, which the signer
+    /// picks freely, and only when the signature covers content with the
+    /// artifact's recorded digest. See [`MinisignKey::parse`] for the
+    /// accepted formats.
+    pub fn minisign(public_key: impl AsRef<str>) -> Result<Self> {
+        MinisignKey::parse(public_key).map(Self::Minisign)
+    }
+}
+
+/// Ed25519 public key in minisign/signify format.
+///
+/// Accepts either the full `.pub` file (untrusted comment plus key line) or
+/// the bare base64 key line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinisignKey {
+    key_id: [u8; 8],
+    public_key: [u8; 32],
+}
+
+impl MinisignKey {
+    /// Parse a `.pub` file or its bare base64 key line.
+    ///
+    /// Only the `Ed` algorithm is accepted; the untrusted comment is ignored.
+    pub fn parse(value: impl AsRef<str>) -> Result<Self> {
+        use base64::Engine as _;
+
+        let line = value
+            .as_ref()
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+            .ok_or(ResourceError::EmptyValue)?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(line)
+            .map_err(|err| ResourceError::InvalidMinisignKey(err.to_string()))?;
+
+        let invalid_length =
+            || ResourceError::InvalidMinisignKey(format!("unexpected key length {}", bytes.len()));
+        let (algorithm, rest) = bytes.split_first_chunk::<2>().ok_or_else(invalid_length)?;
+        if algorithm != b"Ed" {
+            return Err(ResourceError::InvalidMinisignKey(
+                "unsupported key algorithm".to_string(),
+            ));
+        }
+        let (key_id, public_key) = rest.split_first_chunk::<8>().ok_or_else(invalid_length)?;
+        let public_key = <[u8; 32]>::try_from(public_key).map_err(|_| invalid_length())?;
+        let key_id = *key_id;
+
+        Ok(Self { key_id, public_key })
+    }
+
+    /// Key id as printed by minisign, e.g. `E7620F1842B4E81F`.
+    pub fn key_id(&self) -> String {
+        format_minisign_key_id(&self.key_id)
+    }
+
+    pub fn public_key(&self) -> &[u8; 32] {
+        &self.public_key
+    }
+
+    /// Lowercase hex of the Ed25519 public key, as recorded in
+    /// [`VerifiedSignature::public_key`].
+    pub fn fingerprint(&self) -> String {
+        hex::encode(self.public_key)
+    }
+}
+
+/// Formats a raw little-endian minisign key id the way minisign prints it.
+pub fn format_minisign_key_id(key_id: &[u8; 8]) -> String {
+    format!("{:016X}", u64::from_le_bytes(*key_id))
+}
+
+/// Key id of a Sigstore signature: the OIDC issuer that authenticated the
+/// signer and the certificate identity, as `<issuer>|<identity>`.
+pub fn sigstore_key_id(issuer: &str, identity: &str) -> String {
+    format!("{issuer}|{identity}")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureScheme {
+    Minisign,
+    OpenPgp,
+    /// Sigstore keyless signing; the key id is the issuer and certificate
+    /// identity.
+    Sigstore,
+}
+
+/// Metadata key for the identity (SAN) of a verified Sigstore certificate.
+pub const SIGSTORE_IDENTITY_METADATA_KEY: &str = "sigstore.identity";
+/// Metadata key for the OIDC issuer of a verified Sigstore certificate.
+pub const SIGSTORE_ISSUER_METADATA_KEY: &str = "sigstore.issuer";
+
+/// A detached signature that has already been checked against the artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedSignature {
+    pub scheme: SignatureScheme,
+    pub key_id: String,
+    /// Hex of the full public key that verified the signature, for schemes
+    /// whose key id is chosen by the signer (minisign).
+    #[serde(default)]
+    pub public_key: Option<String>,
+    /// Digest of the content the signature was verified against.
+    #[serde(default)]
+    pub digest: Option<ValidDigest>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -504,6 +722,8 @@ pub struct ArtifactDescriptor {
     pub digest: Option<ValidDigest>,
     pub file_name: Option<String>,
     pub metadata: Metadata,
+    #[serde(default)]
+    pub signatures: Vec<VerifiedSignature>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -732,6 +952,42 @@ impl ResolvedResource {
 
         Ok(())
     }
+
+    /// Checks a [`VerificationRequirement::Signature`] against the signatures
+    /// recorded on the resolved artifact, which must have been verified over
+    /// content with the artifact's recorded digest. Other requirements pass
+    /// unchanged.
+    pub fn validate_signatures(&self) -> Result<()> {
+        let VerificationRequirement::Signature(requirement) = &self.spec.verification else {
+            return Ok(());
+        };
+
+        let digest = self
+            .state
+            .artifact
+            .as_ref()
+            .and_then(|artifact| artifact.digest.as_ref())
+            .ok_or(ResourceError::MissingArtifactDigest {
+                scheme: requirement.scheme,
+            })?;
+        self.validate_signatures_for(digest)
+    }
+
+    /// Like [`ResolvedResource::validate_signatures`], but binds the
+    /// signature to `content`, the digest of the bytes actually being used.
+    pub fn validate_signatures_for(&self, content: &ValidDigest) -> Result<()> {
+        let VerificationRequirement::Signature(requirement) = &self.spec.verification else {
+            return Ok(());
+        };
+
+        let signatures = self
+            .state
+            .artifact
+            .as_ref()
+            .map(|artifact| artifact.signatures.as_slice())
+            .unwrap_or_default();
+        requirement.validate(signatures, content)
+    }
 }
 
 fn parse_resolved_version(version: &ResolvedVersion) -> Result<VersionKind> {
@@ -780,6 +1036,16 @@ fn anchor_matches(
         TrustAnchor::Metadata { key, value } => {
             metadata.get(key).is_some_and(|found| found == value)
         }
+        TrustAnchor::Minisign(key) => artifact.is_some_and(|artifact| {
+            let Some(content) = artifact.digest.as_ref() else {
+                return false;
+            };
+            artifact.signatures.iter().any(|signature| {
+                signature.scheme == SignatureScheme::Minisign
+                    && signature.public_key.as_deref() == Some(key.fingerprint().as_str())
+                    && signature.digest.as_ref() == Some(content)
+            })
+        }),
     }
 }
 
@@ -1038,12 +1304,114 @@ mod tests {
                 digest: Some(digest),
                 file_name: Some("node.zip".to_string()),
                 metadata: Metadata::new(),
+                signatures: Vec::new(),
             }),
         );
 
         assert_eq!(resolved.trust_decision(), TrustDecision::Trusted);
     }
 
+    #[test]
+    fn minisign_key_parses_public_key_file() {
+        let key = MinisignKey::parse(
+            "untrusted comment: minisign public key E7620F1842B4E81F\n\
+             RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3\n",
+        )
+        .unwrap();
+
+        assert_eq!(key.key_id(), "E7620F1842B4E81F");
+        assert!(matches!(
+            MinisignKey::parse("RWQf6LRCGA9i53mlYecO"),
+            Err(ResourceError::InvalidMinisignKey(_))
+        ));
+    }
+
+    #[test]
+    fn trust_policy_can_require_minisign_signature() {
+        let policy = TrustPolicy {
+            mode: TrustMode::RequireAnchorMatch,
+            anchors: vec![
+                TrustAnchor::minisign("RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3")
+                    .unwrap(),
+            ],
+        };
+        let content = ValidDigest::from_bytes(DigestAlgorithm::Sha256, vec![7; 32]).unwrap();
+        let mut artifact = ArtifactDescriptor {
+            digest: Some(content.clone()),
+            file_name: Some("tool.tar.gz".to_string()),
+            metadata: Metadata::new(),
+            signatures: Vec::new(),
+        };
+
+        assert_eq!(
+            policy.evaluate(
+                None,
+                Some(&artifact),
+                &Metadata::new(),
+                &VerificationRequirement::None
+            ),
+            TrustDecision::Untrusted("no trust anchor matched")
+        );
+
+        // Same signer-chosen key id, different key.
+        artifact.signatures.push(VerifiedSignature {
+            scheme: SignatureScheme::Minisign,
+            key_id: "E7620F1842B4E81F".to_string(),
+            public_key: Some("00".repeat(32)),
+            digest: Some(content.clone()),
+        });
+        assert_eq!(
+            policy.evaluate(
+                None,
+                Some(&artifact),
+                &Metadata::new(),
+                &VerificationRequirement::None
+            ),
+            TrustDecision::Untrusted("no trust anchor matched")
+        );
+
+        artifact.signatures[0].public_key = Some(
+            MinisignKey::parse("RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3")
+                .unwrap()
+                .fingerprint(),
+        );
+        assert_eq!(
+            policy.evaluate(
+                None,
+                Some(&artifact),
+                &Metadata::new(),
+                &VerificationRequirement::None
+            ),
+            TrustDecision::Trusted
+        );
+
+        // Pinned key, but the signature covers other content.
+        artifact.signatures[0].digest =
+            Some(ValidDigest::from_bytes(DigestAlgorithm::Sha256, vec![8; 32]).unwrap());
+        assert_eq!(
+            policy.evaluate(
+                None,
+                Some(&artifact),
+                &Metadata::new(),
+                &VerificationRequirement::None
+            ),
+            TrustDecision::Untrusted("no trust anchor matched")
+        );
+
+        // No recorded artifact digest to bind the signature to.
+        artifact.signatures[0].digest = Some(content);
+        artifact.digest = None;
+        assert_eq!(
+            policy.evaluate(
+                None,
+                Some(&artifact),
+                &Metadata::new(),
+                &VerificationRequirement::None
+            ),
+            TrustDecision::Untrusted("no trust anchor matched")
+        );
+    }
+
     #[test]
     fn resource_behavior_contract_has_explicit_defaults() {
         let spec = ResourceSpec::new(
@@ -1114,4 +1482,152 @@ mod tests {
 
         assert_eq!(resolved.behavior_contract(), expected);
     }
+
+    #[test]
+    fn signature_requirement_rejects_unsigned_and_wrongly_signed_artifacts() {
+        let requirement = SignatureRequirement::new(
+            SignatureScheme::OpenPgp,
+            ["30B6 5883 B216 EDD8 1528  A31A DEF6 B49A A247 4D3E"],
+        )
+        .unwrap();
+        let spec = ResourceSpec::new(
+            ResourceId::parse("nodejs.org/node").unwrap(),
+            ResourceLocator::Url(ValidUrl::parse("https://nodejs.org/node.tar.gz").unwrap()),
+        )
+        .verification(VerificationRequirement::Signature(requirement));
+        let digest = |byte: u8| ValidDigest::from_bytes(DigestAlgorithm::Sha256, vec![byte; 32]);
+        let resolve_signed = |key_id: Option<&str>, signed: u8, recorded: Option<u8>| {
+            RequestedResource::new(spec.clone()).resolve(
+                ResolvedVersion::new("20.12.1").unwrap(),
+                ResolvedLocator::Url(ValidUrl::parse("https://nodejs.org/node.tar.gz").unwrap()),
+                Some(ArtifactDescriptor {
+                    digest: recorded.map(|byte| digest(byte).unwrap()),
+                    file_name: Some("node.tar.gz".to_string()),
+                    metadata: Metadata::new(),
+                    signatures: key_id
+                        .map(|key_id| VerifiedSignature {
+                            scheme: SignatureScheme::OpenPgp,
+                            key_id: key_id.to_string(),
+                            public_key: None,
+                            digest: Some(digest(signed).unwrap()),
+                        })
+                        .into_iter()
+                        .collect(),
+                }),
+            )
+        };
+        let resolve = |key_id: Option<&str>| resolve_signed(key_id, 1, Some(1));
+
+        assert_eq!(
+            resolve(None).validate_signatures(),
+            Err(ResourceError::MissingSignature {
+                scheme: SignatureScheme::OpenPgp
+            })
+        );
+        assert_eq!(
+            resolve(Some("19ABC8E3C4AF96F7B8B85834F5B319863F59BD65")).validate_signatures(),
+            Err(ResourceError::UnexpectedSignatureKey {
+                scheme: SignatureScheme::OpenPgp,
+                key_id: "19ABC8E3C4AF96F7B8B85834F5B319863F59BD65".to_string(),
+            })
+        );
+        assert!(
+            resolve(Some("30B65883B216EDD81528A31ADEF6B49AA2474D3E"))
+                .validate_signatures()
+                .is_ok()
+        );
+
+        // The signature must cover the bytes being used, not just any bytes.
+        let allowed = Some("30B65883B216EDD81528A31ADEF6B49AA2474D3E");
+        assert!(matches!(
+            resolve_signed(allowed, 2, Some(1)).validate_signatures(),
+            Err(ResourceError::SignedDigestMismatch { .. })
+        ));
+        assert_eq!(
+            resolve_signed(allowed, 1, None).validate_signatures(),
+            Err(ResourceError::MissingArtifactDigest {
+                scheme: SignatureScheme::OpenPgp
+            })
+        );
+        let unbound = resolve_signed(allowed, 1, None);
+        assert!(unbound.validate_signatures_for(&digest(1).unwrap()).is_ok());
+        assert!(
+            unbound
+                .validate_signatures_for(&digest(2).unwrap())
+                .is_err()
+        );
+        assert_eq!(
+            SignatureRequirement::new(SignatureScheme::OpenPgp, Vec::<String>::new()),
+            Err(ResourceError::EmptySignatureKeys)
+        );
+    }
+
+    #[test]
+    fn sigstore_identity_is_matched_exactly_and_exposed_as_metadata() {
+        let issuer = "https://token.actions.githubusercontent.com";
+        let identity = sigstore_key_id(
+            issuer,
+            "https://github.com/Example/tool/.github/workflows/release.yml@refs/tags/v1",
+        );
+        let requirement =
+            SignatureRequirement::new(SignatureScheme::Sigstore, [&identity]).unwrap();
+        let content = ValidDigest::from_bytes(DigestAlgorithm::Sha256, vec![7; 32]).unwrap();
+        let signed = |key_id: &str| {
+            vec![VerifiedSignature {
+                scheme: SignatureScheme::Sigstore,
+                key_id: key_id.to_string(),
+                public_key: None,
+                digest: Some(content.clone()),
+            }]
+        };
+
+        assert!(requirement.validate(&signed(&identity), &content).is_ok());
+        assert!(
+            requirement
+                .validate(&signed(&identity.to_ascii_uppercase()), &content)
+                .is_err()
+        );
+        let other_issuer = sigstore_key_id(
+            "https://accounts.example.com",
+            "https://github.com/Example/tool/.github/workflows/release.yml@refs/tags/v1",
+        );
+        assert!(
+            requirement
+                .validate(&signed(&other_issuer), &content)
+                .is_err()
+        );
+        assert_eq!(
+            SignatureRequirement::new(
+                SignatureScheme::Sigstore,
+                ["https://github.com/Example/tool/.github/workflows/release.yml@refs/tags/v1"]
+            ),
+            Err(ResourceError::InvalidSigstoreKeyId(
+                "https://github.com/Example/tool/.github/workflows/release.yml@refs/tags/v1"
+                    .to_string()
+            ))
+        );
+
+        let policy = TrustPolicy {
+            mode: TrustMode::RequireAnchorMatch,
+            anchors: vec![
+                TrustAnchor::metadata(
+                    SIGSTORE_ISSUER_METADATA_KEY,
+                    "https://token.actions.githubusercontent.com",
+                )
+                .unwrap(),
+            ],
+        };
+        let facts = Metadata::from([(
+            SIGSTORE_ISSUER_METADATA_KEY.to_string(),
+            "https://token.actions.githubusercontent.com".to_string(),
+        )]);
+        assert_eq!(
+            policy.evaluate(None, None, &facts, &VerificationRequirement::None),
+            TrustDecision::Trusted
+        );
+        assert_eq!(
+            policy.evaluate(None, None, &Metadata::new(), &VerificationRequirement::None),
+            TrustDecision::Untrusted("no trust anchor matched")
+        );
+    }
 }
@@ -4,7 +4,10 @@ use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use pulith_resource::{RequestedResource, ResolvedResource, ResourceLocator, ValidUrl};
+use pulith_resource::{
+    DigestAlgorithm, RequestedResource, ResolvedResource, ResourceLocator, SignatureRequirement,
+    ValidUrl,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -18,6 +21,16 @@ pub enum SourceError {
     EmptyMirrorSet,
     #[error("path must not be empty")]
     EmptyPath,
+    #[error("no release matches version `{0}`")]
+    ReleaseNotFound(String),
+    #[error("release `{release}` has no asset named `{asset}`")]
+    AssetNotFound { release: String, asset: String },
+    #[error("invalid OCI reference: {0}")]
+    InvalidOciReference(String),
+    #[error("invalid S3 object: {0}")]
+    InvalidS3Object(String),
+    #[error("invalid git source: {0}")]
+    InvalidGitSource(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,6 +39,39 @@ pub struct HttpAssetSource {
     pub file_name: Option<String>,
 }
 
+/// Upstream checksum file (`SHA256SUMS`, `*.sha256`, `checksums.txt`) that
+/// lists the expected digest of release artifacts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecksumManifestSource {
+    pub url: ValidUrl,
+    pub algorithm: DigestAlgorithm,
+    pub signature: Option<ManifestSignature>,
+}
+
+impl ChecksumManifestSource {
+    pub fn new(url: ValidUrl, algorithm: DigestAlgorithm) -> Self {
+        Self {
+            url,
+            algorithm,
+            signature: None,
+        }
+    }
+
+    /// Require the manifest to carry a valid detached signature before any
+    /// digest is taken from it.
+    #[must_use]
+    pub fn with_signature(mut self, url: ValidUrl, requirement: SignatureRequirement) -> Self {
+        self.signature = Some(ManifestSignature { url, requirement });
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestSignature {
+    pub url: ValidUrl,
+    pub requirement: SignatureRequirement,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SourcePath(String);
 
@@ -79,11 +125,233 @@ pub struct GitSource {
     pub subpath: Option<PathBuf>,
 }
 
+impl GitSource {
+    /// Refuse revisions git would parse as options, such as
+    /// `--upload-pack=<cmd>`.
+    pub fn new(url: ValidUrl, rev: Option<String>) -> Result<Self> {
+        if url.as_url().as_str().starts_with('-') {
+            return Err(SourceError::InvalidGitSource(format!("url `{url}`")));
+        }
+        if let Some(rev) = &rev
+            && (rev.is_empty() || rev.starts_with('-'))
+        {
+            return Err(SourceError::InvalidGitSource(format!("rev `{rev}`")));
+        }
+        Ok(Self {
+            url,
+            rev,
+            subpath: None,
+        })
+    }
+
+    #[must_use]
+    pub fn with_subpath(mut self, subpath: impl Into<PathBuf>) -> Self {
+        self.subpath = Some(subpath.into());
+        self
+    }
+}
+
+/// Manifest reference inside an OCI repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OciReference {
+    Tag(String),
+    /// Content digest in `sha256:<hex>` form.
+    Digest(String),
+}
+
+impl OciReference {
+    /// Parse `sha256:<hex>` as a digest and anything else as a tag.
+    pub fn parse(value: impl Into<String>) -> Result<Self> {
+        let value = value.into();
+        if let Some(hex) = value.strip_prefix("sha256:") {
+            if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(SourceError::InvalidOciReference(value));
+            }
+            return Ok(Self::Digest(value.to_ascii_lowercase()));
+        }
+
+        let valid_tag = !value.is_empty()
+            && value.len() <= 128
+            && !value.starts_with(['.', '-'])
+            && value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'.' | b'-'));
+        if valid_tag {
+            Ok(Self::Tag(value))
+        } else {
+            Err(SourceError::InvalidOciReference(value))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Tag(value) | Self::Digest(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for OciReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Artifact stored in an OCI distribution registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OciSource {
+    /// Registry base URL, e.g. `https://ghcr.io`.
+    pub registry: ValidUrl,
+    pub repository: String,
+    pub reference: OciReference,
+    /// Only layers with this media type are considered.
+    pub media_type: Option<String>,
+}
+
+impl OciSource {
+    pub fn new(
+        registry: ValidUrl,
+        repository: impl Into<String>,
+        reference: OciReference,
+    ) -> Result<Self> {
+        let repository = repository.into();
+        let valid_repository = !repository.is_empty()
+            && repository.split('/').all(|part| {
+                !part.is_empty()
+                    && part.bytes().all(|b| {
+                        b.is_ascii_lowercase()
+                            || b.is_ascii_digit()
+                            || matches!(b, b'.' | b'_' | b'-')
+                    })
+            });
+        if !valid_repository {
+            return Err(SourceError::InvalidOciReference(repository));
+        }
+
+        Ok(Self {
+            registry,
+            repository,
+            reference,
+            media_type: None,
+        })
+    }
+
+    #[must_use]
+    pub fn with_media_type(mut self, media_type: impl Into<String>) -> Self {
+        self.media_type = Some(media_type.into());
+        self
+    }
+}
+
+/// Object in an S3-compatible bucket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct S3Source {
+    /// Service endpoint, e.g. `https://s3.eu-west-1.amazonaws.com` or
+    /// `http://minio.internal:9000`.
+    pub endpoint: ValidUrl,
+    pub bucket: String,
+    pub key: String,
+    /// Signing region; MinIO accepts `us-east-1` unless configured otherwise.
+    pub region: String,
+    /// Address the bucket as a subdomain of the endpoint host instead of the
+    /// first path segment.
+    pub virtual_hosted_style: bool,
+}
+
+impl S3Source {
+    pub fn new(
+        endpoint: ValidUrl,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+        region: impl Into<String>,
+    ) -> Result<Self> {
+        let source = Self {
+            endpoint,
+            bucket: bucket.into(),
+            key: key.into(),
+            region: region.into(),
+            virtual_hosted_style: false,
+        };
+        source.validate()?;
+        Ok(source)
+    }
+
+    /// Check the bucket, key and region. The fields are public and may come
+    /// from deserialization, so [`S3Source::object_url`] checks again.
+    pub fn validate(&self) -> Result<()> {
+        let bucket = &self.bucket;
+        let valid_bucket = (3..=63).contains(&bucket.len())
+            && bucket
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'.' | b'-'))
+            && bucket.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && bucket.ends_with(|c: char| c.is_ascii_alphanumeric());
+        if !valid_bucket {
+            return Err(SourceError::InvalidS3Object(format!("bucket `{bucket}`")));
+        }
+        // URL parsing would resolve dot segments and address another object.
+        let key = &self.key;
+        if key.is_empty()
+            || key.starts_with('/')
+            || key.split('/').any(|segment| matches!(segment, "." | ".."))
+        {
+            return Err(SourceError::InvalidS3Object(format!("key `{key}`")));
+        }
+        if self.region.is_empty() {
+            return Err(SourceError::InvalidS3Object("empty region".to_string()));
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn with_virtual_hosted_style(mut self) -> Self {
+        self.virtual_hosted_style = true;
+        self
+    }
+
+    /// URL of the object, with the key percent-encoded per path segment.
+    pub fn object_url(&self) -> Result<ValidUrl> {
+        self.validate()?;
+        let mut url = self.endpoint.as_url().clone();
+        let mut path = url.path().trim_end_matches('/').to_string();
+        if self.virtual_hosted_style {
+            let host = url.host_str().unwrap_or_default();
+            let host = format!("{}.{host}", self.bucket);
+            url.set_host(Some(&host))
+                .map_err(|error| SourceError::InvalidS3Object(error.to_string()))?;
+        } else {
+            path.push('/');
+            path.push_str(&self.bucket);
+        }
+        for segment in self.key.split('/') {
+            path.push('/');
+            path.push_str(&encode_key_segment(segment));
+        }
+        url.set_path(&path);
+        ValidUrl::parse(url.as_str())
+            .map_err(|error| SourceError::InvalidS3Object(error.to_string()))
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn encode_key_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RemoteSource {
     HttpAsset(HttpAssetSource),
     Mirror(MirrorSource),
     Git(GitSource),
+    Oci(OciSource),
+    S3(S3Source),
 }
 
 impl RemoteSource {
@@ -108,6 +376,8 @@ impl RemoteSource {
                 rev: source.rev.clone(),
                 subpath: source.subpath.clone(),
             }],
+            Self::Oci(source) => vec![ResolvedSourceCandidate::Oci(source.clone())],
+            Self::S3(source) => vec![ResolvedSourceCandidate::S3(source.clone())],
         }
     }
 }
@@ -240,6 +510,8 @@ pub enum ResolvedSourceCandidate {
         rev: Option<String>,
         subpath: Option<PathBuf>,
     },
+    Oci(OciSource),
+    S3(S3Source),
 }
 
 impl ResolvedSourceCandidate {
@@ -439,4 +711,131 @@ mod tests {
         let expanded = PassthroughAdapter.expand(&resource, &definition).unwrap();
         assert_eq!(expanded.entries().len(), 1);
     }
+
+    #[test]
+    fn oci_source_validates_reference_and_repository() {
+        let digest = format!("sha256:{}", "AB".repeat(32));
+        assert_eq!(
+            OciReference::parse(&digest).unwrap(),
+            OciReference::Digest(digest.to_ascii_lowercase())
+        );
+        assert_eq!(
+            OciReference::parse("1.2.0-linux_amd64").unwrap(),
+            OciReference::Tag("1.2.0-linux_amd64".to_string())
+        );
+        assert!(OciReference::parse("sha256:abc").is_err());
+        assert!(OciReference::parse("-bad").is_err());
+        assert!(OciReference::parse("a/b").is_err());
+
+        let registry = ValidUrl::parse("https://ghcr.io").unwrap();
+        let source = OciSource::new(
+            registry.clone(),
+            "example/toolchains/clang",
+            OciReference::parse("18.1.0").unwrap(),
+        )
+        .unwrap()
+        .with_media_type("application/vnd.example.toolchain.tar+gzip");
+        assert!(
+            OciSource::new(registry.clone(), "Example/Tool", source.reference.clone()).is_err()
+        );
+        assert!(OciSource::new(registry, "example//tool", source.reference.clone()).is_err());
+
+        let planned = SourceSpec::new(
+            SourceSet::new(vec![SourceDefinition::Remote(RemoteSource::Oci(
+                source.clone(),
+            ))])
+            .unwrap(),
+        )
+        .plan(SelectionStrategy::OrderedFallback);
+        assert_eq!(planned.candidates(), [ResolvedSourceCandidate::Oci(source)]);
+    }
+
+    #[test]
+    fn git_source_rejects_option_like_revisions() {
+        let url = ValidUrl::parse("https://example.com/tool.git").unwrap();
+        let source = GitSource::new(url.clone(), Some("v1.0".to_string()))
+            .unwrap()
+            .with_subpath("bin");
+        assert_eq!(source.subpath, Some(PathBuf::from("bin")));
+        assert!(GitSource::new(url.clone(), None).is_ok());
+        assert_eq!(
+            GitSource::new(url.clone(), Some("--upload-pack=touch /tmp/x".to_string())),
+            Err(SourceError::InvalidGitSource(
+                "rev `--upload-pack=touch /tmp/x`".to_string()
+            ))
+        );
+        assert!(GitSource::new(url, Some(String::new())).is_err());
+    }
+
+    #[test]
+    fn s3_source_validates_and_builds_object_urls() {
+        let endpoint = ValidUrl::parse("http://minio.internal:9000").unwrap();
+        let source = S3Source::new(
+            endpoint.clone(),
+            "tools",
+            "linux/tool 1.0+x.tar.gz",
+            "us-east-1",
+        )
+        .unwrap();
+        assert_eq!(
+            source.object_url().unwrap().as_url().as_str(),
+            "http://minio.internal:9000/tools/linux/tool%201.0%2Bx.tar.gz"
+        );
+        assert_eq!(
+            source
+                .clone()
+                .with_virtual_hosted_style()
+                .object_url()
+                .unwrap()
+                .as_url()
+                .as_str(),
+            "http://tools.minio.internal:9000/linux/tool%201.0%2Bx.tar.gz"
+        );
+
+        assert!(S3Source::new(endpoint.clone(), "Tools", "a", "us-east-1").is_err());
+        assert!(S3Source::new(endpoint.clone(), "-tools", "a", "us-east-1").is_err());
+        assert!(S3Source::new(endpoint.clone(), "tools", "", "us-east-1").is_err());
+        assert!(S3Source::new(endpoint.clone(), "tools", "a", "").is_err());
+        for key in ["../other/secret", "linux/./tool", "linux/.."] {
+            assert!(S3Source::new(endpoint.clone(), "tools", key, "us-east-1").is_err());
+        }
+        let mut deserialized = source.clone();
+        deserialized.key = "linux/../../other-bucket/secret".to_string();
+        assert!(matches!(
+            deserialized.object_url(),
+            Err(SourceError::InvalidS3Object(_))
+        ));
+
+        let planned = SourceSpec::new(
+            SourceSet::new(vec![SourceDefinition::Remote(RemoteSource::S3(
+                source.clone(),
+            ))])
+            .unwrap(),
+        )
+        .plan(SelectionStrategy::OrderedFallback);
+        assert_eq!(planned.candidates(), [ResolvedSourceCandidate::S3(source)]);
+    }
+
+    #[test]
+    fn checksum_manifest_source_records_signature_requirement() {
+        let manifest = ChecksumManifestSource::new(
+            ValidUrl::parse("https://nodejs.org/dist/v20.12.1/SHASUMS256.txt").unwrap(),
+            DigestAlgorithm::Sha256,
+        );
+        assert_eq!(manifest.signature, None);
+
+        let requirement = SignatureRequirement::new(
+            pulith_resource::SignatureScheme::OpenPgp,
+            ["4ED778F539E3634C779C87C6D7062848A1AB005C"],
+        )
+        .unwrap();
+        let manifest = manifest.with_signature(
+            ValidUrl::parse("https://nodejs.org/dist/v20.12.1/SHASUMS256.txt.sig").unwrap(),
+            requirement.clone(),
+        );
+
+        let signature = manifest.signature.unwrap();
+        assert_eq!(signature.requirement, requirement);
+        assert!(signature.url.as_url().path().ends_with(".sig"));
+    }
 }
@@ -591,6 +591,7 @@ impl StoreProvenance {
         let origin = match &receipt.source {
             FetchSource::Url(url) => Some(url.clone()),
             FetchSource::LocalPath(path) => Some(path.to_string_lossy().into_owned()),
+            FetchSource::Git { url, .. } => Some(url.clone()),
         };
 
         let metadata = Self::fetch_metadata(receipt);
@@ -621,6 +622,9 @@ impl StoreProvenance {
         if let Some(sha256_hex) = &receipt.sha256_hex {
             metadata.insert("fetch.sha256".to_string(), sha256_hex.clone());
         }
+        if let FetchSource::Git { commit, .. } = &receipt.source {
+            metadata.insert("fetch.git_commit".to_string(), commit.clone());
+        }
         metadata
     }
 
@@ -750,6 +754,12 @@ mod tests {
             bytes_downloaded: 12,
             total_bytes: Some(12),
             sha256_hex: Some("abc123".to_string()),
+            signatures: Vec::new(),
+            selection: None,
+            cache_hit: false,
+            revalidated: false,
+            coalesced: false,
+            attempts: Vec::new(),
         };
 
         let provenance = StoreProvenance::from_fetch_receipt(&receipt);
@@ -804,6 +814,12 @@ mod tests {
             bytes_downloaded: 12,
             total_bytes: Some(12),
             sha256_hex: Some("abc123".to_string()),
+            signatures: Vec::new(),
+            selection: None,
+            cache_hit: false,
+            revalidated: false,
+            coalesced: false,
+            attempts: Vec::new(),
         };
         let report = ArchiveReport {
             format: ArchiveFormat::Zip,